@@ -0,0 +1,85 @@
+use crate::redact::redact_secrets;
+use serde_json::Value;
+
+const MAX_ERROR_MESSAGE_CHARS: usize = 200;
+
+fn read_message(value: Option<&Value>) -> Option<&str> {
+    let s = value?.as_str()?.trim();
+    if s.is_empty() {
+        None
+    } else {
+        Some(s)
+    }
+}
+
+/// Extract a human-readable message from a provider error body.
+///
+/// Known shapes:
+/// - Anthropic: `{"type":"error","error":{"type":"...","message":"..."}}`
+/// - OpenAI/ChatGPT: `{"error":{"message":"..."}}`, `{"error":"..."}` or `{"detail":"..."}`
+///
+/// The message is redacted and truncated; bodies that are not JSON (e.g. HTML error pages) yield
+/// `None` so callers fall back to the generic status message.
+pub(crate) fn error_message_from_body(body: &str) -> Option<String> {
+    let json: Value = serde_json::from_str(body.trim()).ok()?;
+    let root = json.as_object()?;
+
+    let message = match root.get("error") {
+        Some(Value::Object(error)) => read_message(error.get("message")),
+        Some(other) => read_message(Some(other)),
+        None => None,
+    }
+    .or_else(|| read_message(root.get("detail")))
+    .or_else(|| read_message(root.get("message")))?;
+
+    let redacted = redact_secrets(message);
+    let mut out: String = redacted.chars().take(MAX_ERROR_MESSAGE_CHARS).collect();
+    if redacted.chars().count() > MAX_ERROR_MESSAGE_CHARS {
+        out.push('…');
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_anthropic_error_fixture_message() {
+        let body = include_str!("fixtures/claude_error_overloaded.json");
+        assert_eq!(error_message_from_body(body).as_deref(), Some("Overloaded"));
+    }
+
+    #[test]
+    fn reads_openai_style_error_shapes() {
+        assert_eq!(
+            error_message_from_body(r#"{"error":{"message":"Teapot says no"}}"#).as_deref(),
+            Some("Teapot says no")
+        );
+        assert_eq!(
+            error_message_from_body(r#"{"detail":"Service unavailable"}"#).as_deref(),
+            Some("Service unavailable")
+        );
+    }
+
+    #[test]
+    fn ignores_non_json_and_empty_messages() {
+        assert_eq!(
+            error_message_from_body("<html>502 Bad Gateway</html>"),
+            None
+        );
+        assert_eq!(
+            error_message_from_body(r#"{"error":{"message":"  "}}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn redacts_secrets_in_error_messages() {
+        let out = error_message_from_body(
+            r#"{"error":{"message":"bad cookie sessionKey=sk-ant-sid01-abc123"}}"#,
+        )
+        .unwrap();
+        assert!(!out.contains("abc123"));
+    }
+}
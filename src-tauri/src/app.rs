@@ -1,14 +1,30 @@
-use crate::claude::ClaudeApiClient;
-use crate::codex::CodexApiClient;
+use crate::claude::{
+    normalize_session_key, resolve_base_url, ClaudeApiClient, CLAUDE_BASE_URL_ENV,
+    CLAUDE_SESSION_KEY_ENV,
+};
+use crate::codex::{normalize_codex_cookie, CodexApiClient, CODEX_COOKIE_ENV};
 use crate::commands;
+use crate::http_client::HttpTimeouts;
+use crate::metrics;
+use crate::notifications::notify_plain;
 use crate::refresh;
-use crate::settings::SettingsStore;
+use crate::settings::{
+    SettingsStore, KEY_CLAUDE_BASE_URL, KEY_HTTP_PROXY, KEY_HTTP_TIMEOUT_SECONDS,
+    KEY_METRICS_ENABLED, KEY_METRICS_PORT, KEY_POLLING_PAUSED, KEY_REFRESH_ON_WAKE,
+};
+use crate::staleness::{now_unix_ms, LastSuccess};
 use crate::state::{
-    AppState, DebugOverride, RefreshBus, SecretManager, KEYRING_USER_CLAUDE_SESSION_KEY,
+    load_cached_snapshot, AppState, DebugOverride, RefreshBus, SecretManager,
+    KEYRING_USER_CLAUDE_SESSION_KEY, KEYRING_USER_CODEX_COOKIE,
+    LEGACY_KEYRING_USERS_CLAUDE_SESSION_KEY,
 };
 use crate::tray::{self, TrayUi};
+use crate::types::IpcResult;
 use std::collections::HashMap;
 use tauri::Manager;
+use tauri_plugin_clipboard_manager::ClipboardExt as _;
+use tauri_plugin_dialog::DialogExt as _;
+use tauri_plugin_opener::OpenerExt as _;
 use tokio::sync::mpsc;
 
 fn format_rfc3339(dt: time::OffsetDateTime) -> String {
@@ -24,6 +40,11 @@ fn debug_resets_at(base: time::OffsetDateTime) -> (String, String) {
 
 pub fn run() {
     let app = tauri::Builder::default()
+        // Must be registered first: a second launch (e.g. login item + manual start) would
+        // otherwise add another tray and poll twice. Focus the running instance instead.
+        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            crate::windows::open_settings_window_or_report(app);
+        }))
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_store::Builder::default().build())
         .plugin(tauri_plugin_autostart::init(
@@ -31,13 +52,36 @@ pub fn run() {
             None,
         ))
         .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .invoke_handler(tauri::generate_handler![
             commands::settings_get_state,
+            commands::settings_get_snapshot,
+            commands::get_provider_capabilities,
+            commands::get_codex_auto_resolution,
             commands::settings_save,
             commands::settings_forget_key,
             commands::settings_forget_claude_key,
             commands::settings_refresh_now,
+            commands::set_codex_source,
+            commands::select_organization,
+            commands::set_tray_colors,
+            commands::set_tray_title_template,
+            commands::emit_latest_snapshot,
+            commands::export_support_bundle,
+            commands::export_usage_json,
+            commands::export_settings,
+            commands::import_settings,
+            commands::reset_all_settings,
+            commands::test_claude_session_key,
+            commands::test_codex_cookie,
+            commands::clear_usage_data,
+            commands::get_usage_history,
+            commands::get_burn_rates,
+            commands::debug_export_menu_png,
+            // Debug-only: refuses to run unless CLAUDOMETER_DEBUG is set.
+            commands::fetch_raw_usage,
             commands::open_settings,
             commands::check_for_updates,
         ])
@@ -45,7 +89,22 @@ pub fn run() {
             let id = event.id().as_ref();
             match id {
                 tray::ITEM_OPEN_SETTINGS => {
-                    let _ = crate::windows::open_settings_window(app);
+                    crate::windows::open_settings_window_or_report(app);
+                }
+                tray::ITEM_OPEN_CLAUDE_USAGE_PAGE | tray::ITEM_OPEN_CODEX_USAGE_PAGE => {
+                    let state = app.state::<AppState<tauri::Wry>>();
+                    let url = if id == tray::ITEM_OPEN_CLAUDE_USAGE_PAGE {
+                        state.claude_usage_page_url()
+                    } else {
+                        state.codex_usage_page_url()
+                    };
+                    if let Err(e) = app.opener().open_url(url, None::<&str>) {
+                        let app = app.clone();
+                        let body = format!("Couldn't open the usage page: {e}");
+                        tauri::async_runtime::spawn(async move {
+                            notify_plain(&app, &body).await;
+                        });
+                    }
                 }
                 tray::ITEM_REFRESH_NOW => {
                     let refresh = app.state::<AppState<tauri::Wry>>().refresh.clone();
@@ -53,6 +112,67 @@ pub fn run() {
                         let _ = refresh.refresh_now().await;
                     });
                 }
+                tray::ITEM_SNOOZE_NOTIFICATIONS => {
+                    let state = app.state::<AppState<tauri::Wry>>().inner().clone();
+                    state.toggle_notification_snooze();
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        // Rebuild the menu so the item shows the new snooze state.
+                        let latest = state.latest_snapshot.lock().await.clone();
+                        state.update_snapshot(&app, latest).await;
+                    });
+                }
+                tray::ITEM_TOGGLE_PAUSED => {
+                    let state = app.state::<AppState<tauri::Wry>>().inner().clone();
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if state.toggle_polling_paused().await {
+                            let latest = state.latest_snapshot.lock().await.clone();
+                            state.update_snapshot(&app, latest).await;
+                        } else {
+                            // Resuming refreshes right away, which also re-renders the tray and
+                            // wakes the loop if it was waiting on explicit requests only.
+                            let _ = state.refresh.refresh_now().await;
+                        }
+                    });
+                }
+                tray::ITEM_COPY_USAGE => {
+                    let state = app.state::<AppState<tauri::Wry>>().inner().clone();
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let snapshot = state.latest_snapshot.lock().await.clone();
+                        let summary = tray::clipboard_summary(
+                            state.track_claude_enabled(),
+                            state.track_codex_enabled(),
+                            &state.tray_options(),
+                            snapshot.as_ref(),
+                        );
+                        let _ = app.clipboard().write_text(summary);
+                    });
+                }
+                tray::ITEM_SAVE_USAGE_REPORT => {
+                    let state = app.state::<AppState<tauri::Wry>>().inner().clone();
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let report = commands::usage_report(&state).await;
+                        let notify_app = app.clone();
+                        app.dialog()
+                            .file()
+                            .set_file_name("claudometer-usage.json")
+                            .add_filter("JSON", &["json"])
+                            .save_file(move |path| {
+                                let Some(path) = path.and_then(|p| p.into_path().ok()) else {
+                                    return;
+                                };
+                                if let Err(e) = std::fs::write(&path, report) {
+                                    let body = format!("Couldn't save the usage report: {e}");
+                                    tauri::async_runtime::spawn(async move {
+                                        notify_plain(&notify_app, &body).await;
+                                    });
+                                }
+                            });
+                    });
+                }
                 tray::ITEM_CHECK_UPDATES => {
                     let app = app.clone();
                     tauri::async_runtime::spawn(async move {
@@ -134,9 +254,29 @@ pub fn run() {
                     });
                 }
                 tray::ITEM_QUIT => {
-                    app.exit(0);
+                    let state = app.state::<AppState<tauri::Wry>>().inner().clone();
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        state.refresh.shutdown().await;
+                        app.exit(0);
+                    });
+                }
+                _ => {
+                    if let Some(org_id) = tray::organization_id_from_item(id) {
+                        let state = app.state::<AppState<tauri::Wry>>().inner().clone();
+                        let app = app.clone();
+                        let org_id = org_id.to_string();
+                        tauri::async_runtime::spawn(async move {
+                            if let IpcResult::Err { error, .. } =
+                                commands::switch_organization(&state, &org_id).await
+                            {
+                                let body =
+                                    format!("Couldn't switch organization: {}", error.message);
+                                notify_plain(&app, &body).await;
+                            }
+                        });
+                    }
                 }
-                _ => {}
             }
         })
         .setup(|app| {
@@ -151,14 +291,32 @@ pub fn run() {
                 tauri::Error::Setup(err.into())
             })?;
 
-            let tray = TrayUi::new(&app_handle)?;
-
-            let claude = ClaudeApiClient::new().map_err(|e| {
+            let usage_data = SettingsStore::usage_data(&app_handle).map_err(|e| {
                 let err: Box<dyn std::error::Error> = Box::new(e);
                 tauri::Error::Setup(err.into())
             })?;
 
-            let codex = CodexApiClient::new().map_err(|e| {
+            crate::notifications::migrate_period_id_map_keys(&settings);
+            crate::settings::migrate_usage_data(&settings, &usage_data);
+
+            // Tray creation can fail on desktops without a tray host; keep running without it.
+            let tray = TrayUi::new(&app_handle);
+
+            let proxy = settings.get_string(KEY_HTTP_PROXY);
+            let http_timeouts =
+                HttpTimeouts::from_seconds(settings.get_u64(KEY_HTTP_TIMEOUT_SECONDS, 40));
+            let claude_base_url = resolve_base_url(
+                std::env::var(CLAUDE_BASE_URL_ENV).ok(),
+                settings.get_string(KEY_CLAUDE_BASE_URL),
+            );
+            let claude =
+                ClaudeApiClient::with_base_url(&claude_base_url, proxy.as_deref(), http_timeouts)
+                    .map_err(|e| {
+                    let err: Box<dyn std::error::Error> = Box::new(e);
+                    tauri::Error::Setup(err.into())
+                })?;
+
+            let codex = CodexApiClient::new(proxy.as_deref(), http_timeouts).map_err(|e| {
                 let err: Box<dyn std::error::Error> = Box::new(e);
                 tauri::Error::Setup(err.into())
             })?;
@@ -166,18 +324,45 @@ pub fn run() {
             let (tx, rx) = mpsc::unbounded_channel();
             let refresh = RefreshBus::new(tx);
 
+            // Show the last known values right away; the first refresh replaces them.
+            let cached_snapshot = load_cached_snapshot(&settings, &usage_data);
+            let last_success = cached_snapshot
+                .as_ref()
+                .map(LastSuccess::from_snapshot)
+                .unwrap_or_default();
+
+            let claude_session_key = SecretManager::new(KEYRING_USER_CLAUDE_SESSION_KEY)
+                .with_env_fallback(CLAUDE_SESSION_KEY_ENV, normalize_session_key);
+            for legacy_user in LEGACY_KEYRING_USERS_CLAUDE_SESSION_KEY {
+                claude_session_key.migrate_from(legacy_user);
+            }
+
+            let polling_paused = settings.get_bool(KEY_POLLING_PAUSED, false);
             let state = AppState {
                 settings: settings.clone(),
-                claude_session_key: SecretManager::new(KEYRING_USER_CLAUDE_SESSION_KEY),
+                usage_data,
+                claude_session_key,
+                codex_cookie: SecretManager::new(KEYRING_USER_CODEX_COOKIE)
+                    .with_env_fallback(CODEX_COOKIE_ENV, normalize_codex_cookie),
                 claude: std::sync::Arc::new(claude),
                 codex: std::sync::Arc::new(codex),
                 organizations: std::sync::Arc::new(tokio::sync::Mutex::new(vec![])),
                 orgs_cache: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
-                latest_snapshot: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
+                latest_snapshot: std::sync::Arc::new(tokio::sync::Mutex::new(
+                    cached_snapshot.clone(),
+                )),
                 reset_baseline_by_org: std::sync::Arc::new(tokio::sync::Mutex::new(HashMap::new())),
                 debug_override: std::sync::Arc::new(tokio::sync::Mutex::new(
                     DebugOverride::default(),
                 )),
+                claude_org_notice: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
+                last_success: std::sync::Arc::new(tokio::sync::Mutex::new(last_success)),
+                notifications_snoozed_until: std::sync::Arc::new(std::sync::Mutex::new(None)),
+                paused: std::sync::Arc::new(tokio::sync::Mutex::new(polling_paused)),
+                rate_limit_notified: std::sync::Arc::new(tokio::sync::Mutex::new(
+                    Default::default(),
+                )),
+                codex_auto_resolution: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
                 tray: tray.clone(),
                 refresh: refresh.clone(),
             };
@@ -185,9 +370,38 @@ pub fn run() {
             state.tray.update_snapshot(
                 state.track_claude_enabled(),
                 state.track_codex_enabled(),
-                None,
+                &state.tray_options(),
+                &tray::TrayExtras {
+                    peaks: state.daily_peaks(),
+                    staleness: last_success
+                        .staleness(now_unix_ms(), state.refresh_interval_seconds()),
+                    polling_paused,
+                    ..Default::default()
+                },
+                cached_snapshot.as_ref(),
             );
             refresh::spawn_refresh_loop(app_handle.clone(), state.clone(), rx);
+            if settings.get_bool(KEY_REFRESH_ON_WAKE, true) {
+                refresh::spawn_wake_refresh(state.clone());
+            }
+
+            if settings.get_bool(KEY_METRICS_ENABLED, false) {
+                let port = settings
+                    .get_u64(KEY_METRICS_PORT, 9464)
+                    .clamp(1, u16::MAX as u64) as u16;
+                match metrics::spawn_metrics_server(state.latest_snapshot.clone(), port) {
+                    Ok(server) => {
+                        app.manage(server);
+                    }
+                    Err(e) => {
+                        let app_handle = app_handle.clone();
+                        let body = format!("Metrics endpoint unavailable on port {port}: {e}");
+                        tauri::async_runtime::spawn(async move {
+                            notify_plain(&app_handle, &body).await;
+                        });
+                    }
+                }
+            }
 
             if settings.get_bool(crate::settings::KEY_CHECK_UPDATES_ON_STARTUP, true) {
                 crate::updater::check_for_updates_background(app_handle.clone());
@@ -199,13 +413,19 @@ pub fn run() {
         .build(tauri::generate_context!())
         .expect("error while building tauri application");
 
-    app.run(|_, event| {
-        if let tauri::RunEvent::ExitRequested { code, api, .. } = event {
+    app.run(|app, event| match event {
+        tauri::RunEvent::ExitRequested { code, api, .. } => {
             // Tray-first app: keep running after the last window is closed.
             // Exiting should be explicit (tray menu -> `app.exit(0)`).
             if code.is_none() {
                 api.prevent_exit();
             }
         }
+        tauri::RunEvent::Exit => {
+            if let Some(server) = app.try_state::<metrics::MetricsServer>() {
+                server.shutdown();
+            }
+        }
+        _ => {}
     });
 }
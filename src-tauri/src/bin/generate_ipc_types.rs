@@ -43,13 +43,24 @@ fn main() {
     write_decl::<claudometer_lib::types::ClaudeUsageSnapshot>(&mut out);
     write_decl::<claudometer_lib::types::CodexUsageSnapshot>(&mut out);
     write_decl::<claudometer_lib::types::UsageSnapshotBundle>(&mut out);
+    write_decl::<claudometer_lib::types::ClaudeOrgSnapshot>(&mut out);
+    write_decl::<claudometer_lib::types::UsageSnapshotResponse>(&mut out);
 
     write_decl::<claudometer_lib::types::ClaudeOrganization>(&mut out);
     write_decl::<claudometer_lib::types::SettingsState>(&mut out);
     write_decl::<claudometer_lib::types::SaveSettingsPayload>(&mut out);
+    write_decl::<claudometer_lib::types::ClaudeSourceCapabilities>(&mut out);
+    write_decl::<claudometer_lib::types::CodexSourceCapabilities>(&mut out);
+    write_decl::<claudometer_lib::types::ProviderCapabilities>(&mut out);
+    write_decl::<claudometer_lib::types::AutoResolution>(&mut out);
+    write_decl::<claudometer_lib::types::UsageHistorySample>(&mut out);
+    write_decl::<claudometer_lib::types::BurnRate>(&mut out);
+    write_decl::<claudometer_lib::types::SaveSettingsResult>(&mut out);
+    write_decl::<claudometer_lib::types::TrayColors>(&mut out);
 
     write_decl::<claudometer_lib::types::IpcErrorCode>(&mut out);
     write_decl::<claudometer_lib::types::IpcError>(&mut out);
+    write_decl::<claudometer_lib::types::RefreshErrorEvent>(&mut out);
 
     // Export the generic result envelope used by IPC responses.
     write_decl::<claudometer_lib::types::IpcResult<()>>(&mut out);
@@ -0,0 +1,66 @@
+//! 5x7 bitmap font shared by the tray badge and the debug menu export.
+
+pub(crate) const GLYPH_WIDTH: usize = 5;
+pub(crate) const GLYPH_HEIGHT: usize = 7;
+
+/// Column-major 5x7 glyphs (bit 0 = top row). Lowercase is rendered with the uppercase glyphs and
+/// anything unknown falls back to `?`.
+pub(crate) fn glyph(c: char) -> [u8; GLYPH_WIDTH] {
+    match c.to_ascii_uppercase() {
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00],
+        '0' => [0x3E, 0x51, 0x49, 0x45, 0x3E],
+        '1' => [0x00, 0x42, 0x7F, 0x40, 0x00],
+        '2' => [0x42, 0x61, 0x51, 0x49, 0x46],
+        '3' => [0x21, 0x41, 0x45, 0x4B, 0x31],
+        '4' => [0x18, 0x14, 0x12, 0x7F, 0x10],
+        '5' => [0x27, 0x45, 0x45, 0x45, 0x39],
+        '6' => [0x3C, 0x4A, 0x49, 0x49, 0x30],
+        '7' => [0x01, 0x71, 0x09, 0x05, 0x03],
+        '8' => [0x36, 0x49, 0x49, 0x49, 0x36],
+        '9' => [0x06, 0x49, 0x49, 0x29, 0x1E],
+        'A' => [0x7E, 0x11, 0x11, 0x11, 0x7E],
+        'B' => [0x7F, 0x49, 0x49, 0x49, 0x36],
+        'C' => [0x3E, 0x41, 0x41, 0x41, 0x22],
+        'D' => [0x7F, 0x41, 0x41, 0x22, 0x1C],
+        'E' => [0x7F, 0x49, 0x49, 0x49, 0x41],
+        'F' => [0x7F, 0x09, 0x09, 0x09, 0x01],
+        'G' => [0x3E, 0x41, 0x49, 0x49, 0x7A],
+        'H' => [0x7F, 0x08, 0x08, 0x08, 0x7F],
+        'I' => [0x00, 0x41, 0x7F, 0x41, 0x00],
+        'J' => [0x20, 0x40, 0x41, 0x3F, 0x01],
+        'K' => [0x7F, 0x08, 0x14, 0x22, 0x41],
+        'L' => [0x7F, 0x40, 0x40, 0x40, 0x40],
+        'M' => [0x7F, 0x02, 0x0C, 0x02, 0x7F],
+        'N' => [0x7F, 0x04, 0x08, 0x10, 0x7F],
+        'O' => [0x3E, 0x41, 0x41, 0x41, 0x3E],
+        'P' => [0x7F, 0x09, 0x09, 0x09, 0x06],
+        'Q' => [0x3E, 0x41, 0x51, 0x21, 0x5E],
+        'R' => [0x7F, 0x09, 0x19, 0x29, 0x46],
+        'S' => [0x46, 0x49, 0x49, 0x49, 0x31],
+        'T' => [0x01, 0x01, 0x7F, 0x01, 0x01],
+        'U' => [0x3F, 0x40, 0x40, 0x40, 0x3F],
+        'V' => [0x1F, 0x20, 0x40, 0x20, 0x1F],
+        'W' => [0x3F, 0x40, 0x38, 0x40, 0x3F],
+        'X' => [0x63, 0x14, 0x08, 0x14, 0x63],
+        'Y' => [0x07, 0x08, 0x70, 0x08, 0x07],
+        'Z' => [0x61, 0x51, 0x49, 0x45, 0x43],
+        '%' => [0x23, 0x13, 0x08, 0x64, 0x62],
+        ':' => [0x00, 0x36, 0x36, 0x00, 0x00],
+        '.' => [0x00, 0x60, 0x60, 0x00, 0x00],
+        ',' => [0x00, 0x50, 0x30, 0x00, 0x00],
+        '-' => [0x08, 0x08, 0x08, 0x08, 0x08],
+        '+' => [0x08, 0x08, 0x3E, 0x08, 0x08],
+        '=' => [0x14, 0x14, 0x14, 0x14, 0x14],
+        '(' => [0x00, 0x1C, 0x22, 0x41, 0x00],
+        ')' => [0x00, 0x41, 0x22, 0x1C, 0x00],
+        '/' => [0x20, 0x10, 0x08, 0x04, 0x02],
+        '<' => [0x08, 0x14, 0x22, 0x41, 0x00],
+        '>' => [0x00, 0x41, 0x22, 0x14, 0x08],
+        '!' => [0x00, 0x00, 0x5F, 0x00, 0x00],
+        '\'' => [0x00, 0x05, 0x03, 0x00, 0x00],
+        '_' => [0x40, 0x40, 0x40, 0x40, 0x40],
+        '·' => [0x00, 0x00, 0x08, 0x00, 0x00],
+        '…' => [0x40, 0x00, 0x40, 0x00, 0x40],
+        _ => [0x02, 0x01, 0x51, 0x09, 0x06],
+    }
+}
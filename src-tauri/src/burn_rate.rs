@@ -0,0 +1,231 @@
+use crate::provider_view::{CLAUDE_PROVIDER_KEY, CODEX_PROVIDER_KEY};
+use crate::types::{BurnRate, UsageHistorySample};
+
+const MIN_SAMPLES_FOR_PROJECTION: usize = 3;
+/// How far back `burn_rates` looks; older samples say little about the current rate.
+const BURN_RATE_LOOKBACK_MS: i64 = 60 * 60 * 1000;
+const HOUR_MS: f64 = 60.0 * 60.0 * 1000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UsageSample {
+    pub at_unix_ms: i64,
+    pub percent: f64,
+}
+
+/// Least-squares slope of `samples` in percent per millisecond. `None` with too few samples or
+/// when they all share one timestamp.
+fn slope_per_ms(samples: &[UsageSample]) -> Option<f64> {
+    if samples.len() < MIN_SAMPLES_FOR_PROJECTION {
+        return None;
+    }
+    let t0 = samples[0].at_unix_ms;
+    let n = samples.len() as f64;
+    let mean_t = samples
+        .iter()
+        .map(|s| (s.at_unix_ms - t0) as f64)
+        .sum::<f64>()
+        / n;
+    let mean_p = samples.iter().map(|s| s.percent).sum::<f64>() / n;
+    let (mut cov, mut var) = (0.0, 0.0);
+    for s in samples {
+        let dt = (s.at_unix_ms - t0) as f64 - mean_t;
+        cov += dt * (s.percent - mean_p);
+        var += dt * dt;
+    }
+    if var <= 0.0 {
+        return None;
+    }
+    Some(cov / var)
+}
+
+/// Usage growth in percent per hour. `None` when there are too few samples or usage is
+/// flat/decreasing.
+pub fn percent_per_hour(samples: &[UsageSample]) -> Option<f64> {
+    slope_per_ms(samples)
+        .filter(|slope| *slope > 0.0)
+        .map(|slope| slope * HOUR_MS)
+}
+
+/// Milliseconds until 100% by linear (least-squares) extrapolation of `samples`.
+/// `None` when there are too few samples or usage is flat/decreasing.
+pub fn projected_ms_to_limit(samples: &[UsageSample]) -> Option<i64> {
+    let slope_per_ms = slope_per_ms(samples).filter(|slope| *slope > 0.0)?;
+    let last = samples[samples.len() - 1].percent;
+    let remaining = (100.0 - last).max(0.0);
+    Some((remaining / slope_per_ms).round() as i64)
+}
+
+/// The samples since the last drop in usage: a window only goes down when it resets, and a fit
+/// across the reset would mix two periods.
+fn since_last_reset(samples: &[UsageSample]) -> &[UsageSample] {
+    let start = samples
+        .windows(2)
+        .rposition(|pair| pair[1].percent < pair[0].percent)
+        .map_or(0, |i| i + 1);
+    &samples[start..]
+}
+
+/// The last hour of `provider`'s `history` as (session, weekly) series, each trimmed to the
+/// samples since that window last reset. Only the samples since the last organization switch
+/// count: another organization's usage says nothing about the current one.
+pub fn recent_series(
+    history: &[UsageHistorySample],
+    provider: &str,
+    now_ms: i64,
+) -> (Vec<UsageSample>, Vec<UsageSample>) {
+    let mut recent: Vec<&UsageHistorySample> = history
+        .iter()
+        .filter(|s| s.provider == provider && now_ms - s.timestamp_ms <= BURN_RATE_LOOKBACK_MS)
+        .collect();
+    if let Some(last) = recent.last() {
+        let start = recent
+            .iter()
+            .rposition(|s| s.organization_id != last.organization_id)
+            .map_or(0, |i| i + 1);
+        recent.drain(..start);
+    }
+    let session: Vec<UsageSample> = recent
+        .iter()
+        .map(|s| UsageSample {
+            at_unix_ms: s.timestamp_ms,
+            percent: s.session_percent,
+        })
+        .collect();
+    let weekly: Vec<UsageSample> = recent
+        .iter()
+        .filter_map(|s| {
+            Some(UsageSample {
+                at_unix_ms: s.timestamp_ms,
+                percent: s.weekly_percent?,
+            })
+        })
+        .collect();
+    (
+        since_last_reset(&session).to_vec(),
+        since_last_reset(&weekly).to_vec(),
+    )
+}
+
+/// Burn rate per provider window over the last hour of `history`, for the windows that are
+/// currently climbing.
+pub fn burn_rates(history: &[UsageHistorySample], now_ms: i64) -> Vec<BurnRate> {
+    let mut out = Vec::new();
+    for provider in [CLAUDE_PROVIDER_KEY, CODEX_PROVIDER_KEY] {
+        let (session, weekly) = recent_series(history, provider, now_ms);
+        for (window, samples) in [("session", session), ("weekly", weekly)] {
+            if let Some(percent_per_hour) = percent_per_hour(&samples) {
+                out.push(BurnRate {
+                    provider: provider.to_string(),
+                    window: window.to_string(),
+                    percent_per_hour,
+                    ms_to_limit: projected_ms_to_limit(&samples),
+                });
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(percents: &[f64]) -> Vec<UsageSample> {
+        percents
+            .iter()
+            .enumerate()
+            .map(|(i, p)| UsageSample {
+                at_unix_ms: i as i64 * 60_000,
+                percent: *p,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn projection_extrapolates_the_trend_to_the_limit() {
+        // +10% per minute ending at 80% → 2 minutes to the limit.
+        let climbing = samples(&[60.0, 70.0, 80.0]);
+        assert_eq!(projected_ms_to_limit(&climbing), Some(2 * 60_000));
+        let rate = percent_per_hour(&climbing).unwrap();
+        assert!((rate - 600.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn projection_is_none_when_not_meaningful() {
+        assert_eq!(projected_ms_to_limit(&samples(&[40.0, 40.0, 40.0])), None);
+        assert_eq!(projected_ms_to_limit(&samples(&[50.0, 40.0, 30.0])), None);
+        assert_eq!(projected_ms_to_limit(&samples(&[10.0, 90.0])), None);
+        let same_instant = vec![
+            UsageSample {
+                at_unix_ms: 0,
+                percent: 10.0,
+            };
+            3
+        ];
+        assert_eq!(percent_per_hour(&same_instant), None);
+    }
+
+    #[test]
+    fn burn_rates_fit_only_the_current_period() {
+        let history: Vec<UsageHistorySample> = [95.0, 98.0, 2.0, 7.0, 12.0]
+            .iter()
+            .enumerate()
+            .map(|(i, p)| UsageHistorySample {
+                timestamp_ms: i as i64 * 60_000,
+                provider: CLAUDE_PROVIDER_KEY.to_string(),
+                session_percent: *p,
+                weekly_percent: Some(50.0),
+                organization_id: None,
+            })
+            .collect();
+        let rates = burn_rates(&history, 4 * 60_000);
+        // The weekly window is flat, so only the session has a rate.
+        assert_eq!(rates.len(), 1);
+        assert_eq!(rates[0].window, "session");
+        assert!((rates[0].percent_per_hour - 300.0).abs() < 1e-9);
+        // 88% left at 5% per minute.
+        assert_eq!(rates[0].ms_to_limit, Some(1_056_000));
+    }
+
+    #[test]
+    fn recent_series_keep_the_last_hour_since_the_reset() {
+        let sample = |minute: i64, provider: &str, session: f64| UsageHistorySample {
+            timestamp_ms: minute * 60_000,
+            provider: provider.to_string(),
+            session_percent: session,
+            weekly_percent: None,
+            organization_id: None,
+        };
+        let history = vec![
+            sample(0, CLAUDE_PROVIDER_KEY, 5.0),
+            sample(70, CLAUDE_PROVIDER_KEY, 80.0),
+            sample(80, CLAUDE_PROVIDER_KEY, 3.0),
+            sample(85, CODEX_PROVIDER_KEY, 50.0),
+            sample(90, CLAUDE_PROVIDER_KEY, 9.0),
+        ];
+        let (session, weekly) = recent_series(&history, CLAUDE_PROVIDER_KEY, 90 * 60_000);
+        let percents: Vec<f64> = session.iter().map(|s| s.percent).collect();
+        assert_eq!(percents, vec![3.0, 9.0]);
+        assert!(weekly.is_empty());
+    }
+
+    #[test]
+    fn recent_series_start_at_the_last_organization_switch() {
+        let sample = |minute: i64, org: &str, session: f64| UsageHistorySample {
+            timestamp_ms: minute * 60_000,
+            provider: CLAUDE_PROVIDER_KEY.to_string(),
+            session_percent: session,
+            weekly_percent: None,
+            organization_id: Some(org.to_string()),
+        };
+        let history = vec![
+            sample(0, "org-a", 10.0),
+            sample(1, "org-a", 20.0),
+            sample(2, "org-b", 60.0),
+            sample(3, "org-b", 62.0),
+        ];
+        let (session, _) = recent_series(&history, CLAUDE_PROVIDER_KEY, 3 * 60_000);
+        let percents: Vec<f64> = session.iter().map(|s| s.percent).collect();
+        assert_eq!(percents, vec![60.0, 62.0]);
+    }
+}
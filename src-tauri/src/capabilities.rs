@@ -0,0 +1,105 @@
+use crate::types::{
+    ClaudeSourceCapabilities, CodexSourceCapabilities, CodexUsageSource, ProviderCapabilities,
+    UsageSource,
+};
+
+pub fn claude_source_capabilities(source: UsageSource) -> ClaudeSourceCapabilities {
+    match source {
+        UsageSource::Web => ClaudeSourceCapabilities {
+            source,
+            needs_session_key: true,
+            needs_organization: true,
+            uses_local_credentials: false,
+        },
+        UsageSource::Cli => ClaudeSourceCapabilities {
+            source,
+            needs_session_key: false,
+            needs_organization: false,
+            uses_local_credentials: true,
+        },
+    }
+}
+
+pub fn codex_source_capabilities(source: CodexUsageSource) -> CodexSourceCapabilities {
+    match source {
+        CodexUsageSource::Oauth => CodexSourceCapabilities {
+            source,
+            uses_local_credentials: true,
+            needs_cli_binary: false,
+            needs_cookie: false,
+        },
+        CodexUsageSource::Web => CodexSourceCapabilities {
+            source,
+            uses_local_credentials: false,
+            needs_cli_binary: false,
+            needs_cookie: true,
+        },
+        CodexUsageSource::Cli => CodexSourceCapabilities {
+            source,
+            uses_local_credentials: false,
+            needs_cli_binary: true,
+            needs_cookie: false,
+        },
+        // Every credential is optional: whichever is present gets used.
+        CodexUsageSource::Auto => CodexSourceCapabilities {
+            source,
+            uses_local_credentials: true,
+            needs_cli_binary: false,
+            needs_cookie: false,
+        },
+    }
+}
+
+pub fn provider_capabilities() -> ProviderCapabilities {
+    ProviderCapabilities {
+        claude: [UsageSource::Web, UsageSource::Cli]
+            .into_iter()
+            .map(claude_source_capabilities)
+            .collect(),
+        codex: [
+            CodexUsageSource::Oauth,
+            CodexUsageSource::Web,
+            CodexUsageSource::Cli,
+            CodexUsageSource::Auto,
+        ]
+        .into_iter()
+        .map(codex_source_capabilities)
+        .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provider_capabilities_cover_every_source() {
+        let caps = provider_capabilities();
+
+        let web = &caps.claude[0];
+        assert_eq!(web.source, UsageSource::Web);
+        assert!(web.needs_session_key && web.needs_organization);
+        assert!(!web.uses_local_credentials);
+
+        let claude_cli = &caps.claude[1];
+        assert_eq!(claude_cli.source, UsageSource::Cli);
+        assert!(!claude_cli.needs_session_key && !claude_cli.needs_organization);
+        assert!(claude_cli.uses_local_credentials);
+
+        let oauth = &caps.codex[0];
+        assert_eq!(oauth.source, CodexUsageSource::Oauth);
+        assert!(oauth.uses_local_credentials && !oauth.needs_cli_binary);
+
+        let codex_web = &caps.codex[1];
+        assert_eq!(codex_web.source, CodexUsageSource::Web);
+        assert!(codex_web.needs_cookie && !codex_web.uses_local_credentials);
+
+        let codex_cli = &caps.codex[2];
+        assert_eq!(codex_cli.source, CodexUsageSource::Cli);
+        assert!(!codex_cli.uses_local_credentials && codex_cli.needs_cli_binary);
+
+        let auto = &caps.codex[3];
+        assert_eq!(auto.source, CodexUsageSource::Auto);
+        assert!(!auto.needs_cookie && !auto.needs_cli_binary);
+    }
+}
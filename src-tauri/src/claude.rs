@@ -1,4 +1,10 @@
+use crate::api_error::error_message_from_body;
+use crate::http_client::{
+    is_connectivity_error, provider_timestamp, raw_response_body, retry_after_seconds,
+    with_retry_after, HttpTimeouts, SharedHttpClient, OFFLINE_ERROR_MESSAGE,
+};
 use crate::redact::redact_secrets;
+use crate::staleness::now_unix_ms;
 use crate::types::{ClaudeModelUsage, ClaudeOrganization, ClaudeUsageSnapshot};
 use reqwest::header::{
     HeaderMap, HeaderName, HeaderValue, ACCEPT, AUTHORIZATION, COOKIE, ORIGIN, REFERER, USER_AGENT,
@@ -9,9 +15,19 @@ use thiserror::Error;
 use time::OffsetDateTime;
 
 const BASE_URL: &str = "https://claude.ai/api";
+/// Overrides the Claude API base URL (and the `claudeBaseUrl` setting), e.g. for a gateway.
+pub const CLAUDE_BASE_URL_ENV: &str = "CLAUDOMETER_CLAUDE_BASE_URL";
+/// Session key for setups that keep it outside the OS keyring; a key entered in settings wins.
+pub const CLAUDE_SESSION_KEY_ENV: &str = "CLAUDOMETER_SESSION_KEY";
 const OAUTH_USAGE_URL: &str = "https://api.anthropic.com/api/oauth/usage";
 const OAUTH_BETA_HEADER: &str = "oauth-2025-04-20";
 const CLI_CREDENTIALS_RELATIVE_PATH: &str = ".claude/.credentials.json";
+/// Usage endpoint paths under `/organizations/{id}/`, tried in order. A later path is only tried
+/// when the earlier one fails with an `Error`-class failure (e.g. a 404 after an endpoint move).
+const USAGE_PATHS: [&str; 2] = ["usage", "rate_limits"];
+/// Attempts per usage fetch when the request fails with a network error or 5xx.
+const MAX_FETCH_ATTEMPTS: u32 = 3;
+const RETRY_BASE_DELAY_MS: u64 = 500;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ClaudeWebErrorStatus {
@@ -34,11 +50,33 @@ fn now_iso() -> String {
         .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
 }
 
+/// Clean up a pasted session key: surrounding whitespace and a copied `sessionKey=` cookie prefix
+/// are dropped. Empty keys and keys with inner whitespace are rejected with a user-facing message
+/// (which never echoes the key).
+pub fn normalize_session_key(raw: &str) -> Result<String, &'static str> {
+    let trimmed = raw.trim();
+    let key = trimmed
+        .strip_prefix("sessionKey=")
+        .unwrap_or(trimmed)
+        .trim();
+    if key.is_empty() {
+        return Err("Session key is empty.");
+    }
+    if key.chars().any(char::is_whitespace) {
+        return Err("Session key must not contain spaces or line breaks.");
+    }
+    Ok(key.to_string())
+}
+
 fn build_headers(session_key: &str) -> HeaderMap {
     let mut headers = HeaderMap::new();
     headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
-    if let Ok(cookie) = HeaderValue::from_str(&format!("sessionKey={session_key}")) {
-        headers.insert(COOKIE, cookie);
+    // Keys saved before validation existed may still carry the prefix or stray whitespace; an
+    // unusable key is sent without a cookie so the request fails as Unauthorized.
+    if let Ok(session_key) = normalize_session_key(session_key) {
+        if let Ok(cookie) = HeaderValue::from_str(&format!("sessionKey={session_key}")) {
+            headers.insert(COOKIE, cookie);
+        }
     }
     headers.insert(
     USER_AGENT,
@@ -117,17 +155,22 @@ fn title_case(value: &str) -> String {
         .join(" ")
 }
 
-fn read_model_weekly_usages(root: &serde_json::Map<String, Value>) -> Vec<ClaudeModelUsage> {
-    let preferred = ["seven_day_sonnet", "seven_day_opus"];
+/// Per-model buckets of one window (`<window>_<model>` keys). `preferred` models are listed
+/// first and kept at 0%; any other model only shows up once it has usage.
+fn read_model_usages(
+    root: &serde_json::Map<String, Value>,
+    window: &str,
+    preferred: &[&str],
+) -> Vec<ClaudeModelUsage> {
+    let prefix = format!("{window}_");
     let mut out: Vec<ClaudeModelUsage> = Vec::new();
-    for key in preferred {
-        if let Some(Value::Object(period)) = root.get(key) {
+    for model in preferred {
+        if let Some(Value::Object(period)) = root.get(&format!("{prefix}{model}")) {
             let percent =
                 parse_utilization_percent(period.get("utilization").unwrap_or(&Value::Null));
             let resets_at = read_string(period.get("resets_at"));
-            let name = title_case(key.trim_start_matches("seven_day_"));
             out.push(ClaudeModelUsage {
-                name,
+                name: title_case(model),
                 percent,
                 resets_at,
             });
@@ -135,10 +178,10 @@ fn read_model_weekly_usages(root: &serde_json::Map<String, Value>) -> Vec<Claude
     }
 
     for (key, value) in root.iter() {
-        if !key.starts_with("seven_day_") || key == "seven_day" {
+        let Some(model) = key.strip_prefix(&prefix) else {
             continue;
-        }
-        if preferred.contains(&key.as_str()) {
+        };
+        if preferred.contains(&model) {
             continue;
         }
         let Some(period) = value.as_object() else {
@@ -149,9 +192,8 @@ fn read_model_weekly_usages(root: &serde_json::Map<String, Value>) -> Vec<Claude
             continue;
         }
         let resets_at = read_string(period.get("resets_at"));
-        let name = title_case(key.trim_start_matches("seven_day_"));
         out.push(ClaudeModelUsage {
-            name,
+            name: title_case(model),
             percent,
             resets_at,
         });
@@ -164,6 +206,7 @@ fn parse_usage_from_json(
     json: Value,
     organization_id: &str,
     last_updated_at: &str,
+    provider_timestamp: Option<String>,
 ) -> ClaudeUsageSnapshot {
     let root = json.as_object().cloned().unwrap_or_default();
     let five_hour = root.get("five_hour").and_then(|v| v.as_object());
@@ -175,13 +218,15 @@ fn parse_usage_from_json(
         .unwrap_or(0.0);
     let session_resets_at = five_hour.and_then(|o| read_string(o.get("resets_at")));
 
+    // A weekly bucket with only `resets_at` means usage is unknown, not 0%.
     let weekly_percent = seven_day
         .and_then(|o| o.get("utilization"))
-        .map(parse_utilization_percent)
-        .unwrap_or(0.0);
+        .filter(|v| !v.is_null())
+        .map(parse_utilization_percent);
     let weekly_resets_at = seven_day.and_then(|o| read_string(o.get("resets_at")));
 
-    let models = read_model_weekly_usages(&root);
+    let models = read_model_usages(&root, "seven_day", &["sonnet", "opus"]);
+    let session_models = read_model_usages(&root, "five_hour", &["opus"]);
 
     ClaudeUsageSnapshot::Ok {
         organization_id: organization_id.to_string(),
@@ -190,31 +235,131 @@ fn parse_usage_from_json(
         weekly_percent,
         weekly_resets_at,
         models,
+        session_models,
         last_updated_at: last_updated_at.to_string(),
+        provider_timestamp,
+    }
+}
+
+/// `value` as an absolute http(s) URL without a trailing slash, or `None` if it isn't one.
+fn normalize_base_url(value: &str) -> Option<String> {
+    let url = reqwest::Url::parse(value.trim()).ok()?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return None;
+    }
+    Some(url.as_str().trim_end_matches('/').to_string())
+}
+
+/// Base URL for the Claude web API: the env override, then the setting, then `claude.ai`.
+/// Invalid overrides are skipped.
+pub fn resolve_base_url(env_value: Option<String>, setting: Option<String>) -> String {
+    [env_value, setting]
+        .into_iter()
+        .flatten()
+        .filter(|value| !value.trim().is_empty())
+        .find_map(|value| normalize_base_url(&value))
+        .unwrap_or_else(|| BASE_URL.to_string())
+}
+
+/// Only network errors and 5xx are worth retrying; 401/403/429 are authoritative answers.
+/// A request that already ran into the timeout isn't retried: another full timeout wouldn't fit
+/// in the provider's fetch budget, and the refresh would report "timed out" instead.
+fn is_transient(res: &Result<reqwest::Response, reqwest::Error>) -> bool {
+    match res {
+        Ok(r) => r.status().is_server_error(),
+        Err(e) => !e.is_timeout(),
     }
 }
 
+/// Backoff before retry number `attempt` (1-based): 0.5s, 1s, 2s, ... jittered by ±20%.
+/// `fraction` in `[0, 1)` picks the point within the jitter band.
+fn retry_delay(attempt: u32, fraction: f64) -> std::time::Duration {
+    let base_ms = RETRY_BASE_DELAY_MS << attempt.saturating_sub(1).min(8);
+    let delta = (fraction * 2.0 - 1.0) * (base_ms as f64 * 0.2);
+    std::time::Duration::from_millis((base_ms as f64 + delta) as u64)
+}
+
+/// Organizations from a `/organizations` response; `None` when the payload isn't a list.
+fn parse_organizations(json: &Value) -> Option<Vec<ClaudeOrganization>> {
+    let arr = json.as_array()?;
+
+    let mut out = Vec::new();
+    for entry in arr {
+        let Some(obj) = entry.as_object() else {
+            continue;
+        };
+        let Some(uuid) = obj.get("uuid").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let uuid = uuid.trim();
+        if uuid.is_empty() {
+            continue;
+        }
+        let name = obj
+            .get("name")
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string());
+        out.push(ClaudeOrganization {
+            id: uuid.to_string(),
+            name,
+        });
+    }
+    Some(out)
+}
+
 pub struct ClaudeApiClient {
-    http: reqwest::Client,
+    http: SharedHttpClient,
+    base_url: String,
 }
 
 impl ClaudeApiClient {
-    pub fn new() -> Result<Self, ClaudeError> {
+    /// Client for the API at `base_url` (see `resolve_base_url`).
+    pub fn with_base_url(
+        base_url: &str,
+        proxy: Option<&str>,
+        timeouts: HttpTimeouts,
+    ) -> Result<Self, ClaudeError> {
+        // NO_PROXY is matched against the host actually being contacted.
+        let host = reqwest::Url::parse(base_url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_default();
         Ok(Self {
-            http: reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(40))
-                .connect_timeout(std::time::Duration::from_secs(10))
-                .build()?,
+            http: SharedHttpClient::new(proxy, &host, timeouts)?,
+            base_url: base_url.trim_end_matches('/').to_string(),
         })
     }
 
+    pub fn set_timeouts(&self, timeouts: HttpTimeouts) -> Result<(), ClaudeError> {
+        Ok(self.http.set_timeouts(timeouts)?)
+    }
+
+    async fn send_with_retry(
+        &self,
+        request: impl Fn() -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let mut attempt = 1;
+        loop {
+            let res = request().send().await;
+            if attempt >= MAX_FETCH_ATTEMPTS || !is_transient(&res) {
+                return res;
+            }
+            let delay = retry_delay(attempt, crate::refresh::next_jitter_fraction());
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
     pub async fn fetch_organizations_checked(
         &self,
         session_key: &str,
     ) -> Result<Vec<ClaudeOrganization>, ClaudeWebErrorStatus> {
-        let url = format!("{BASE_URL}/organizations");
+        let url = format!("{}/organizations", self.base_url);
         let res = self
             .http
+            .get()
             .get(url)
             .headers(build_headers(session_key))
             .send()
@@ -233,69 +378,79 @@ impl ClaudeApiClient {
             Ok(v) => v,
             Err(_) => return Err(ClaudeWebErrorStatus::Error),
         };
-        let Some(arr) = json.as_array() else {
-            return Ok(vec![]);
-        };
+        // Anything but an array is an error payload, not a confirmed empty list: treating it as
+        // empty would drop the selected organization on a transient failure.
+        parse_organizations(&json).ok_or(ClaudeWebErrorStatus::Error)
+    }
 
-        let mut out = Vec::new();
-        for entry in arr {
-            let Some(obj) = entry.as_object() else {
-                continue;
-            };
-            let Some(uuid) = obj.get("uuid").and_then(|v| v.as_str()) else {
-                continue;
-            };
-            let uuid = uuid.trim();
-            if uuid.is_empty() {
-                continue;
+    fn usage_url(&self, organization_id: &str, path: &str) -> String {
+        format!(
+            "{}/organizations/{}/{path}",
+            self.base_url,
+            urlencoding::encode(organization_id)
+        )
+    }
+
+    pub async fn fetch_usage_snapshot(
+        &self,
+        session_key: &str,
+        organization_id: &str,
+    ) -> ClaudeUsageSnapshot {
+        let mut first_error = None;
+        for path in USAGE_PATHS {
+            let url = self.usage_url(organization_id, path);
+            let snapshot = self
+                .fetch_usage_snapshot_at(session_key, organization_id, &url)
+                .await;
+            if !matches!(snapshot, ClaudeUsageSnapshot::Error { .. }) {
+                return snapshot;
             }
-            let name = obj
-                .get("name")
-                .and_then(|v| v.as_str())
-                .map(|s| s.trim())
-                .filter(|s| !s.is_empty())
-                .map(|s| s.to_string());
-            out.push(ClaudeOrganization {
-                id: uuid.to_string(),
-                name,
-            });
+            // The primary path's error is the informative one; fallbacks mostly 404.
+            first_error.get_or_insert(snapshot);
         }
-        Ok(out)
+        first_error.expect("USAGE_PATHS is not empty")
     }
 
-    pub async fn fetch_usage_snapshot(
+    async fn fetch_usage_snapshot_at(
         &self,
         session_key: &str,
         organization_id: &str,
+        url: &str,
     ) -> ClaudeUsageSnapshot {
         let last_updated_at = now_iso();
-        let url = format!(
-            "{BASE_URL}/organizations/{}/usage",
-            urlencoding::encode(organization_id)
-        );
 
+        let http = self.http.get();
         let res = self
-            .http
-            .get(url)
-            .headers(build_headers(session_key))
-            .send()
+            .send_with_retry(|| http.get(url).headers(build_headers(session_key)))
             .await;
 
         let res = match res {
             Ok(r) => r,
+            Err(e) if is_connectivity_error(&e) => {
+                return ClaudeUsageSnapshot::Offline {
+                    organization_id: Some(organization_id.to_string()),
+                    last_updated_at,
+                    error_message: Some(OFFLINE_ERROR_MESSAGE.to_string()),
+                };
+            }
             Err(e) => {
-                let msg = redact_secrets(&e.to_string()).to_string();
                 return ClaudeUsageSnapshot::Error {
                     organization_id: Some(organization_id.to_string()),
                     last_updated_at,
-                    error_message: Some(msg),
+                    error_message: Some(redact_secrets(&e.to_string()).to_string()),
                 };
             }
         };
 
         if !res.status().is_success() {
-            let status = map_http_status(res.status().as_u16());
-            let msg = format!("Claude API error ({})", res.status().as_u16());
+            let status_code = res.status().as_u16();
+            let status = map_http_status(status_code);
+            let retry_after = retry_after_seconds(res.headers());
+            let body = res.text().await.unwrap_or_default();
+            let msg = match error_message_from_body(&body) {
+                Some(detail) => format!("Claude API error ({status_code}): {detail}"),
+                None => format!("Claude API error ({status_code})"),
+            };
             return match status {
                 ClaudeWebErrorStatus::Unauthorized => ClaudeUsageSnapshot::Unauthorized {
                     organization_id: Some(organization_id.to_string()),
@@ -305,7 +460,8 @@ impl ClaudeApiClient {
                 ClaudeWebErrorStatus::RateLimited => ClaudeUsageSnapshot::RateLimited {
                     organization_id: Some(organization_id.to_string()),
                     last_updated_at,
-                    error_message: Some(msg),
+                    error_message: Some(with_retry_after(&msg, retry_after)),
+                    retry_after_seconds: retry_after,
                 },
                 ClaudeWebErrorStatus::Error => ClaudeUsageSnapshot::Error {
                     organization_id: Some(organization_id.to_string()),
@@ -315,6 +471,7 @@ impl ClaudeApiClient {
             };
         }
 
+        let provider_timestamp = provider_timestamp(res.headers());
         let text = res.text().await;
         let text = match text {
             Ok(t) => t,
@@ -329,7 +486,9 @@ impl ClaudeApiClient {
         };
 
         match serde_json::from_str::<Value>(&text) {
-            Ok(json) => parse_usage_from_json(json, organization_id, &last_updated_at),
+            Ok(json) => {
+                parse_usage_from_json(json, organization_id, &last_updated_at, provider_timestamp)
+            }
             Err(e) => ClaudeUsageSnapshot::Error {
                 organization_id: Some(organization_id.to_string()),
                 last_updated_at,
@@ -338,11 +497,34 @@ impl ClaudeApiClient {
         }
     }
 
+    /// Body of the primary web usage endpoint exactly as returned, for debugging the parser.
+    /// Only transport failures are errors; a non-2xx response still yields its body.
+    pub async fn fetch_raw_usage(
+        &self,
+        session_key: &str,
+        organization_id: &str,
+    ) -> Result<String, String> {
+        let url = self.usage_url(organization_id, USAGE_PATHS[0]);
+        let request = self.http.get().get(url).headers(build_headers(session_key));
+        raw_response_body(request).await
+    }
+
+    /// Like `fetch_raw_usage`, for the OAuth usage endpoint.
+    pub async fn fetch_raw_oauth_usage(&self, access_token: &str) -> Result<String, String> {
+        let request = self
+            .http
+            .get()
+            .get(OAUTH_USAGE_URL)
+            .headers(build_oauth_headers(access_token));
+        raw_response_body(request).await
+    }
+
     pub async fn fetch_oauth_usage_snapshot(&self, access_token: &str) -> ClaudeUsageSnapshot {
         let last_updated_at = now_iso();
 
         let res = self
             .http
+            .get()
             .get(OAUTH_USAGE_URL)
             .headers(build_oauth_headers(access_token))
             .send()
@@ -350,6 +532,13 @@ impl ClaudeApiClient {
 
         let res = match res {
             Ok(r) => r,
+            Err(e) if is_connectivity_error(&e) => {
+                return ClaudeUsageSnapshot::Offline {
+                    organization_id: Some("oauth".to_string()),
+                    last_updated_at,
+                    error_message: Some(OFFLINE_ERROR_MESSAGE.to_string()),
+                };
+            }
             Err(_) => {
                 return ClaudeUsageSnapshot::Error {
                     organization_id: Some("oauth".to_string()),
@@ -361,6 +550,7 @@ impl ClaudeApiClient {
 
         if !res.status().is_success() {
             let status = map_http_status(res.status().as_u16());
+            let retry_after = retry_after_seconds(res.headers());
             let msg = match status {
                 ClaudeWebErrorStatus::Unauthorized => {
                     "OAuth usage is unauthorized. Re-authenticate (run `claude login`)."
@@ -377,7 +567,8 @@ impl ClaudeApiClient {
                 ClaudeWebErrorStatus::RateLimited => ClaudeUsageSnapshot::RateLimited {
                     organization_id: Some("oauth".to_string()),
                     last_updated_at,
-                    error_message: Some(msg.to_string()),
+                    error_message: Some(with_retry_after(msg, retry_after)),
+                    retry_after_seconds: retry_after,
                 },
                 ClaudeWebErrorStatus::Error => ClaudeUsageSnapshot::Error {
                     organization_id: Some("oauth".to_string()),
@@ -387,6 +578,7 @@ impl ClaudeApiClient {
             };
         }
 
+        let provider_timestamp = provider_timestamp(res.headers());
         let text = match res.text().await {
             Ok(t) => t,
             Err(_) => {
@@ -399,7 +591,7 @@ impl ClaudeApiClient {
         };
 
         match serde_json::from_str::<Value>(&text) {
-            Ok(json) => parse_usage_from_json(json, "oauth", &last_updated_at),
+            Ok(json) => parse_usage_from_json(json, "oauth", &last_updated_at, provider_timestamp),
             Err(_) => ClaudeUsageSnapshot::Error {
                 organization_id: Some("oauth".to_string()),
                 last_updated_at,
@@ -419,8 +611,13 @@ pub enum CliCredentialsError {
     InvalidJson,
     #[error("missing access token")]
     MissingAccessToken,
+    #[error("access token expired")]
+    Expired,
 }
 
+/// Treat a token as expired this long before `expiresAt`, so a request doesn't race the expiry.
+const CLI_TOKEN_EXPIRY_SKEW_MS: i64 = 60_000;
+
 fn credentials_path() -> Result<PathBuf, CliCredentialsError> {
     let home = std::env::var_os("HOME").ok_or(CliCredentialsError::HomeMissing)?;
     Ok(PathBuf::from(home).join(CLI_CREDENTIALS_RELATIVE_PATH))
@@ -455,7 +652,7 @@ fn read_cli_oauth_from_keychain() -> Result<String, CliCredentialsError> {
 
     let json: Value =
         serde_json::from_str(&json_str).map_err(|_| CliCredentialsError::InvalidJson)?;
-    extract_cli_oauth_access_token(&json).ok_or(CliCredentialsError::MissingAccessToken)
+    access_token_from_credentials(&json, now_unix_ms())
 }
 
 #[cfg(target_os = "macos")]
@@ -469,8 +666,13 @@ pub fn read_cli_oauth_access_token() -> Result<String, CliCredentialsError> {
     read_cli_oauth_access_token_from_path(&path)
 }
 
+/// Whether Claude Code credentials exist, even if their access token has expired (the CLI renews
+/// it the next time it runs).
 pub fn cli_credentials_available() -> bool {
-    read_cli_oauth_access_token().is_ok()
+    matches!(
+        read_cli_oauth_access_token(),
+        Ok(_) | Err(CliCredentialsError::Expired)
+    )
 }
 
 pub(crate) fn read_cli_oauth_access_token_from_path(
@@ -479,7 +681,30 @@ pub(crate) fn read_cli_oauth_access_token_from_path(
     let contents = std::fs::read_to_string(path).map_err(|_| CliCredentialsError::MissingFile)?;
     let json: Value =
         serde_json::from_str(&contents).map_err(|_| CliCredentialsError::InvalidJson)?;
-    extract_cli_oauth_access_token(&json).ok_or(CliCredentialsError::MissingAccessToken)
+    access_token_from_credentials(&json, now_unix_ms())
+}
+
+/// The access token from Claude Code credentials, unless `expiresAt` says it's already expired.
+/// Renewing it is left to the CLI: the refresh token rotates on use, and Claudometer never
+/// persists OAuth tokens.
+fn access_token_from_credentials(json: &Value, now_ms: i64) -> Result<String, CliCredentialsError> {
+    let token =
+        extract_cli_oauth_access_token(json).ok_or(CliCredentialsError::MissingAccessToken)?;
+    if access_token_expired(extract_cli_oauth_expires_at(json), now_ms) {
+        return Err(CliCredentialsError::Expired);
+    }
+    Ok(token)
+}
+
+/// `claudeAiOauth.expiresAt` (Unix milliseconds), if present.
+fn extract_cli_oauth_expires_at(json: &Value) -> Option<i64> {
+    json.get("claudeAiOauth")?.get("expiresAt")?.as_i64()
+}
+
+/// Whether a token expiring at `expires_at_ms` is unusable at `now_ms`. Unknown expiry is
+/// assumed valid and left for the server to judge.
+fn access_token_expired(expires_at_ms: Option<i64>, now_ms: i64) -> bool {
+    expires_at_ms.is_some_and(|expires_at| expires_at - CLI_TOKEN_EXPIRY_SKEW_MS <= now_ms)
 }
 
 fn extract_cli_oauth_access_token(json: &Value) -> Option<String> {
@@ -497,6 +722,22 @@ fn extract_cli_oauth_access_token(json: &Value) -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn normalize_session_key_strips_prefix_and_whitespace() {
+        assert_eq!(
+            normalize_session_key("  sessionKey=sk-ant-sid01-abc\n").as_deref(),
+            Ok("sk-ant-sid01-abc")
+        );
+        assert_eq!(
+            normalize_session_key("sk-ant-sid01-abc").as_deref(),
+            Ok("sk-ant-sid01-abc")
+        );
+        assert!(normalize_session_key("sessionKey=").is_err());
+        assert!(normalize_session_key("   ").is_err());
+        assert!(normalize_session_key("sk-ant-sid01-abc\nsk-ant-sid01-def").is_err());
+        assert!(normalize_session_key("sk-ant sid01").is_err());
+    }
+
     #[test]
     fn extracts_access_token() {
         let json: Value = serde_json::json!({
@@ -510,6 +751,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn expired_cli_token_is_not_used() {
+        let json = serde_json::json!({
+          "claudeAiOauth": {
+            "accessToken": "test-token",
+            "refreshToken": "refresh-token",
+            "expiresAt": 1_767_740_400_000_i64
+          }
+        });
+        assert_eq!(extract_cli_oauth_expires_at(&json), Some(1_767_740_400_000));
+
+        let before = 1_767_740_400_000 - 5 * 60_000;
+        assert_eq!(
+            access_token_from_credentials(&json, before).ok().as_deref(),
+            Some("test-token")
+        );
+        // Within the skew window or past expiry.
+        assert!(matches!(
+            access_token_from_credentials(&json, 1_767_740_400_000 - 30_000),
+            Err(CliCredentialsError::Expired)
+        ));
+        assert!(access_token_expired(Some(1_000), 2_000_000));
+        assert!(!access_token_expired(None, 2_000_000));
+    }
+
     #[test]
     fn missing_access_token_is_none() {
         let json: Value = serde_json::json!({ "claudeAiOauth": {} });
@@ -540,11 +806,28 @@ mod tests {
         assert!(matches!(err, CliCredentialsError::InvalidJson));
     }
 
+    #[test]
+    fn organizations_error_payload_is_not_a_confirmed_empty_list() {
+        assert!(parse_organizations(&serde_json::json!([])).is_some_and(|orgs| orgs.is_empty()));
+        assert!(parse_organizations(&serde_json::json!({
+            "type": "error",
+            "error": { "type": "overloaded_error", "message": "Overloaded" }
+        }))
+        .is_none());
+        let orgs = parse_organizations(&serde_json::json!([
+            { "uuid": " org-1 ", "name": "Team" },
+            { "uuid": "" }
+        ]))
+        .unwrap();
+        assert_eq!(orgs.len(), 1);
+        assert_eq!(orgs[0].id, "org-1");
+    }
+
     #[test]
     fn parse_oauth_usage_ok_fixture_includes_sonnet_and_opus() {
         let json: Value = serde_json::from_str(include_str!("fixtures/oauth_usage_ok.json"))
             .expect("fixture json");
-        let snapshot = parse_usage_from_json(json, "oauth", "2026-01-01T00:00:00.000Z");
+        let snapshot = parse_usage_from_json(json, "oauth", "2026-01-01T00:00:00.000Z", None);
         let ClaudeUsageSnapshot::Ok { models, .. } = snapshot else {
             panic!("expected ok snapshot");
         };
@@ -555,12 +838,44 @@ mod tests {
         assert_eq!(models[1].percent, 9.0);
     }
 
+    #[test]
+    fn parse_session_model_buckets_only_when_present() {
+        let json: Value =
+            serde_json::from_str(include_str!("fixtures/oauth_usage_session_opus.json"))
+                .expect("fixture json");
+        let snapshot = parse_usage_from_json(json, "oauth", "2026-01-01T00:00:00.000Z", None);
+        let ClaudeUsageSnapshot::Ok {
+            models,
+            session_models,
+            ..
+        } = snapshot
+        else {
+            panic!("expected ok snapshot");
+        };
+        assert_eq!(session_models.len(), 1);
+        assert_eq!(session_models[0].name, "Opus");
+        assert_eq!(session_models[0].percent, 64.0);
+        assert_eq!(
+            session_models[0].resets_at.as_deref(),
+            Some("2026-01-01T12:00:00.000Z")
+        );
+        assert!(models.iter().all(|m| m.name != "Opus" || m.percent == 9.0));
+
+        let json: Value = serde_json::from_str(include_str!("fixtures/oauth_usage_ok.json"))
+            .expect("fixture json");
+        let snapshot = parse_usage_from_json(json, "oauth", "2026-01-01T00:00:00.000Z", None);
+        assert!(matches!(
+            snapshot,
+            ClaudeUsageSnapshot::Ok { session_models, .. } if session_models.is_empty()
+        ));
+    }
+
     #[test]
     fn parse_oauth_usage_null_models_fixture_skips_null_bucket() {
         let json: Value =
             serde_json::from_str(include_str!("fixtures/oauth_usage_null_models.json"))
                 .expect("fixture json");
-        let snapshot = parse_usage_from_json(json, "oauth", "2026-01-01T00:00:00.000Z");
+        let snapshot = parse_usage_from_json(json, "oauth", "2026-01-01T00:00:00.000Z", None);
         let ClaudeUsageSnapshot::Ok { models, .. } = snapshot else {
             panic!("expected ok snapshot");
         };
@@ -569,6 +884,41 @@ mod tests {
         assert!(models.iter().any(|m| m.name == "Foo"));
     }
 
+    #[test]
+    fn parse_weekly_bucket_without_utilization_is_unknown() {
+        let json: Value = serde_json::from_str(include_str!(
+            "fixtures/oauth_usage_weekly_no_utilization.json"
+        ))
+        .expect("fixture json");
+        let snapshot = parse_usage_from_json(json, "oauth", "2026-01-01T00:00:00.000Z", None);
+        let ClaudeUsageSnapshot::Ok {
+            session_percent,
+            weekly_percent,
+            weekly_resets_at,
+            ..
+        } = snapshot
+        else {
+            panic!("expected ok snapshot");
+        };
+        assert_eq!(session_percent, 12.3);
+        assert_eq!(weekly_percent, None);
+        assert_eq!(
+            weekly_resets_at.as_deref(),
+            Some("2026-01-08T12:00:00.000Z")
+        );
+
+        let json: Value = serde_json::from_str(include_str!("fixtures/oauth_usage_ok.json"))
+            .expect("fixture json");
+        let snapshot = parse_usage_from_json(json, "oauth", "2026-01-01T00:00:00.000Z", None);
+        assert!(matches!(
+            snapshot,
+            ClaudeUsageSnapshot::Ok {
+                weekly_percent: Some(p),
+                ..
+            } if p == 55.0
+        ));
+    }
+
     #[test]
     fn map_http_status_for_oauth() {
         assert!(matches!(
@@ -585,4 +935,173 @@ mod tests {
         ));
         assert!(matches!(map_http_status(500), ClaudeWebErrorStatus::Error));
     }
+
+    #[test]
+    fn retry_delay_doubles_with_bounded_jitter() {
+        assert_eq!(retry_delay(1, 0.5).as_millis(), 500);
+        assert_eq!(retry_delay(2, 0.5).as_millis(), 1000);
+        assert_eq!(retry_delay(3, 0.5).as_millis(), 2000);
+        assert_eq!(retry_delay(1, 0.0).as_millis(), 400);
+        assert!(retry_delay(1, 0.999).as_millis() < 600);
+    }
+
+    #[test]
+    fn base_url_override_must_be_absolute_http() {
+        assert_eq!(resolve_base_url(None, None), BASE_URL);
+        assert_eq!(
+            resolve_base_url(None, Some("https://gateway.internal/claude/".to_string())),
+            "https://gateway.internal/claude"
+        );
+        assert_eq!(
+            resolve_base_url(
+                Some("http://localhost:9000/api".to_string()),
+                Some("https://gateway.internal".to_string())
+            ),
+            "http://localhost:9000/api"
+        );
+        assert_eq!(
+            resolve_base_url(
+                Some("ftp://gateway".to_string()),
+                Some("/relative".to_string())
+            ),
+            BASE_URL
+        );
+    }
+
+    #[tokio::test]
+    async fn usage_fetch_retries_server_error_then_succeeds() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/organizations/org-1/usage"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/organizations/org-1/usage"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "five_hour": { "utilization": 42.0, "resets_at": null },
+                "seven_day": { "utilization": 10.0, "resets_at": null }
+            })))
+            .mount(&server)
+            .await;
+
+        let client =
+            ClaudeApiClient::with_base_url(&server.uri(), None, HttpTimeouts::default()).unwrap();
+        let snapshot = client.fetch_usage_snapshot("test-key", "org-1").await;
+        assert!(matches!(
+            snapshot,
+            ClaudeUsageSnapshot::Ok { session_percent, .. } if session_percent == 42.0
+        ));
+    }
+
+    #[tokio::test]
+    async fn usage_fetch_captures_the_response_date() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/organizations/org-1/usage"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("date", "Thu, 01 Jan 2026 09:58:30 GMT")
+                    .set_body_json(serde_json::json!({
+                        "five_hour": { "utilization": 12.3, "resets_at": null }
+                    })),
+            )
+            .mount(&server)
+            .await;
+
+        let client =
+            ClaudeApiClient::with_base_url(&server.uri(), None, HttpTimeouts::default()).unwrap();
+        let snapshot = client.fetch_usage_snapshot("test-key", "org-1").await;
+        let ClaudeUsageSnapshot::Ok {
+            provider_timestamp, ..
+        } = snapshot
+        else {
+            panic!("expected ok snapshot");
+        };
+        assert_eq!(provider_timestamp.as_deref(), Some("2026-01-01T09:58:30Z"));
+    }
+
+    #[tokio::test]
+    async fn usage_fetch_does_not_retry_a_timed_out_request() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/organizations/org-1/usage"))
+            .respond_with(
+                ResponseTemplate::new(200).set_delay(std::time::Duration::from_millis(500)),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let timeouts = HttpTimeouts {
+            request: std::time::Duration::from_millis(100),
+            connect: std::time::Duration::from_millis(100),
+        };
+        let client = ClaudeApiClient::with_base_url(&server.uri(), None, timeouts).unwrap();
+        let snapshot = client.fetch_usage_snapshot("test-key", "org-1").await;
+        assert!(matches!(snapshot, ClaudeUsageSnapshot::Offline { .. }));
+    }
+
+    #[tokio::test]
+    async fn usage_fetch_falls_back_when_the_primary_path_is_gone() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/organizations/org-1/usage"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/organizations/org-1/{}", USAGE_PATHS[1])))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "five_hour": { "utilization": 42.0, "resets_at": null },
+                "seven_day": { "utilization": 10.0, "resets_at": null }
+            })))
+            .mount(&server)
+            .await;
+
+        let client =
+            ClaudeApiClient::with_base_url(&server.uri(), None, HttpTimeouts::default()).unwrap();
+        let snapshot = client.fetch_usage_snapshot("test-key", "org-1").await;
+        assert!(matches!(
+            snapshot,
+            ClaudeUsageSnapshot::Ok { session_percent, .. } if session_percent == 42.0
+        ));
+    }
+
+    #[tokio::test]
+    async fn usage_fetch_does_not_fall_back_when_unauthorized() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/organizations/org-1/usage"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/organizations/org-1/{}", USAGE_PATHS[1])))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client =
+            ClaudeApiClient::with_base_url(&server.uri(), None, HttpTimeouts::default()).unwrap();
+        let snapshot = client.fetch_usage_snapshot("test-key", "org-1").await;
+        assert!(matches!(snapshot, ClaudeUsageSnapshot::Unauthorized { .. }));
+    }
 }
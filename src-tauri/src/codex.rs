@@ -1,7 +1,12 @@
+use crate::api_error::error_message_from_body;
+use crate::http_client::{
+    is_connectivity_error, provider_timestamp, raw_response_body, retry_after_seconds,
+    with_retry_after, HttpTimeouts, SharedHttpClient, OFFLINE_ERROR_MESSAGE,
+};
 use crate::redact::redact_secrets;
 use crate::types::CodexUsageSnapshot;
 use reqwest::header::{
-    HeaderMap, HeaderName, HeaderValue, ACCEPT, AUTHORIZATION, ORIGIN, REFERER, USER_AGENT,
+    HeaderMap, HeaderName, HeaderValue, ACCEPT, AUTHORIZATION, COOKIE, ORIGIN, REFERER, USER_AGENT,
 };
 use serde::Deserialize;
 use serde_json::Value;
@@ -9,15 +14,25 @@ use std::path::PathBuf;
 use std::process::Stdio;
 use thiserror::Error;
 use time::OffsetDateTime;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::process::Command;
 use tokio::sync::Mutex;
 use tokio::time::{Duration, Instant};
 
 const USAGE_URL_PRIMARY: &str = "https://chatgpt.com/backend-api/wham/usage";
 const USAGE_URL_FALLBACK: &str = "https://chatgpt.com/api/codex/usage";
+const CHATGPT_HOST: &str = "chatgpt.com";
 
 const CODEX_AUTH_RELATIVE_PATH: &str = ".codex/auth.json";
+/// Treat a token as expired this long before its `exp`, so a request doesn't race the expiry.
+const OAUTH_TOKEN_EXPIRY_SKEW_SECONDS: i64 = 60;
+/// Cookie holding the chatgpt.com login; a bare value pasted into settings is sent under it.
+const CHATGPT_SESSION_COOKIE: &str = "__Secure-next-auth.session-token";
+/// Environment variable read as a fallback for the chatgpt.com cookie.
+pub const CODEX_COOKIE_ENV: &str = "CLAUDOMETER_CODEX_COOKIE";
+const MISSING_SESSION_COOKIE_WARNING: &str =
+    "Codex cookie has no __Secure-next-auth.session-token. \
+Copy the full Cookie header from a logged-in chatgpt.com tab.";
 
 fn now_iso() -> String {
     OffsetDateTime::now_utc()
@@ -68,11 +83,77 @@ fn build_oauth_headers(access_token: &str, account_id: Option<&str>) -> HeaderMa
     headers
 }
 
+/// Cleans up a pasted chatgpt.com cookie: either a full `Cookie` header value or just the session
+/// token. The error never echoes the value.
+pub fn normalize_codex_cookie(raw: &str) -> Result<String, &'static str> {
+    let trimmed = raw.trim();
+    let cookie = trimmed
+        .strip_prefix("Cookie:")
+        .or_else(|| trimmed.strip_prefix("cookie:"))
+        .unwrap_or(trimmed)
+        .trim();
+    if cookie.is_empty() {
+        return Err("Codex cookie is empty.");
+    }
+    if cookie.chars().any(char::is_control) {
+        return Err("Codex cookie must not contain line breaks.");
+    }
+    if cookie.contains('=') {
+        Ok(cookie.to_string())
+    } else {
+        Ok(format!("{CHATGPT_SESSION_COOKIE}={cookie}"))
+    }
+}
+
+/// Non-blocking check of a normalized cookie: a warning when it lacks the chatgpt.com session
+/// cookie (possibly split into `.0`, `.1`, ... chunks), which usually means the wrong thing was
+/// pasted.
+pub fn codex_cookie_warning(cookie: &str) -> Option<&'static str> {
+    let has_session = cookie.split(';').any(|pair| {
+        let name = pair.split('=').next().unwrap_or_default().trim();
+        name == CHATGPT_SESSION_COOKIE
+            || name
+                .strip_prefix(CHATGPT_SESSION_COOKIE)
+                .is_some_and(|rest| rest.starts_with('.'))
+    });
+    (!has_session).then_some(MISSING_SESSION_COOKIE_WARNING)
+}
+
+fn build_web_headers(cookie: &str) -> HeaderMap {
+    let mut headers = build_common_headers();
+    // An unusable cookie is sent without the header so the request fails as Unauthorized.
+    if let Ok(cookie) = normalize_codex_cookie(cookie) {
+        if let Ok(value) = HeaderValue::from_str(&cookie) {
+            headers.insert(COOKIE, value);
+        }
+    }
+    headers
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CodexHttpErrorStatus {
     Unauthorized,
     RateLimited,
     Error,
+    /// The request never reached the server.
+    Offline,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CodexHttpError {
+    status: CodexHttpErrorStatus,
+    message: Option<String>,
+    retry_after_seconds: Option<u64>,
+}
+
+impl From<CodexHttpErrorStatus> for CodexHttpError {
+    fn from(status: CodexHttpErrorStatus) -> Self {
+        Self {
+            status,
+            message: None,
+            retry_after_seconds: None,
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -107,6 +188,59 @@ pub enum CodexCredentialsError {
 pub struct CodexOAuthCredentials {
     pub access_token: String,
     pub account_id: Option<String>,
+    /// The access token's `exp` claim (Unix seconds), when it is a readable JWT.
+    pub expires_at: Option<i64>,
+}
+
+/// Decode unpadded base64url, as used in JWT segments.
+fn decode_base64url(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for byte in input.trim_end_matches('=').bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'-' => 62,
+            b'_' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+/// The `exp` claim of a JWT access token. The signature isn't checked; this only decides whether
+/// the request is worth sending.
+fn jwt_expiry(token: &str) -> Option<i64> {
+    let payload = token.split('.').nth(1)?;
+    let claims: Value = serde_json::from_slice(&decode_base64url(payload)?).ok()?;
+    claims.get("exp")?.as_i64()
+}
+
+fn parse_codex_oauth_credentials(
+    json: &Value,
+) -> Result<CodexOAuthCredentials, CodexCredentialsError> {
+    let tokens = json.get("tokens").and_then(|v| v.as_object());
+    let token = |key: &str| {
+        tokens
+            .and_then(|t| t.get(key))
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    let access_token = token("access_token").ok_or(CodexCredentialsError::MissingAccessToken)?;
+    Ok(CodexOAuthCredentials {
+        expires_at: jwt_expiry(&access_token),
+        access_token,
+        account_id: token("account_id"),
+    })
 }
 
 fn auth_file_path() -> Result<PathBuf, CodexCredentialsError> {
@@ -129,25 +263,14 @@ pub fn read_codex_oauth_credentials() -> Result<CodexOAuthCredentials, CodexCred
     let data = std::fs::read_to_string(&path).map_err(|_| CodexCredentialsError::MissingFile)?;
     let json: Value =
         serde_json::from_str(&data).map_err(|_| CodexCredentialsError::InvalidJson)?;
+    parse_codex_oauth_credentials(&json)
+}
 
-    let tokens = json.get("tokens").and_then(|v| v.as_object());
-    let access_token = tokens
-        .and_then(|t| t.get("access_token"))
-        .and_then(|v| v.as_str())
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .ok_or(CodexCredentialsError::MissingAccessToken)?;
-
-    let account_id = tokens
-        .and_then(|t| t.get("account_id"))
-        .and_then(|v| v.as_str())
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty());
-
-    Ok(CodexOAuthCredentials {
-        access_token,
-        account_id,
-    })
+/// Whether the access token's `exp` has passed (minus a small skew). Unknown expiry counts as
+/// valid and is left to the server. The token isn't refreshed here: refresh tokens rotate on use,
+/// and the new ones would have to be written back to `auth.json`.
+fn oauth_token_expired(expires_at: Option<i64>, now_seconds: i64) -> bool {
+    expires_at.is_some_and(|exp| exp - OAUTH_TOKEN_EXPIRY_SKEW_SECONDS <= now_seconds)
 }
 
 #[derive(Debug, Deserialize)]
@@ -166,25 +289,30 @@ struct CodexRateLimit {
 
 #[derive(Debug, Deserialize)]
 struct CodexWindow {
-    used_percent: i64,
+    used_percent: f64,
     reset_at: i64,
 }
 
-fn parse_codex_usage_response(json: Value) -> Option<(CodexWindow, CodexWindow)> {
+/// The session (primary) window, plus the weekly (secondary) one when the account has it.
+fn parse_codex_usage_response(json: Value) -> Option<(CodexWindow, Option<CodexWindow>)> {
     let parsed: CodexUsageResponse = serde_json::from_value(json).ok()?;
     let rate = parsed.rate_limit?;
     let primary = rate.primary_window?;
-    let secondary = rate.secondary_window?;
-    Some((primary, secondary))
+    Some((primary, rate.secondary_window))
 }
 
-fn ok_snapshot(primary: CodexWindow, secondary: CodexWindow) -> CodexUsageSnapshot {
+fn ok_snapshot(
+    primary: CodexWindow,
+    secondary: Option<CodexWindow>,
+    provider_timestamp: Option<String>,
+) -> CodexUsageSnapshot {
     CodexUsageSnapshot::Ok {
-        session_percent: clamp_percent(primary.used_percent as f64),
+        session_percent: clamp_percent(primary.used_percent),
         session_resets_at: epoch_seconds_to_rfc3339(primary.reset_at),
-        weekly_percent: clamp_percent(secondary.used_percent as f64),
-        weekly_resets_at: epoch_seconds_to_rfc3339(secondary.reset_at),
+        weekly_percent: secondary.as_ref().map(|w| clamp_percent(w.used_percent)),
+        weekly_resets_at: secondary.and_then(|w| epoch_seconds_to_rfc3339(w.reset_at)),
         last_updated_at: now_iso(),
+        provider_timestamp,
     }
 }
 
@@ -195,10 +323,11 @@ fn unauthorized_snapshot(message: &str) -> CodexUsageSnapshot {
     }
 }
 
-fn rate_limited_snapshot(message: &str) -> CodexUsageSnapshot {
+fn rate_limited_snapshot(message: &str, retry_after_seconds: Option<u64>) -> CodexUsageSnapshot {
     CodexUsageSnapshot::RateLimited {
         last_updated_at: now_iso(),
-        error_message: Some(message.to_string()),
+        error_message: Some(with_retry_after(message, retry_after_seconds)),
+        retry_after_seconds,
     }
 }
 
@@ -210,81 +339,133 @@ fn error_snapshot(message: &str) -> CodexUsageSnapshot {
 }
 
 pub struct CodexApiClient {
-    http: reqwest::Client,
+    http: SharedHttpClient,
     rpc: CodexRpcClient,
 }
 
 impl CodexApiClient {
-    pub fn new() -> Result<Self, CodexError> {
+    pub fn new(proxy: Option<&str>, timeouts: HttpTimeouts) -> Result<Self, CodexError> {
         Ok(Self {
-            http: reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(40))
-                .connect_timeout(std::time::Duration::from_secs(10))
-                .build()?,
+            http: SharedHttpClient::new(proxy, CHATGPT_HOST, timeouts)?,
             rpc: CodexRpcClient::new(),
         })
     }
 
-    async fn fetch_usage_json(&self, headers: HeaderMap) -> Result<Value, CodexHttpErrorStatus> {
+    pub fn set_timeouts(&self, timeouts: HttpTimeouts) -> Result<(), CodexError> {
+        Ok(self.http.set_timeouts(timeouts)?)
+    }
+
+    /// Stop the Codex CLI app-server, if one is running.
+    pub async fn shutdown(&self) {
+        self.rpc.shutdown().await;
+    }
+
+    /// The usage endpoint's JSON and its provider timestamp.
+    async fn fetch_usage_json(
+        &self,
+        headers: HeaderMap,
+    ) -> Result<(Value, Option<String>), CodexHttpError> {
         async fn attempt(
             http: &reqwest::Client,
             url: &'static str,
             headers: &HeaderMap,
-        ) -> Result<Value, CodexHttpErrorStatus> {
+        ) -> Result<(Value, Option<String>), CodexHttpError> {
             let res = http.get(url).headers(headers.clone()).send().await;
             let res = match res {
                 Ok(r) => r,
-                Err(_) => return Err(CodexHttpErrorStatus::Error),
+                Err(e) if is_connectivity_error(&e) => {
+                    return Err(CodexHttpErrorStatus::Offline.into())
+                }
+                Err(_) => return Err(CodexHttpErrorStatus::Error.into()),
             };
 
             if !res.status().is_success() {
-                return Err(map_http_status(res.status().as_u16()));
+                let status = map_http_status(res.status().as_u16());
+                let retry_after = retry_after_seconds(res.headers());
+                let body = res.text().await.unwrap_or_default();
+                return Err(CodexHttpError {
+                    status,
+                    message: error_message_from_body(&body),
+                    retry_after_seconds: retry_after,
+                });
             }
 
+            let provider_timestamp = provider_timestamp(res.headers());
             let json: Value = match res.json().await {
                 Ok(v) => v,
-                Err(_) => return Err(CodexHttpErrorStatus::Error),
+                Err(_) => return Err(CodexHttpErrorStatus::Error.into()),
             };
-            Ok(json)
+            Ok((json, provider_timestamp))
         }
 
-        match attempt(&self.http, USAGE_URL_PRIMARY, &headers).await {
+        let http = self.http.get();
+        match attempt(&http, USAGE_URL_PRIMARY, &headers).await {
             Ok(v) => Ok(v),
-            Err(CodexHttpErrorStatus::Error) => {
-                attempt(&self.http, USAGE_URL_FALLBACK, &headers).await
+            Err(e) if e.status == CodexHttpErrorStatus::Error => {
+                attempt(&http, USAGE_URL_FALLBACK, &headers).await
             }
             Err(e) => Err(e),
         }
     }
 
+    /// Usage with the `auth.json` credentials; an expired token is reported without a request.
     pub async fn fetch_oauth_usage_snapshot(
+        &self,
+        credentials: &CodexOAuthCredentials,
+    ) -> CodexUsageSnapshot {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        if oauth_token_expired(credentials.expires_at, now) {
+            return unauthorized_snapshot(
+                "Codex session expired. Run `codex` to renew it and try again.",
+            );
+        }
+        let headers =
+            build_oauth_headers(&credentials.access_token, credentials.account_id.as_deref());
+        usage_snapshot_from_result(
+            self.fetch_usage_json(headers).await,
+            "Codex OAuth credentials are invalid. Run `codex` to re-authenticate.",
+        )
+    }
+
+    pub async fn fetch_web_usage_snapshot(&self, cookie: &str) -> CodexUsageSnapshot {
+        let unauthorized_message = normalize_codex_cookie(cookie)
+            .ok()
+            .and_then(|cookie| codex_cookie_warning(&cookie))
+            .unwrap_or("Codex cookie is invalid or expired. Paste a fresh one from chatgpt.com.");
+        usage_snapshot_from_result(
+            self.fetch_usage_json(build_web_headers(cookie)).await,
+            unauthorized_message,
+        )
+    }
+
+    /// Body of the usage endpoint exactly as returned for the OAuth credentials, for debugging
+    /// the parser. Unlike the snapshot fetch this doesn't fall back to the second URL.
+    pub async fn fetch_raw_oauth_usage(
         &self,
         access_token: &str,
         account_id: Option<&str>,
-    ) -> CodexUsageSnapshot {
+    ) -> Result<String, String> {
         let headers = build_oauth_headers(access_token, account_id);
-        let result = self.fetch_usage_json(headers).await;
-        match result {
-            Ok(json) => match parse_codex_usage_response(json) {
-                Some((primary, secondary)) => ok_snapshot(primary, secondary),
-                None => error_snapshot("Codex usage data missing required fields."),
-            },
-            Err(CodexHttpErrorStatus::Unauthorized) => unauthorized_snapshot(
-                "Codex OAuth credentials are invalid. Run `codex` to re-authenticate.",
-            ),
-            Err(CodexHttpErrorStatus::RateLimited) => rate_limited_snapshot("Rate limited."),
-            Err(CodexHttpErrorStatus::Error) => error_snapshot("Failed to fetch Codex usage."),
-        }
+        raw_response_body(self.http.get().get(USAGE_URL_PRIMARY).headers(headers)).await
+    }
+
+    /// Like `fetch_raw_oauth_usage`, authenticated with the chatgpt.com cookie.
+    pub async fn fetch_raw_web_usage(&self, cookie: &str) -> Result<String, String> {
+        let headers = build_web_headers(cookie);
+        raw_response_body(self.http.get().get(USAGE_URL_PRIMARY).headers(headers)).await
     }
 
-    pub async fn fetch_cli_usage_snapshot(&self, codex_binary: &str) -> CodexUsageSnapshot {
-        match self.rpc.fetch_rate_limits(codex_binary).await {
-            Ok((primary, secondary)) => ok_snapshot(primary, secondary),
-            Err(CodexCliError::BinaryMissing) => error_snapshot(
-                "Codex CLI missing. Install `@openai/codex` (or ensure `codex` is on PATH).",
-            ),
+    pub async fn fetch_cli_usage_snapshot(&self, options: &CodexCliOptions) -> CodexUsageSnapshot {
+        match self.rpc.fetch_rate_limits(options).await {
+            Ok((primary, secondary)) => ok_snapshot(primary, Some(secondary), None),
+            Err(CodexCliError::BinaryMissing) => {
+                error_snapshot(&binary_missing_message(&options.binary))
+            }
             Err(CodexCliError::TimedOut) => error_snapshot("Codex CLI probe timed out."),
             Err(CodexCliError::Malformed) => error_snapshot("Codex CLI returned invalid data."),
+            Err(CodexCliError::NotLoggedIn) => {
+                unauthorized_snapshot("Codex CLI is not logged in. Run `codex` to log in.")
+            }
             Err(CodexCliError::Failed(msg)) => error_snapshot(&msg),
             Err(CodexCliError::Backoff { retry_in }) => error_snapshot(&format!(
                 "Codex CLI temporarily paused after previous failure; retrying in {}s.",
@@ -294,11 +475,76 @@ impl CodexApiClient {
     }
 }
 
+/// Snapshot for a usage endpoint response, shared by the OAuth and web sources.
+fn usage_snapshot_from_result(
+    result: Result<(Value, Option<String>), CodexHttpError>,
+    unauthorized_message: &str,
+) -> CodexUsageSnapshot {
+    match result {
+        Ok((json, provider_timestamp)) => match parse_codex_usage_response(json) {
+            Some((primary, secondary)) => ok_snapshot(primary, secondary, provider_timestamp),
+            None => error_snapshot("Codex usage data missing required fields."),
+        },
+        Err(e) => match e.status {
+            CodexHttpErrorStatus::Unauthorized => unauthorized_snapshot(unauthorized_message),
+            CodexHttpErrorStatus::RateLimited => {
+                rate_limited_snapshot("Rate limited.", e.retry_after_seconds)
+            }
+            CodexHttpErrorStatus::Offline => CodexUsageSnapshot::Offline {
+                last_updated_at: now_iso(),
+                error_message: Some(OFFLINE_ERROR_MESSAGE.to_string()),
+            },
+            CodexHttpErrorStatus::Error => match e.message {
+                Some(detail) => error_snapshot(&format!("Failed to fetch Codex usage: {detail}")),
+                None => error_snapshot("Failed to fetch Codex usage."),
+            },
+        },
+    }
+}
+
+const DEFAULT_CODEX_BINARY: &str = "codex";
+const DEFAULT_RPC_TIMEOUT_SECONDS: u64 = 12;
+/// Longest accepted `codexCliTimeoutSeconds`; larger values are capped to it.
+pub const MAX_RPC_TIMEOUT_SECONDS: u64 = 120;
+
+/// How the CLI source launches and talks to `codex app-server`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodexCliOptions {
+    pub binary: String,
+    pub rpc_timeout: Duration,
+    /// Keep the app-server process running between polls.
+    pub keep_alive: bool,
+}
+
+impl CodexCliOptions {
+    /// An empty `path` means `codex` from PATH; a zero timeout means the 12s default.
+    pub fn from_settings(path: Option<String>, timeout_seconds: u64, keep_alive: bool) -> Self {
+        let timeout_seconds = match timeout_seconds {
+            0 => DEFAULT_RPC_TIMEOUT_SECONDS,
+            secs => secs.min(MAX_RPC_TIMEOUT_SECONDS),
+        };
+        Self {
+            binary: path.unwrap_or_else(|| DEFAULT_CODEX_BINARY.to_string()),
+            rpc_timeout: Duration::from_secs(timeout_seconds),
+            keep_alive,
+        }
+    }
+}
+
+fn binary_missing_message(binary: &str) -> String {
+    if binary == DEFAULT_CODEX_BINARY {
+        "Codex CLI missing. Install `@openai/codex` (or ensure `codex` is on PATH).".to_string()
+    } else {
+        format!("Codex CLI not found at {binary}.")
+    }
+}
+
 #[derive(Debug)]
 enum CodexCliError {
     BinaryMissing,
     TimedOut,
     Malformed,
+    NotLoggedIn,
     Failed(String),
     Backoff { retry_in: Duration },
 }
@@ -327,7 +573,7 @@ impl RpcRateLimitWindow {
     fn to_codex_window(&self) -> Option<CodexWindow> {
         let reset_at = self.resets_at?;
         Some(CodexWindow {
-            used_percent: self.used_percent.round() as i64,
+            used_percent: self.used_percent,
             reset_at,
         })
     }
@@ -337,6 +583,14 @@ struct CodexRpcClient {
     state: Mutex<CodexRpcState>,
 }
 
+/// An idle app-server is recycled after this long unless keep-alive is on.
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Whether the next poll can reuse the running app-server instead of spawning a new one.
+fn should_reuse_session(exited: bool, idle_for: Duration, keep_alive: bool) -> bool {
+    !exited && (keep_alive || idle_for <= SESSION_IDLE_TIMEOUT)
+}
+
 impl CodexRpcClient {
     fn new() -> Self {
         Self {
@@ -347,9 +601,12 @@ impl CodexRpcClient {
         }
     }
 
+    /// With `keep_alive` one app-server stays up across refreshes however long the interval;
+    /// otherwise it is recycled once idle. Either way a process that died, or one started with
+    /// different options, is respawned.
     async fn fetch_rate_limits(
         &self,
-        binary: &str,
+        options: &CodexCliOptions,
     ) -> Result<(CodexWindow, CodexWindow), CodexCliError> {
         let mut state = self.state.lock().await;
 
@@ -363,19 +620,19 @@ impl CodexRpcClient {
             state.backoff_until = None;
         }
 
-        // Keep the CLI process around to avoid spawning a new one every refresh, but recycle it if
-        // it sits idle for too long.
-        const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
-        if let Some(session) = state.session.as_ref() {
-            if session.last_used.elapsed() > IDLE_TIMEOUT {
-                if let Some(session) = state.session.take() {
-                    session.shutdown();
-                }
+        if let Some(session) = state.session.as_mut() {
+            let exited = session.child.has_exited();
+            let reconfigured =
+                session.binary != options.binary || session.pipe.timeout != options.rpc_timeout;
+            if reconfigured
+                || !should_reuse_session(exited, session.last_used.elapsed(), options.keep_alive)
+            {
+                state.session = None;
             }
         }
 
         if state.session.is_none() {
-            state.session = Some(CodexRpcSession::spawn(binary).await?);
+            state.session = Some(CodexRpcSession::spawn(options).await?);
         }
 
         let result = match state.session.as_mut() {
@@ -387,14 +644,19 @@ impl CodexRpcClient {
             Ok(value) => Ok(value),
             Err(err) => {
                 // Drop the session and back off to avoid repeated spawns/timeouts.
-                if let Some(session) = state.session.take() {
-                    session.shutdown();
-                }
+                state.session = None;
                 state.backoff_until = Some(Instant::now() + backoff_for_error(&err));
                 Err(err)
             }
         }
     }
+
+    /// Kill the app-server and wait for it to exit.
+    async fn shutdown(&self) {
+        if let Some(session) = self.state.lock().await.session.take() {
+            session.child.kill().await;
+        }
+    }
 }
 
 struct CodexRpcState {
@@ -402,170 +664,220 @@ struct CodexRpcState {
     backoff_until: Option<Instant>,
 }
 
-struct CodexRpcSession {
-    child: tokio::process::Child,
-    stdin: tokio::process::ChildStdin,
-    stdout_lines: tokio::io::Lines<BufReader<tokio::process::ChildStdout>>,
-    stderr_lines: tokio::io::Lines<BufReader<tokio::process::ChildStderr>>,
+/// Newline-delimited JSON-RPC over the app-server's stdin/stdout.
+struct RpcPipe<W, R> {
+    writer: W,
+    lines: tokio::io::Lines<R>,
     next_id: i64,
+    /// Deadline for each request's response.
+    timeout: Duration,
+}
+
+impl<W: AsyncWrite + Unpin, R: AsyncBufRead + Unpin> RpcPipe<W, R> {
+    fn new(writer: W, reader: R, timeout: Duration) -> Self {
+        Self {
+            writer,
+            lines: reader.lines(),
+            next_id: 1,
+            timeout,
+        }
+    }
+
+    /// Send `method` and wait for the response with the same id, skipping notifications and
+    /// responses to other requests.
+    async fn request(&mut self, method: &str, params: Value) -> Result<Value, CodexCliError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.write_line(serde_json::json!({"id": id, "method": method, "params": params}))
+            .await?;
+        tokio::time::timeout(self.timeout, self.read_response(id))
+            .await
+            .map_err(|_| CodexCliError::TimedOut)?
+    }
+
+    async fn notify(&mut self, method: &str, params: Value) -> Result<(), CodexCliError> {
+        self.write_line(serde_json::json!({"method": method, "params": params}))
+            .await
+    }
+
+    async fn write_line(&mut self, payload: Value) -> Result<(), CodexCliError> {
+        let data = serde_json::to_vec(&payload).map_err(|_| CodexCliError::Malformed)?;
+        self.writer
+            .write_all(&data)
+            .await
+            .map_err(|_| CodexCliError::Failed("Codex CLI write failed.".to_string()))?;
+        self.writer
+            .write_all(b"\n")
+            .await
+            .map_err(|_| CodexCliError::Failed("Codex CLI write failed.".to_string()))?;
+        self.writer
+            .flush()
+            .await
+            .map_err(|_| CodexCliError::Failed("Codex CLI write failed.".to_string()))
+    }
+
+    async fn read_response(&mut self, id: i64) -> Result<Value, CodexCliError> {
+        loop {
+            let line = self
+                .lines
+                .next_line()
+                .await
+                .map_err(|_| CodexCliError::Malformed)?;
+            let Some(line) = line else {
+                return Err(CodexCliError::Malformed);
+            };
+            let json: Value = serde_json::from_str(&line).map_err(|_| CodexCliError::Malformed)?;
+            if json.get("id").and_then(|v| v.as_i64()) != Some(id) {
+                continue;
+            }
+            if let Some(err) = json
+                .get("error")
+                .and_then(|v| v.get("message"))
+                .and_then(|v| v.as_str())
+            {
+                return Err(rpc_error_from_message(err));
+            }
+            return Ok(json);
+        }
+    }
+}
+
+/// Owns a spawned process and kills it when dropped, so every way out of a fetch (errors,
+/// timeouts, the fetch itself being cancelled) terminates and reaps it.
+struct ChildGuard {
+    child: Option<tokio::process::Child>,
+}
+
+impl ChildGuard {
+    fn new(child: tokio::process::Child) -> Self {
+        Self { child: Some(child) }
+    }
+
+    fn has_exited(&mut self) -> bool {
+        self.child
+            .as_mut()
+            .is_none_or(|child| matches!(child.try_wait(), Ok(Some(_))))
+    }
+
+    /// Kill the process and wait for it to exit.
+    async fn kill(mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill().await;
+        }
+    }
+}
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        let Some(mut child) = self.child.take() else {
+            return;
+        };
+        let _ = child.start_kill();
+        tauri::async_runtime::spawn(async move {
+            let _ = child.wait().await;
+        });
+    }
+}
+
+struct CodexRpcSession {
+    binary: String,
+    child: ChildGuard,
+    pipe: RpcPipe<tokio::process::ChildStdin, BufReader<tokio::process::ChildStdout>>,
     last_used: Instant,
 }
 
 impl CodexRpcSession {
-    async fn spawn(binary: &str) -> Result<Self, CodexCliError> {
-        let mut child = Command::new(binary)
+    async fn spawn(options: &CodexCliOptions) -> Result<Self, CodexCliError> {
+        // stderr is never read; discarding it keeps a long-lived child from blocking on a full pipe.
+        let mut child = Command::new(&options.binary)
             .args(["-s", "read-only", "-a", "untrusted", "app-server"])
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
+            .stderr(Stdio::null())
             .spawn()
             .map_err(|_| CodexCliError::BinaryMissing)?;
 
-        let stdin = child.stdin.take().ok_or(CodexCliError::Malformed)?;
-        let stdout = child.stdout.take().ok_or(CodexCliError::Malformed)?;
-        let stderr = child.stderr.take().ok_or(CodexCliError::Malformed)?;
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take();
+        let child = ChildGuard::new(child);
+        let (Some(stdin), Some(stdout)) = (stdin, stdout) else {
+            return Err(CodexCliError::Malformed);
+        };
 
         let mut session = Self {
+            binary: options.binary.clone(),
             child,
-            stdin,
-            stdout_lines: BufReader::new(stdout).lines(),
-            stderr_lines: BufReader::new(stderr).lines(),
-            next_id: 1,
+            pipe: RpcPipe::new(stdin, BufReader::new(stdout), options.rpc_timeout),
             last_used: Instant::now(),
         };
 
-        let init_id = session.next_id;
-        session.next_id += 1;
         session
-            .send_request(
-                init_id,
+            .pipe
+            .request(
                 "initialize",
                 serde_json::json!({"clientInfo": {"name": "claudometer", "version": env!("CARGO_PKG_VERSION")}}),
             )
             .await?;
-        let _ = session.read_response(init_id).await?;
         let _ = session
-            .send_notification("initialized", serde_json::json!({}))
+            .pipe
+            .notify("initialized", serde_json::json!({}))
             .await;
 
         Ok(session)
     }
 
-    fn shutdown(mut self) {
-        let _ = self.child.start_kill();
-        let mut child = self.child;
-        tauri::async_runtime::spawn(async move {
-            let _ = child.wait().await;
-        });
-    }
-
     async fn read_rate_limits(&mut self) -> Result<(CodexWindow, CodexWindow), CodexCliError> {
-        if let Ok(Some(_)) = self.child.try_wait() {
-            return Err(CodexCliError::Failed(
-                "Codex CLI exited unexpectedly.".to_string(),
-            ));
-        }
-
-        let limits_id = self.next_id;
-        self.next_id += 1;
-        self.send_request(limits_id, "account/rateLimits/read", serde_json::json!({}))
+        let message = self
+            .pipe
+            .request("account/rateLimits/read", serde_json::json!({}))
             .await?;
-        let message = self.read_response(limits_id).await?;
-
-        let result = message
-            .get("result")
-            .cloned()
-            .ok_or(CodexCliError::Malformed)?;
-        let parsed: RpcRateLimitsResponse =
-            serde_json::from_value(result).map_err(|_| CodexCliError::Malformed)?;
-
-        let primary = parsed
-            .rate_limits
-            .primary
-            .as_ref()
-            .and_then(RpcRateLimitWindow::to_codex_window)
-            .ok_or(CodexCliError::Malformed)?;
-        let secondary = parsed
-            .rate_limits
-            .secondary
-            .as_ref()
-            .and_then(RpcRateLimitWindow::to_codex_window)
-            .ok_or(CodexCliError::Malformed)?;
-
-        Ok((primary, secondary))
-    }
-
-    async fn send_request(
-        &mut self,
-        id: i64,
-        method: &str,
-        params: Value,
-    ) -> Result<(), CodexCliError> {
-        let payload = serde_json::json!({"id": id, "method": method, "params": params});
-        self.write_line(payload).await
-    }
-
-    async fn send_notification(
-        &mut self,
-        method: &str,
-        params: Value,
-    ) -> Result<(), CodexCliError> {
-        let payload = serde_json::json!({"method": method, "params": params});
-        self.write_line(payload).await
-    }
-
-    async fn write_line(&mut self, payload: Value) -> Result<(), CodexCliError> {
-        let data = serde_json::to_vec(&payload).map_err(|_| CodexCliError::Malformed)?;
-        self.stdin
-            .write_all(&data)
-            .await
-            .map_err(|_| CodexCliError::Failed("Codex CLI write failed.".to_string()))?;
-        self.stdin
-            .write_all(b"\n")
-            .await
-            .map_err(|_| CodexCliError::Failed("Codex CLI write failed.".to_string()))?;
         self.last_used = Instant::now();
-        Ok(())
+        parse_rate_limits_message(&message)
     }
+}
 
-    async fn read_response(&mut self, id: i64) -> Result<Value, CodexCliError> {
-        let deadline = Instant::now() + Duration::from_secs(12);
-        loop {
-            let now = Instant::now();
-            if now >= deadline {
-                return Err(CodexCliError::TimedOut);
-            }
-            let timeout = deadline - now;
-
-            tokio::select! {
-                biased;
-                line = tokio::time::timeout(timeout, self.stdout_lines.next_line()) => {
-                    let line = match line {
-                        Ok(v) => v.map_err(|_| CodexCliError::Malformed)?,
-                        Err(_) => return Err(CodexCliError::TimedOut),
-                    };
-                    let Some(line) = line else {
-                        return Err(CodexCliError::Malformed);
-                    };
-                    let json: Value = serde_json::from_str(&line).map_err(|_| CodexCliError::Malformed)?;
-                    if json.get("id").and_then(|v| v.as_i64()) != Some(id) {
-                        continue;
-                    }
-                    if let Some(err) = json.get("error").and_then(|v| v.get("message")).and_then(|v| v.as_str()) {
-                        return Err(CodexCliError::Failed(redact_secrets(err).to_string()));
-                    }
-                    self.last_used = Instant::now();
-                    return Ok(json);
-                }
-                line = tokio::time::timeout(timeout, self.stderr_lines.next_line()) => {
-                    let line = match line {
-                        Ok(v) => v.map_err(|_| CodexCliError::Malformed)?,
-                        Err(_) => return Err(CodexCliError::TimedOut),
-                    };
-                    let _ = line;
-                }
-            }
-        }
+fn parse_rate_limits_message(message: &Value) -> Result<(CodexWindow, CodexWindow), CodexCliError> {
+    let result = message
+        .get("result")
+        .cloned()
+        .ok_or(CodexCliError::Malformed)?;
+    let parsed: RpcRateLimitsResponse =
+        serde_json::from_value(result).map_err(|_| CodexCliError::Malformed)?;
+
+    let primary = parsed
+        .rate_limits
+        .primary
+        .as_ref()
+        .and_then(RpcRateLimitWindow::to_codex_window)
+        .ok_or(CodexCliError::Malformed)?;
+    let secondary = parsed
+        .rate_limits
+        .secondary
+        .as_ref()
+        .and_then(RpcRateLimitWindow::to_codex_window)
+        .ok_or(CodexCliError::Malformed)?;
+
+    Ok((primary, secondary))
+}
+
+/// The app-server reports missing auth as a plain RPC error; tell it apart from other failures so
+/// the user gets a login hint instead of a generic error.
+fn rpc_error_from_message(message: &str) -> CodexCliError {
+    let lower = message.to_ascii_lowercase();
+    let auth_required = [
+        "not logged in",
+        "login required",
+        "auth required",
+        "authentication required",
+        "unauthorized",
+        "no credentials",
+    ]
+    .iter()
+    .any(|needle| lower.contains(needle));
+    if auth_required {
+        CodexCliError::NotLoggedIn
+    } else {
+        CodexCliError::Failed(redact_secrets(message).to_string())
     }
 }
 
@@ -574,6 +886,7 @@ fn backoff_for_error(error: &CodexCliError) -> Duration {
         CodexCliError::BinaryMissing => Duration::from_secs(15 * 60),
         CodexCliError::TimedOut => Duration::from_secs(10 * 60),
         CodexCliError::Malformed => Duration::from_secs(5 * 60),
+        CodexCliError::NotLoggedIn => Duration::from_secs(5 * 60),
         CodexCliError::Failed(_) => Duration::from_secs(5 * 60),
         CodexCliError::Backoff { .. } => Duration::from_secs(5 * 60),
     }
@@ -583,13 +896,81 @@ fn backoff_for_error(error: &CodexCliError) -> Duration {
 mod tests {
     use super::*;
 
+    fn jwt_with_exp(exp: i64) -> String {
+        // Header and payload are base64url without padding; the signature is never checked.
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+        let payload = format!("{{\"exp\":{exp}}}");
+        let mut encoded = String::new();
+        for chunk in payload.as_bytes().chunks(3) {
+            let n = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |acc, (i, b)| acc | (u32::from(*b) << (16 - 8 * i)));
+            for i in 0..=chunk.len() {
+                encoded.push(ALPHABET[((n >> (18 - 6 * i)) & 63) as usize] as char);
+            }
+        }
+        format!("eyJhbGciOiJub25lIn0.{encoded}.sig")
+    }
+
+    #[test]
+    fn oauth_credentials_parse_expiry() {
+        let json = serde_json::json!({
+            "tokens": {
+                "access_token": jwt_with_exp(1_767_740_400),
+                "refresh_token": "rt-1",
+                "account_id": "acct-1"
+            }
+        });
+        let creds = parse_codex_oauth_credentials(&json).unwrap();
+        assert_eq!(creds.account_id.as_deref(), Some("acct-1"));
+        assert_eq!(creds.expires_at, Some(1_767_740_400));
+
+        let json = serde_json::json!({ "tokens": { "access_token": "opaque-token" } });
+        let creds = parse_codex_oauth_credentials(&json).unwrap();
+        assert_eq!(creds.expires_at, None);
+
+        let json = serde_json::json!({ "tokens": { "refresh_token": "rt-1" } });
+        assert!(matches!(
+            parse_codex_oauth_credentials(&json),
+            Err(CodexCredentialsError::MissingAccessToken)
+        ));
+    }
+
+    #[test]
+    fn oauth_token_expiry_uses_a_skew_and_ignores_unknown_expiry() {
+        assert!(oauth_token_expired(Some(1_000), 1_000));
+        assert!(oauth_token_expired(Some(1_030), 1_000));
+        assert!(!oauth_token_expired(Some(2_000), 1_000));
+        assert!(!oauth_token_expired(None, 1_000));
+    }
+
     #[test]
     fn parse_oauth_fixture_maps_windows() {
         let data = include_str!("fixtures/codex_oauth_usage_ok.json");
         let json: Value = serde_json::from_str(data).unwrap();
         let (primary, secondary) = parse_codex_usage_response(json).unwrap();
-        assert_eq!(primary.used_percent, 25);
-        assert_eq!(secondary.used_percent, 40);
+        assert_eq!(primary.used_percent, 25.6);
+        assert_eq!(secondary.unwrap().used_percent, 40.0);
+    }
+
+    #[test]
+    fn parse_oauth_fixture_without_weekly_window() {
+        let data = include_str!("fixtures/codex_oauth_usage_primary_only.json");
+        let json: Value = serde_json::from_str(data).unwrap();
+        let (primary, secondary) = parse_codex_usage_response(json).unwrap();
+        assert_eq!(primary.used_percent, 12.0);
+        assert!(secondary.is_none());
+
+        let CodexUsageSnapshot::Ok {
+            weekly_percent,
+            weekly_resets_at,
+            ..
+        } = ok_snapshot(primary, secondary, None)
+        else {
+            panic!("expected an Ok snapshot");
+        };
+        assert_eq!((weekly_percent, weekly_resets_at), (None, None));
     }
 
     #[test]
@@ -604,6 +985,165 @@ mod tests {
             .as_ref()
             .and_then(RpcRateLimitWindow::to_codex_window)
             .unwrap();
-        assert_eq!(primary.used_percent, 33);
+        assert_eq!(primary.used_percent, 33.2);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn child_guard_reaps_the_process_after_a_timeout() {
+        let mut child = Command::new("sleep")
+            .arg("30")
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let pid = child.id().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let guard = ChildGuard::new(child);
+
+        let mut lines = BufReader::new(stdout).lines();
+        let read = tokio::time::timeout(Duration::from_millis(50), lines.next_line()).await;
+        assert!(read.is_err());
+        drop(guard);
+
+        // Gone from the process table once killed and waited on (not left as a zombie).
+        let proc_path = PathBuf::from(format!("/proc/{pid}"));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while proc_path.exists() && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(!proc_path.exists());
+    }
+
+    #[test]
+    fn codex_cookie_accepts_a_header_or_a_bare_session_token() {
+        assert_eq!(
+            normalize_codex_cookie(" Cookie: a=1; b=2 ").as_deref(),
+            Ok("a=1; b=2")
+        );
+        assert_eq!(
+            normalize_codex_cookie("token-value").as_deref(),
+            Ok("__Secure-next-auth.session-token=token-value")
+        );
+        assert!(normalize_codex_cookie("  ").is_err());
+        assert!(normalize_codex_cookie("a=1\nb=2").is_err());
+    }
+
+    #[test]
+    fn codex_cookie_warning_flags_cookies_without_the_session_token() {
+        let bare = normalize_codex_cookie("token-value").unwrap();
+        assert_eq!(codex_cookie_warning(&bare), None);
+        let header = "Cookie: oai-did=1; __Secure-next-auth.session-token=abc; _cf=2";
+        assert_eq!(
+            codex_cookie_warning(&normalize_codex_cookie(header).unwrap()),
+            None
+        );
+        let chunked =
+            "__Secure-next-auth.session-token.0=abc; __Secure-next-auth.session-token.1=def";
+        assert_eq!(codex_cookie_warning(chunked), None);
+
+        assert!(codex_cookie_warning("oai-did=1; _cf=2").is_some());
+        assert!(
+            codex_cookie_warning("__Secure-next-auth.callback-url=https%3A%2F%2Fchatgpt.com")
+                .is_some()
+        );
+        assert!(codex_cookie_warning("__Secure-next-auth.session-tokenx=abc").is_some());
+    }
+
+    #[test]
+    fn rpc_auth_required_error_maps_to_not_logged_in() {
+        assert!(matches!(
+            rpc_error_from_message("Authentication required: please log in"),
+            CodexCliError::NotLoggedIn
+        ));
+        assert!(matches!(
+            rpc_error_from_message("Not logged in"),
+            CodexCliError::NotLoggedIn
+        ));
+        assert!(matches!(
+            rpc_error_from_message("unknown method"),
+            CodexCliError::Failed(msg) if msg == "unknown method"
+        ));
+    }
+
+    #[tokio::test]
+    async fn rpc_pipe_reuses_one_stream_and_matches_response_ids() {
+        let (client, server) = tokio::io::duplex(4096);
+        let (client_read, client_write) = tokio::io::split(client);
+        let mut pipe = RpcPipe::new(
+            client_write,
+            BufReader::new(client_read),
+            Duration::from_secs(12),
+        );
+
+        let server = tokio::spawn(async move {
+            let (server_read, mut server_write) = tokio::io::split(server);
+            let mut requests = BufReader::new(server_read).lines();
+            let mut methods = Vec::new();
+            while let Ok(Some(line)) = requests.next_line().await {
+                let request: Value = serde_json::from_str(&line).unwrap();
+                let id = request["id"].as_i64().unwrap();
+                methods.push(request["method"].as_str().unwrap().to_string());
+                let reply = if id == 1 {
+                    // Notifications and stale responses are skipped while waiting for id 1.
+                    "{\"method\":\"account/updated\"}\n{\"id\":99,\"result\":{}}\n{\"id\":1,\"result\":{\"rateLimits\":{\"primary\":{\"usedPercent\":33.2,\"resetsAt\":1760000000},\"secondary\":{\"usedPercent\":10,\"resetsAt\":1760500000}}}}\n".to_string()
+                } else {
+                    format!("{{\"id\":{id},\"error\":{{\"message\":\"Not logged in\"}}}}\n")
+                };
+                server_write.write_all(reply.as_bytes()).await.unwrap();
+                if id == 2 {
+                    break;
+                }
+            }
+            methods
+        });
+
+        let message = pipe
+            .request("account/rateLimits/read", serde_json::json!({}))
+            .await
+            .unwrap();
+        let (primary, secondary) = parse_rate_limits_message(&message).unwrap();
+        assert_eq!((primary.used_percent, primary.reset_at), (33.2, 1760000000));
+        assert_eq!(secondary.used_percent, 10.0);
+
+        assert!(matches!(
+            pipe.request("account/rateLimits/read", serde_json::json!({}))
+                .await,
+            Err(CodexCliError::NotLoggedIn)
+        ));
+        assert_eq!(
+            server.await.unwrap(),
+            ["account/rateLimits/read", "account/rateLimits/read"]
+        );
+
+        // The other end closing (process death) surfaces as an error instead of hanging.
+        assert!(pipe
+            .request("account/rateLimits/read", serde_json::json!({}))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn dead_or_idle_sessions_are_respawned() {
+        let idle = SESSION_IDLE_TIMEOUT + Duration::from_secs(1);
+        assert!(should_reuse_session(false, Duration::from_secs(60), false));
+        assert!(!should_reuse_session(false, idle, false));
+        assert!(should_reuse_session(false, idle, true));
+        assert!(!should_reuse_session(true, Duration::ZERO, true));
+    }
+
+    #[test]
+    fn cli_options_default_to_codex_on_path() {
+        let defaults = CodexCliOptions::from_settings(None, 0, false);
+        assert_eq!(defaults.binary, "codex");
+        assert_eq!(defaults.rpc_timeout, Duration::from_secs(12));
+        assert!(binary_missing_message(&defaults.binary).contains("on PATH"));
+
+        let custom =
+            CodexCliOptions::from_settings(Some("/opt/codex/bin/codex".to_string()), 600, true);
+        assert_eq!(custom.rpc_timeout, Duration::from_secs(120));
+        assert_eq!(
+            binary_missing_message(&custom.binary),
+            "Codex CLI not found at /opt/codex/bin/codex."
+        );
     }
 }
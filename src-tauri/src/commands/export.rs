@@ -0,0 +1,185 @@
+use crate::claude::read_cli_oauth_access_token;
+use crate::codex::read_codex_oauth_credentials;
+use crate::redact::redact_secrets;
+use crate::settings::debug_mode_enabled;
+use crate::state::AppState;
+use crate::support_bundle::usage_report_json;
+use crate::types::{CodexUsageSource, IpcError, IpcErrorCode, IpcResult, UsageSource};
+use serde_json::json;
+use tauri::{AppHandle, Runtime, State};
+
+type CommandResult<T> = Result<T, IpcError>;
+
+fn now_iso() -> String {
+    time::OffsetDateTime::now_utc()
+        .format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
+}
+
+/// The current snapshot plus which providers are tracked and from which source, as pretty JSON.
+/// Shared by `export_usage_json` and the tray's "Save usage report…" item.
+pub(crate) async fn usage_report<R: Runtime>(state: &AppState<R>) -> String {
+    let snapshot = state.latest_snapshot.lock().await.clone();
+    let metadata = json!({
+        "appVersion": env!("CARGO_PKG_VERSION"),
+        "exportedAt": now_iso(),
+        "trackClaude": state.track_claude_enabled(),
+        "trackCodex": state.track_codex_enabled(),
+        "claudeUsageSource": state.usage_source(),
+        "codexUsageSource": state.codex_usage_source(),
+    });
+    usage_report_json(metadata, snapshot.as_ref())
+}
+
+/// Usage report as a JSON string (see `usage_report`).
+#[tauri::command]
+pub async fn export_usage_json<R: Runtime>(
+    state: State<'_, AppState<R>>,
+) -> CommandResult<IpcResult<String>> {
+    Ok(IpcResult::ok(usage_report(&state).await))
+}
+
+/// Debug helper: one-off fetch of `provider`'s (`"claude"` or `"codex"`) usage endpoint for the
+/// configured source, returning the response body unparsed (secrets redacted). Only available
+/// when `CLAUDOMETER_DEBUG` is set.
+#[tauri::command]
+pub async fn fetch_raw_usage<R: Runtime>(
+    state: State<'_, AppState<R>>,
+    provider: String,
+) -> CommandResult<IpcResult<String>> {
+    if !debug_mode_enabled() {
+        return Ok(IpcResult::err(
+            IpcErrorCode::Validation,
+            "Raw usage is only available with CLAUDOMETER_DEBUG set.",
+        ));
+    }
+    let body = match provider.as_str() {
+        "claude" => raw_claude_usage(&state).await,
+        "codex" => raw_codex_usage(&state).await,
+        _ => Err("Unknown provider.".to_string()),
+    };
+    Ok(match body {
+        Ok(body) => IpcResult::ok(redact_secrets(&body).to_string()),
+        Err(message) => IpcResult::err(IpcErrorCode::Unknown, message),
+    })
+}
+
+async fn raw_claude_usage<R: Runtime>(state: &AppState<R>) -> Result<String, String> {
+    match state.usage_source() {
+        UsageSource::Web => {
+            let session_key = match state
+                .claude_session_key
+                .get_current(state.remember_session_key())
+                .await
+            {
+                Ok(Some(key)) => key,
+                Ok(None) => return Err("Claude session key is not configured.".to_string()),
+                Err(()) => return Err("OS keychain/secret service is unavailable.".to_string()),
+            };
+            let Some(org_id) = state.selected_org_id() else {
+                return Err("No Claude organization selected yet; refresh first.".to_string());
+            };
+            state.claude.fetch_raw_usage(&session_key, &org_id).await
+        }
+        UsageSource::Cli => match read_cli_oauth_access_token() {
+            Ok(token) => state.claude.fetch_raw_oauth_usage(&token).await,
+            Err(_) => Err("Claude CLI credentials are unavailable.".to_string()),
+        },
+    }
+}
+
+async fn raw_codex_usage<R: Runtime>(state: &AppState<R>) -> Result<String, String> {
+    let source = match state.codex_usage_source() {
+        // Whichever source the last Auto refresh settled on.
+        CodexUsageSource::Auto => match state.codex_auto_resolution.lock().await.as_ref() {
+            Some(resolution) => resolution.source,
+            None => return Err("Codex Auto hasn't picked a source yet; refresh first.".to_string()),
+        },
+        source => source,
+    };
+    match source {
+        CodexUsageSource::Oauth => match read_codex_oauth_credentials() {
+            Ok(creds) => {
+                state
+                    .codex
+                    .fetch_raw_oauth_usage(&creds.access_token, creds.account_id.as_deref())
+                    .await
+            }
+            Err(_) => Err("Codex credentials not found.".to_string()),
+        },
+        CodexUsageSource::Web => {
+            match state
+                .codex_cookie
+                .get_current(state.remember_codex_cookie())
+                .await
+            {
+                Ok(Some(cookie)) => state.codex.fetch_raw_web_usage(&cookie).await,
+                Ok(None) => Err("Codex cookie is not configured.".to_string()),
+                Err(()) => Err("OS keychain/secret service is unavailable.".to_string()),
+            }
+        }
+        CodexUsageSource::Cli | CodexUsageSource::Auto => {
+            Err("The Codex CLI source has no HTTP response to show.".to_string())
+        }
+    }
+}
+
+/// Debug helper: render the current usage summary to a PNG under the app data dir and return its
+/// path. Only available in builds with the `menu-export` feature.
+#[tauri::command]
+pub async fn debug_export_menu_png<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState<R>>,
+) -> CommandResult<IpcResult<String>> {
+    #[cfg(feature = "menu-export")]
+    {
+        use crate::menu_export::{export_path, render_png};
+
+        let snapshot = state.latest_snapshot.lock().await.clone();
+        let lines = crate::tray::summary_lines(
+            state.track_claude_enabled(),
+            state.track_codex_enabled(),
+            &state.tray_options(),
+            snapshot.as_ref(),
+        );
+
+        let data_dir = match crate::data_dir::app_data_dir(&app) {
+            Ok(dir) => dir,
+            Err(e) => {
+                return Ok(IpcResult::err(
+                    IpcErrorCode::Unknown,
+                    redact_secrets(&e.to_string()).to_string(),
+                ))
+            }
+        };
+        let file_name = format!(
+            "menu-{}.png",
+            time::OffsetDateTime::now_utc().unix_timestamp()
+        );
+        let path = match export_path(&data_dir, &file_name) {
+            Ok(path) => path,
+            Err(message) => return Ok(IpcResult::err(IpcErrorCode::Validation, message)),
+        };
+
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&path, render_png(&lines)));
+        Ok(match written {
+            Ok(()) => IpcResult::ok(path.to_string_lossy().to_string()),
+            Err(e) => IpcResult::err(
+                IpcErrorCode::Unknown,
+                redact_secrets(&e.to_string()).to_string(),
+            ),
+        })
+    }
+
+    #[cfg(not(feature = "menu-export"))]
+    {
+        let _ = (app, state);
+        Ok(IpcResult::err(
+            IpcErrorCode::Validation,
+            "Menu export is not available in this build.",
+        ))
+    }
+}
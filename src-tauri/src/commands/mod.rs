@@ -1,5 +1,11 @@
+mod export;
 mod settings;
+mod support;
 mod updates;
+mod usage_data;
 
+pub use export::*;
 pub use settings::*;
+pub use support::*;
 pub use updates::*;
+pub use usage_data::*;
@@ -0,0 +1,48 @@
+use crate::redact::redact_secrets;
+use crate::state::AppState;
+use crate::support_bundle::{bundle_entries, render_zip};
+use crate::types::{IpcError, IpcErrorCode, IpcResult};
+use tauri::{AppHandle, Runtime, State};
+
+type CommandResult<T> = Result<T, IpcError>;
+
+/// Write a redacted support bundle (app info, settings, recent alerts, last snapshot) as a zip under
+/// the app data dir and return its path, for attaching to bug reports.
+#[tauri::command]
+pub async fn export_support_bundle<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState<R>>,
+) -> CommandResult<IpcResult<String>> {
+    let snapshot = state.latest_snapshot.lock().await.clone();
+    let entries = bundle_entries(
+        env!("CARGO_PKG_VERSION"),
+        state.settings.entries(),
+        snapshot.as_ref(),
+    );
+
+    let data_dir = match crate::data_dir::app_data_dir(&app) {
+        Ok(dir) => dir,
+        Err(e) => {
+            return Ok(IpcResult::err(
+                IpcErrorCode::Unknown,
+                redact_secrets(&e.to_string()).to_string(),
+            ))
+        }
+    };
+    let path = data_dir.join("support").join(format!(
+        "claudometer-support-{}.zip",
+        time::OffsetDateTime::now_utc().unix_timestamp()
+    ));
+
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(&path, render_zip(entries)));
+    Ok(match written {
+        Ok(()) => IpcResult::ok(path.to_string_lossy().to_string()),
+        Err(e) => IpcResult::err(
+            IpcErrorCode::Unknown,
+            redact_secrets(&e.to_string()).to_string(),
+        ),
+    })
+}
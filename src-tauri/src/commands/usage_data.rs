@@ -0,0 +1,86 @@
+use crate::burn_rate::burn_rates;
+use crate::settings::{SettingsStore, KEY_CACHED_SNAPSHOT, KEY_DAILY_PEAKS, KEY_USAGE_HISTORY};
+use crate::staleness::now_unix_ms;
+use crate::state::AppState;
+use crate::types::{BurnRate, IpcError, IpcResult, UsageHistorySample};
+use tauri::{AppHandle, Runtime, State};
+
+type CommandResult<T> = Result<T, IpcError>;
+
+/// Drop today's peaks, the usage history and the startup cache.
+fn clear_retained_usage_data<R: Runtime>(
+    settings: &SettingsStore<R>,
+    usage_data: &SettingsStore<R>,
+) {
+    settings.remove(KEY_DAILY_PEAKS);
+    for key in [KEY_USAGE_HISTORY, KEY_CACHED_SNAPSHOT] {
+        usage_data.remove(key);
+    }
+}
+
+/// Privacy/cleanup action: drop retained usage data (today's peaks, the usage history and the
+/// startup cache). The live `latest_snapshot` is kept so the tray keeps showing current usage.
+#[tauri::command]
+pub async fn clear_usage_data<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState<R>>,
+) -> CommandResult<IpcResult<()>> {
+    clear_retained_usage_data(&state.settings, &state.usage_data);
+
+    let latest = state.latest_snapshot.lock().await.clone();
+    state.update_snapshot(&app, latest).await;
+
+    Ok(IpcResult::ok(()))
+}
+
+/// Recorded usage samples for both providers (oldest first) within the retention window.
+#[tauri::command]
+pub async fn get_usage_history<R: Runtime>(
+    state: State<'_, AppState<R>>,
+) -> CommandResult<Vec<UsageHistorySample>> {
+    Ok(state.usage_history())
+}
+
+/// Current burn rate of each climbing provider window, projected from the usage history.
+#[tauri::command]
+pub async fn get_burn_rates<R: Runtime>(
+    state: State<'_, AppState<R>>,
+) -> CommandResult<Vec<BurnRate>> {
+    Ok(burn_rates(&state.usage_history(), now_unix_ms()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn clear_retained_usage_data_removes_history_peaks_and_cache() {
+        let app = tauri::test::mock_builder()
+            .plugin(tauri_plugin_store::Builder::default().build())
+            .build(tauri::test::mock_context(tauri::test::noop_assets()))
+            .unwrap();
+        let path = |name: &str| {
+            std::env::temp_dir().join(format!(
+                "claudometer-clear-{name}-{}.json",
+                std::process::id()
+            ))
+        };
+        let settings = SettingsStore::open(app.handle(), path("settings")).unwrap();
+        let usage_data = SettingsStore::open(app.handle(), path("usage-data")).unwrap();
+        settings.set(
+            KEY_DAILY_PEAKS,
+            json!({ "2026-01-01": { "claudeSession": 50.0 } }),
+        );
+        usage_data.set(KEY_USAGE_HISTORY, json!([{ "timestampMs": 1 }]));
+        usage_data.set(KEY_CACHED_SNAPSHOT, json!({ "claude": null }));
+
+        clear_retained_usage_data(&settings, &usage_data);
+
+        assert!(settings.get_json(KEY_DAILY_PEAKS).is_none());
+        assert!(usage_data.get_json(KEY_USAGE_HISTORY).is_none());
+        assert!(usage_data.get_json(KEY_CACHED_SNAPSHOT).is_none());
+        let _ = std::fs::remove_file(path("settings"));
+        let _ = std::fs::remove_file(path("usage-data"));
+    }
+}
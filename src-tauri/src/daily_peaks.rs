@@ -0,0 +1,139 @@
+use crate::types::{ClaudeUsageSnapshot, CodexUsageSnapshot, UsageSnapshotBundle};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProviderPeaks {
+    pub session_percent: Option<f64>,
+    pub weekly_percent: Option<f64>,
+}
+
+impl ProviderPeaks {
+    fn record(&mut self, session_percent: f64, weekly_percent: Option<f64>) -> bool {
+        let session_changed = raise(&mut self.session_percent, session_percent);
+        let weekly_changed =
+            weekly_percent.is_some_and(|weekly| raise(&mut self.weekly_percent, weekly));
+        session_changed || weekly_changed
+    }
+}
+
+/// Highest session/weekly usage seen per provider during one local calendar day.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DailyPeaks {
+    /// Local date (`YYYY-MM-DD`) the peaks belong to.
+    pub date: String,
+    pub claude: ProviderPeaks,
+    pub codex: ProviderPeaks,
+}
+
+fn raise(slot: &mut Option<f64>, value: f64) -> bool {
+    if !value.is_finite() {
+        return false;
+    }
+    match *slot {
+        Some(current) if current >= value => false,
+        _ => {
+            *slot = Some(value);
+            true
+        }
+    }
+}
+
+pub fn local_date_key() -> String {
+    chrono::Local::now().date_naive().to_string()
+}
+
+impl DailyPeaks {
+    /// Peaks for `today`, or empty peaks when the stored ones belong to another day.
+    pub fn for_date(self, today: &str) -> Self {
+        if self.date == today {
+            self
+        } else {
+            Self {
+                date: today.to_string(),
+                ..Self::default()
+            }
+        }
+    }
+
+    /// Fold the Ok parts of `snapshot` into the peaks, resetting first when the day changed.
+    /// Returns whether anything changed (so callers only persist when needed).
+    pub fn record(&mut self, today: &str, snapshot: &UsageSnapshotBundle) -> bool {
+        let mut changed = false;
+        if self.date != today {
+            *self = std::mem::take(self).for_date(today);
+            changed = true;
+        }
+
+        if let Some(ClaudeUsageSnapshot::Ok {
+            session_percent,
+            weekly_percent,
+            ..
+        }) = snapshot.claude.as_ref()
+        {
+            changed |= self.claude.record(*session_percent, *weekly_percent);
+        }
+
+        if let Some(CodexUsageSnapshot::Ok {
+            session_percent,
+            weekly_percent,
+            ..
+        }) = snapshot.codex.as_ref()
+        {
+            changed |= self.codex.record(*session_percent, *weekly_percent);
+        }
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claude_ok(session_percent: f64, weekly_percent: f64) -> UsageSnapshotBundle {
+        UsageSnapshotBundle {
+            claude: Some(ClaudeUsageSnapshot::Ok {
+                organization_id: "org".to_string(),
+                session_percent,
+                session_resets_at: None,
+                weekly_percent: Some(weekly_percent),
+                weekly_resets_at: None,
+                models: vec![],
+                session_models: vec![],
+                last_updated_at: "2026-01-01T00:00:00Z".to_string(),
+                provider_timestamp: None,
+            }),
+            codex: None,
+            offline: false,
+            claude_orgs: vec![],
+        }
+    }
+
+    #[test]
+    fn record_keeps_the_highest_value_seen_today() {
+        let mut peaks = DailyPeaks::default();
+        assert!(peaks.record("2026-01-01", &claude_ok(40.0, 70.0)));
+        assert!(peaks.record("2026-01-01", &claude_ok(78.0, 65.0)));
+        assert!(!peaks.record("2026-01-01", &claude_ok(20.0, 60.0)));
+
+        assert_eq!(peaks.claude.session_percent, Some(78.0));
+        assert_eq!(peaks.claude.weekly_percent, Some(70.0));
+        assert_eq!(peaks.codex, ProviderPeaks::default());
+    }
+
+    #[test]
+    fn record_resets_on_a_new_local_date() {
+        let mut peaks = DailyPeaks::default();
+        peaks.record("2026-01-01", &claude_ok(90.0, 90.0));
+        assert!(peaks.record("2026-01-02", &claude_ok(5.0, 50.0)));
+
+        assert_eq!(peaks.date, "2026-01-02");
+        assert_eq!(peaks.claude.session_percent, Some(5.0));
+        assert_eq!(peaks.claude.weekly_percent, Some(50.0));
+
+        let stale = peaks.clone().for_date("2026-01-03");
+        assert_eq!(stale.claude, ProviderPeaks::default());
+    }
+}
@@ -0,0 +1,64 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager as _, Runtime};
+
+/// Overrides where settings and other app data live (portable installs, testing).
+pub const DATA_DIR_ENV: &str = "CLAUDOMETER_DATA_DIR";
+
+/// The override directory from `value`, if it is an absolute path that exists or can be created.
+/// Anything else is ignored so a bad override falls back to the default location.
+fn override_dir(value: Option<OsString>) -> Option<PathBuf> {
+    let path = PathBuf::from(value?);
+    if path.as_os_str().is_empty() || !path.is_absolute() {
+        return None;
+    }
+    std::fs::create_dir_all(&path).ok()?;
+    Some(path)
+}
+
+pub fn data_dir_override() -> Option<PathBuf> {
+    override_dir(std::env::var_os(DATA_DIR_ENV))
+}
+
+/// App data directory: `CLAUDOMETER_DATA_DIR` when usable, otherwise Tauri's default.
+pub fn app_data_dir<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<PathBuf> {
+    match data_dir_override() {
+        Some(dir) => Ok(dir),
+        None => app.path().app_data_dir(),
+    }
+}
+
+/// Path handed to the store plugin. Relative paths are resolved by the plugin against Tauri's
+/// default app data dir, so only the override needs to be absolute.
+pub fn store_path(override_dir: Option<&Path>, file_name: &str) -> PathBuf {
+    match override_dir {
+        Some(dir) => dir.join(file_name),
+        None => PathBuf::from(file_name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_store_uses_override_dir_when_valid() {
+        let dir = std::env::temp_dir().join(format!("claudometer-data-dir-{}", std::process::id()));
+        let resolved = override_dir(Some(dir.clone().into_os_string())).unwrap();
+        assert!(dir.is_dir());
+        assert_eq!(
+            store_path(Some(&resolved), "claudometer-settings.json"),
+            dir.join("claudometer-settings.json")
+        );
+
+        assert_eq!(override_dir(Some(OsString::from("relative/dir"))), None);
+        assert_eq!(override_dir(Some(OsString::new())), None);
+        assert_eq!(override_dir(None), None);
+        assert_eq!(
+            store_path(None, "claudometer-settings.json"),
+            PathBuf::from("claudometer-settings.json")
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,289 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::ops::RangeInclusive;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Accepted values for the `httpTimeoutSeconds` setting.
+pub const HTTP_TIMEOUT_SECONDS_RANGE: RangeInclusive<u64> = 5..=120;
+
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Request and connect timeouts for the API clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpTimeouts {
+    pub request: Duration,
+    pub connect: Duration,
+}
+
+impl Default for HttpTimeouts {
+    fn default() -> Self {
+        Self {
+            request: Duration::from_secs(40),
+            connect: DEFAULT_CONNECT_TIMEOUT,
+        }
+    }
+}
+
+impl HttpTimeouts {
+    /// From the `httpTimeoutSeconds` setting, clamped to `HTTP_TIMEOUT_SECONDS_RANGE`. The connect
+    /// timeout stays at 10s unless the whole request must finish sooner.
+    pub fn from_seconds(seconds: u64) -> Self {
+        let request = Duration::from_secs(seconds.clamp(
+            *HTTP_TIMEOUT_SECONDS_RANGE.start(),
+            *HTTP_TIMEOUT_SECONDS_RANGE.end(),
+        ));
+        Self {
+            request,
+            connect: request.min(DEFAULT_CONNECT_TIMEOUT),
+        }
+    }
+}
+
+/// Error message for a fetch that failed because the host couldn't be reached at all (no network,
+/// DNS failure). The refresh loop keeps the last good values instead of showing this as an error.
+pub const OFFLINE_ERROR_MESSAGE: &str = "Offline: couldn't reach the server.";
+
+/// Whether `error` means the server couldn't be reached (no connection, or it timed out), as
+/// opposed to a server or HTTP failure.
+pub fn is_connectivity_error(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout()
+}
+
+/// Seconds until `value` (a `Retry-After` header: delta-seconds or an HTTP date) says to retry.
+/// Dates in the past mean "now", i.e. 0.
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(seconds);
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&Utc) - now).num_seconds().max(0) as u64)
+}
+
+/// The parsed `Retry-After` header of a response, if present and well-formed.
+pub fn retry_after_seconds(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, Utc::now())
+}
+
+/// `value` (an HTTP date, e.g. a `Date` header) as an RFC 3339 UTC timestamp.
+fn parse_http_date(value: &str) -> Option<String> {
+    let at = DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    Some(
+        at.with_timezone(&Utc)
+            .to_rfc3339_opts(SecondsFormat::Secs, true),
+    )
+}
+
+/// When the server generated a response, from its `Date` header (RFC 9110 section 6.6.1).
+/// Neither usage API reports when its numbers were computed, so this is the provider-side
+/// timestamp the "data age" display shows.
+pub fn provider_timestamp(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    parse_http_date(headers.get(reqwest::header::DATE)?.to_str().ok()?)
+}
+
+/// Response body of `request` as text, prefixed with the HTTP status when it isn't a success.
+pub async fn raw_response_body(request: reqwest::RequestBuilder) -> Result<String, String> {
+    let res = request
+        .send()
+        .await
+        .map_err(|e| crate::redact::redact_secrets(&e.to_string()).to_string())?;
+    let status = res.status();
+    let body = res
+        .text()
+        .await
+        .map_err(|e| crate::redact::redact_secrets(&e.to_string()).to_string())?;
+    if status.is_success() {
+        Ok(body)
+    } else {
+        Ok(format!("HTTP {}\n{body}", status.as_u16()))
+    }
+}
+
+/// `message` with the server's requested wait appended, e.g. "Rate limited; retry in 120s."
+pub fn with_retry_after(message: &str, retry_after_seconds: Option<u64>) -> String {
+    match retry_after_seconds {
+        Some(seconds) => format!("{}; retry in {seconds}s.", message.trim_end_matches('.')),
+        None => message.to_string(),
+    }
+}
+
+/// Checked in order when no proxy is configured in settings.
+const PROXY_ENV_VARS: [&str; 6] = [
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+];
+
+/// Whether `host` matches a `NO_PROXY` list (comma separated; `*`, `example.com` and
+/// `.example.com` all cover subdomains; ports are ignored).
+fn no_proxy_matches(no_proxy: &str, host: &str) -> bool {
+    let host = host.to_ascii_lowercase();
+    no_proxy.split(',').map(str::trim).any(|entry| {
+        let entry = entry.split(':').next().unwrap_or_default();
+        let entry = entry.trim_start_matches('.').to_ascii_lowercase();
+        entry == "*"
+            || (!entry.is_empty() && (host == entry || host.ends_with(&format!(".{entry}"))))
+    })
+}
+
+/// Proxy URL for requests to `host`: the settings override first, then the usual env vars,
+/// unless `NO_PROXY` excludes the host.
+fn resolve_proxy(
+    proxy_override: Option<&str>,
+    env: impl Fn(&str) -> Option<String>,
+    host: &str,
+) -> Option<String> {
+    let no_proxy = env("NO_PROXY").or_else(|| env("no_proxy"));
+    if no_proxy.is_some_and(|list| no_proxy_matches(&list, host)) {
+        return None;
+    }
+    proxy_override
+        .map(str::to_string)
+        .or_else(|| PROXY_ENV_VARS.iter().find_map(|name| env(name)))
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+}
+
+/// HTTP client shared by the API clients, routed through a proxy when one applies to `host`.
+/// A malformed proxy URL is ignored so the app still works on a direct connection.
+pub fn build_http_client(
+    proxy_override: Option<&str>,
+    host: &str,
+    timeouts: HttpTimeouts,
+) -> Result<reqwest::Client, reqwest::Error> {
+    // Proxy selection is done here, so keep reqwest from also reading the environment.
+    let builder = reqwest::Client::builder()
+        .timeout(timeouts.request)
+        .connect_timeout(timeouts.connect)
+        .no_proxy();
+    let Some(url) = resolve_proxy(proxy_override, |name| std::env::var(name).ok(), host) else {
+        return builder.build();
+    };
+    match reqwest::Proxy::all(&url) {
+        Ok(proxy) => builder.proxy(proxy).build(),
+        Err(_) => builder.build(),
+    }
+}
+
+/// An API client's `reqwest::Client`, rebuilt in place when the timeout setting changes.
+pub struct SharedHttpClient {
+    proxy: Option<String>,
+    host: String,
+    client: RwLock<reqwest::Client>,
+}
+
+impl SharedHttpClient {
+    pub fn new(
+        proxy: Option<&str>,
+        host: &str,
+        timeouts: HttpTimeouts,
+    ) -> Result<Self, reqwest::Error> {
+        Ok(Self {
+            proxy: proxy.map(str::to_string),
+            host: host.to_string(),
+            client: RwLock::new(build_http_client(proxy, host, timeouts)?),
+        })
+    }
+
+    /// The current client; cheap to clone, and requests already sent keep their old timeouts.
+    pub fn get(&self) -> reqwest::Client {
+        self.client
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn set_timeouts(&self, timeouts: HttpTimeouts) -> Result<(), reqwest::Error> {
+        let client = build_http_client(self.proxy.as_deref(), &self.host, timeouts)?;
+        *self.client.write().unwrap_or_else(|e| e.into_inner()) = client;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn retry_after_accepts_seconds_and_http_dates() {
+        let now = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(parse_retry_after(" 120 ", now), Some(120));
+        assert_eq!(
+            parse_retry_after("Thu, 01 Jan 2026 00:02:30 GMT", now),
+            Some(150)
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 31 Dec 2025 23:00:00 GMT", now),
+            Some(0)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+
+        assert_eq!(
+            with_retry_after("Rate limited.", Some(120)),
+            "Rate limited; retry in 120s."
+        );
+        assert_eq!(with_retry_after("Rate limited.", None), "Rate limited.");
+    }
+
+    #[test]
+    fn provider_timestamp_reads_the_date_header() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        assert_eq!(provider_timestamp(&headers), None);
+        headers.insert(
+            reqwest::header::DATE,
+            "Thu, 09 Oct 2025 08:55:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(
+            provider_timestamp(&headers).as_deref(),
+            Some("2025-10-09T08:55:00Z")
+        );
+        assert_eq!(parse_http_date("yesterday"), None);
+    }
+
+    #[test]
+    fn http_timeouts_are_clamped_and_keep_connect_within_request() {
+        assert_eq!(HttpTimeouts::from_seconds(40), HttpTimeouts::default());
+        assert_eq!(
+            HttpTimeouts::from_seconds(1),
+            HttpTimeouts {
+                request: Duration::from_secs(5),
+                connect: Duration::from_secs(5),
+            }
+        );
+        assert_eq!(
+            HttpTimeouts::from_seconds(600).request,
+            Duration::from_secs(120)
+        );
+    }
+
+    #[test]
+    fn proxy_resolution_prefers_override_and_respects_no_proxy() {
+        let vars = HashMap::from([
+            ("HTTPS_PROXY", "http://env-proxy:8080"),
+            ("NO_PROXY", "localhost, .chatgpt.com"),
+        ]);
+        let env = |name: &str| vars.get(name).map(|v| v.to_string());
+
+        assert_eq!(
+            resolve_proxy(None, env, "claude.ai").as_deref(),
+            Some("http://env-proxy:8080")
+        );
+        assert_eq!(
+            resolve_proxy(Some("http://override:3128"), env, "claude.ai").as_deref(),
+            Some("http://override:3128")
+        );
+        assert_eq!(resolve_proxy(None, env, "chatgpt.com"), None);
+        assert_eq!(resolve_proxy(None, |_| None, "claude.ai"), None);
+
+        assert!(no_proxy_matches("*", "claude.ai"));
+        assert!(no_proxy_matches("claude.ai:443", "api.claude.ai"));
+        assert!(!no_proxy_matches("laude.ai", "claude.ai"));
+    }
+}
@@ -1,17 +1,35 @@
+mod api_error;
 mod app;
+mod archive;
+#[cfg_attr(
+    not(any(target_os = "macos", feature = "menu-export")),
+    allow(dead_code)
+)]
+mod bitmap_font;
+mod burn_rate;
+mod capabilities;
 mod claude;
 mod codex;
 mod commands;
+mod daily_peaks;
+mod data_dir;
+mod http_client;
+#[cfg(feature = "menu-export")]
+mod menu_export;
+mod metrics;
 mod notifications;
 mod provider_view;
 mod redact;
 mod refresh;
 mod settings;
+mod staleness;
 mod state;
+mod support_bundle;
 mod tray;
 pub mod types;
 mod updater;
 mod usage_alerts;
+mod usage_history;
 mod windows;
 
 pub fn run() {
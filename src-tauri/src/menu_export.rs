@@ -0,0 +1,99 @@
+//! Debug-only rendering of the usage summary to a PNG (built with the `menu-export` feature).
+//!
+//! Drawn with the shared bitmap font and encoded by `archive::encode_png_gray` to avoid
+//! image/font deps.
+
+use crate::archive::encode_png_gray;
+use crate::bitmap_font::{glyph, GLYPH_HEIGHT, GLYPH_WIDTH};
+use std::path::{Path, PathBuf};
+
+const SCALE: usize = 2;
+const MARGIN: usize = 8 * SCALE;
+const ADVANCE: usize = (GLYPH_WIDTH + 1) * SCALE;
+const LINE_HEIGHT: usize = (GLYPH_HEIGHT + 3) * SCALE;
+
+const BACKGROUND: u8 = 0xFF;
+const INK: u8 = 0x20;
+
+/// Rasterize `lines` into an 8-bit grayscale bitmap; returns `(width, height, pixels)`.
+pub(crate) fn rasterize(lines: &[String]) -> (usize, usize, Vec<u8>) {
+    let columns = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let width = MARGIN * 2 + columns.max(1) * ADVANCE;
+    let height = MARGIN * 2 + lines.len().max(1) * LINE_HEIGHT;
+    let mut pixels = vec![BACKGROUND; width * height];
+
+    for (row, line) in lines.iter().enumerate() {
+        let top = MARGIN + row * LINE_HEIGHT;
+        for (col, c) in line.chars().enumerate() {
+            let left = MARGIN + col * ADVANCE;
+            for (gx, bits) in glyph(c).iter().enumerate() {
+                for gy in 0..GLYPH_HEIGHT {
+                    if bits & (1 << gy) == 0 {
+                        continue;
+                    }
+                    for dy in 0..SCALE {
+                        let y = top + gy * SCALE + dy;
+                        let x = left + gx * SCALE;
+                        pixels[y * width + x..y * width + x + SCALE].fill(INK);
+                    }
+                }
+            }
+        }
+    }
+
+    (width, height, pixels)
+}
+
+pub(crate) fn render_png(lines: &[String]) -> Vec<u8> {
+    let (width, height, pixels) = rasterize(lines);
+    encode_png_gray(width, height, &pixels)
+}
+
+/// `<data_dir>/exports/<file_name>`, rejecting names that would escape the exports directory.
+pub(crate) fn export_path(data_dir: &Path, file_name: &str) -> Result<PathBuf, String> {
+    let valid = !file_name.is_empty()
+        && file_name.ends_with(".png")
+        && file_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && !file_name.contains("..");
+    if !valid {
+        return Err("Invalid export file name.".to_string());
+    }
+    Ok(data_dir.join("exports").join(file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_png_writes_signature_and_dimensions() {
+        let lines = vec!["CL 45% · CX 12%".to_string(), "Claude".to_string()];
+        let (width, height, _) = rasterize(&lines);
+        let png = render_png(&lines);
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(
+            u32::from_be_bytes(png[16..20].try_into().unwrap()),
+            width as u32
+        );
+        assert_eq!(
+            u32::from_be_bytes(png[20..24].try_into().unwrap()),
+            height as u32
+        );
+        assert!(png.ends_with(&[0xAE, 0x42, 0x60, 0x82]));
+    }
+
+    #[test]
+    fn export_path_stays_inside_exports_dir() {
+        let dir = Path::new("/data");
+        assert_eq!(
+            export_path(dir, "menu-1.png").unwrap(),
+            Path::new("/data/exports/menu-1.png")
+        );
+        assert!(export_path(dir, "../menu.png").is_err());
+        assert!(export_path(dir, "menu.txt").is_err());
+    }
+}
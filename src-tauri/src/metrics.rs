@@ -0,0 +1,330 @@
+use crate::provider_view::{
+    view_claude, view_codex, ProviderOkView, CLAUDE_PROVIDER_KEY, CODEX_PROVIDER_KEY,
+};
+use crate::types::{UsageSnapshotBundle, UsageStatus};
+use chrono::DateTime;
+use std::fmt::Write as _;
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{oneshot, Mutex};
+
+const UP_METRIC: &str = "claudometer_up";
+const SESSION_PERCENT_METRIC: &str = "claudometer_session_percent";
+const WEEKLY_PERCENT_METRIC: &str = "claudometer_weekly_percent";
+const LAST_UPDATED_METRIC: &str = "claudometer_last_updated_timestamp";
+const RESET_TIMESTAMP_METRIC: &str = "claudometer_reset_timestamp_seconds";
+
+const METRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+/// A scraper that connects but never sends its request is dropped after this long.
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Usage metrics as an OpenMetrics text document (served by the local metrics endpoint).
+pub(crate) fn render_metrics(snapshot: Option<&UsageSnapshotBundle>) -> String {
+    let mut out = String::new();
+    write_up_gauges(&mut out, snapshot);
+    write_usage_gauges(&mut out, snapshot);
+    write_reset_gauges(&mut out, snapshot);
+    out.push_str("# EOF\n");
+    out
+}
+
+fn write_family_header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} gauge");
+}
+
+fn ok_views(snapshot: Option<&UsageSnapshotBundle>) -> Vec<ProviderOkView<'_>> {
+    let Some(snapshot) = snapshot else {
+        return vec![];
+    };
+    snapshot
+        .claude
+        .as_ref()
+        .and_then(view_claude)
+        .into_iter()
+        .chain(snapshot.codex.as_ref().and_then(view_codex))
+        .collect()
+}
+
+/// `(provider_key, status, last_updated_at)` for each tracked provider in `snapshot`.
+fn provider_states(
+    snapshot: Option<&UsageSnapshotBundle>,
+) -> Vec<(&'static str, UsageStatus, &str)> {
+    let Some(snapshot) = snapshot else {
+        return vec![];
+    };
+    let claude = snapshot
+        .claude
+        .as_ref()
+        .map(|s| (CLAUDE_PROVIDER_KEY, s.status(), s.last_updated_at()));
+    let codex = snapshot
+        .codex
+        .as_ref()
+        .map(|s| (CODEX_PROVIDER_KEY, s.status(), s.last_updated_at()));
+    claude.into_iter().chain(codex).collect()
+}
+
+fn epoch_seconds(iso: &str) -> Option<i64> {
+    Some(DateTime::parse_from_rfc3339(iso).ok()?.timestamp())
+}
+
+/// `claudometer_up{provider}`: 1 for a successful fetch, 0 for any error state. Untracked
+/// providers are left out.
+fn write_up_gauges(out: &mut String, snapshot: Option<&UsageSnapshotBundle>) {
+    write_family_header(
+        out,
+        UP_METRIC,
+        "Whether the last fetch for the provider succeeded.",
+    );
+    for (provider, status, _) in provider_states(snapshot) {
+        let up = u8::from(status == UsageStatus::Ok);
+        let _ = writeln!(out, "{UP_METRIC}{{provider=\"{provider}\"}} {up}");
+    }
+}
+
+/// Session/weekly utilization and fetch time, for Ok providers only so a failed fetch never
+/// reads as 0% usage.
+fn write_usage_gauges(out: &mut String, snapshot: Option<&UsageSnapshotBundle>) {
+    let views = ok_views(snapshot);
+    write_family_header(
+        out,
+        SESSION_PERCENT_METRIC,
+        "Session (5-hour) window utilization in percent.",
+    );
+    for view in &views {
+        let _ = writeln!(
+            out,
+            "{SESSION_PERCENT_METRIC}{{provider=\"{}\"}} {}",
+            view.provider_key, view.session_percent
+        );
+    }
+    write_family_header(
+        out,
+        WEEKLY_PERCENT_METRIC,
+        "Weekly window utilization in percent.",
+    );
+    for view in &views {
+        if let Some(weekly_percent) = view.weekly_percent {
+            let _ = writeln!(
+                out,
+                "{WEEKLY_PERCENT_METRIC}{{provider=\"{}\"}} {weekly_percent}",
+                view.provider_key
+            );
+        }
+    }
+    write_family_header(
+        out,
+        LAST_UPDATED_METRIC,
+        "Unix time of the last successful fetch.",
+    );
+    for (provider, status, last_updated_at) in provider_states(snapshot) {
+        if status != UsageStatus::Ok {
+            continue;
+        }
+        if let Some(seconds) = epoch_seconds(last_updated_at) {
+            let _ = writeln!(
+                out,
+                "{LAST_UPDATED_METRIC}{{provider=\"{provider}\"}} {seconds}"
+            );
+        }
+    }
+}
+
+/// `claudometer_reset_timestamp_seconds{provider,window}` per known reset time; unknown or
+/// unparsable resets are omitted rather than reported as 0.
+fn write_reset_gauges(out: &mut String, snapshot: Option<&UsageSnapshotBundle>) {
+    write_family_header(
+        out,
+        RESET_TIMESTAMP_METRIC,
+        "Unix time when the usage window resets.",
+    );
+    let _ = writeln!(out, "# UNIT {RESET_TIMESTAMP_METRIC} seconds");
+    for view in ok_views(snapshot) {
+        for (window, resets_at) in [
+            ("session", view.session_resets_at),
+            ("weekly", view.weekly_resets_at),
+        ] {
+            if let Some(seconds) = resets_at.and_then(epoch_seconds) {
+                let _ = writeln!(
+                    out,
+                    "{RESET_TIMESTAMP_METRIC}{{provider=\"{}\",window=\"{window}\"}} {seconds}",
+                    view.provider_key
+                );
+            }
+        }
+    }
+}
+
+/// Whether an HTTP request line asks for the metrics document (`GET /metrics`, query ignored).
+fn is_metrics_request(request_line: &str) -> bool {
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return false;
+    };
+    method == "GET" && target.split('?').next() == Some("/metrics")
+}
+
+fn http_response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+async fn serve_connection(
+    mut stream: TcpStream,
+    latest_snapshot: Arc<Mutex<Option<UsageSnapshotBundle>>>,
+) {
+    // The request line always fits in the first read; headers and body are ignored.
+    let mut buf = [0u8; 2048];
+    let Ok(Ok(read)) = tokio::time::timeout(REQUEST_READ_TIMEOUT, stream.read(&mut buf)).await
+    else {
+        return;
+    };
+    let request = String::from_utf8_lossy(&buf[..read]);
+    let response = if is_metrics_request(request.lines().next().unwrap_or_default()) {
+        let snapshot = latest_snapshot.lock().await.clone();
+        http_response(
+            "200 OK",
+            METRICS_CONTENT_TYPE,
+            &render_metrics(snapshot.as_ref()),
+        )
+    } else {
+        http_response("404 Not Found", "text/plain; charset=utf-8", "Not found\n")
+    };
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Running `/metrics` endpoint; `shutdown` stops accepting connections.
+pub(crate) struct MetricsServer {
+    shutdown: std::sync::Mutex<Option<oneshot::Sender<()>>>,
+}
+
+impl MetricsServer {
+    pub(crate) fn shutdown(&self) {
+        let sender = self
+            .shutdown
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Some(sender) = sender {
+            let _ = sender.send(());
+        }
+    }
+}
+
+/// Serve `latest_snapshot` as metrics on `127.0.0.1:port`. Binding happens before returning so a
+/// port conflict is reported to the caller instead of failing silently in the background.
+pub(crate) fn spawn_metrics_server(
+    latest_snapshot: Arc<Mutex<Option<UsageSnapshotBundle>>>,
+    port: u16,
+) -> std::io::Result<MetricsServer> {
+    let listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    listener.set_nonblocking(true)?;
+    let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
+
+    tauri::async_runtime::spawn(async move {
+        let Ok(listener) = TcpListener::from_std(listener) else {
+            return;
+        };
+        loop {
+            tokio::select! {
+                _ = &mut shutdown_rx => break,
+                accepted = listener.accept() => {
+                    if let Ok((stream, _)) = accepted {
+                        tauri::async_runtime::spawn(serve_connection(
+                            stream,
+                            latest_snapshot.clone(),
+                        ));
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(MetricsServer {
+        shutdown: std::sync::Mutex::new(Some(shutdown_tx)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ClaudeUsageSnapshot, CodexUsageSnapshot};
+
+    #[test]
+    fn reset_gauges_cover_known_resets_only() {
+        let bundle = UsageSnapshotBundle {
+            claude: Some(ClaudeUsageSnapshot::Ok {
+                organization_id: "org-123".to_string(),
+                session_percent: 25.0,
+                session_resets_at: Some("2026-01-07T05:00:00Z".to_string()),
+                weekly_percent: Some(30.0),
+                weekly_resets_at: None,
+                models: vec![],
+                session_models: vec![],
+                last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+                provider_timestamp: None,
+            }),
+            codex: Some(CodexUsageSnapshot::Error {
+                last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+                error_message: None,
+            }),
+            offline: false,
+            claude_orgs: vec![],
+        };
+        let text = render_metrics(Some(&bundle));
+        assert!(text.contains("# TYPE claudometer_reset_timestamp_seconds gauge\n"));
+        assert!(text.contains(
+            "claudometer_reset_timestamp_seconds{provider=\"claude\",window=\"session\"} 1767762000\n"
+        ));
+        assert!(!text.contains("window=\"weekly\""));
+        assert!(!text.contains("claudometer_reset_timestamp_seconds{provider=\"codex\""));
+        assert!(text.ends_with("# EOF\n"));
+    }
+
+    #[test]
+    fn usage_gauges_cover_ok_providers_and_up_marks_errors() {
+        let bundle = UsageSnapshotBundle {
+            claude: Some(ClaudeUsageSnapshot::Ok {
+                organization_id: "org-123".to_string(),
+                session_percent: 25.5,
+                session_resets_at: None,
+                weekly_percent: None,
+                weekly_resets_at: None,
+                models: vec![],
+                session_models: vec![],
+                last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+                provider_timestamp: None,
+            }),
+            codex: Some(CodexUsageSnapshot::Unauthorized {
+                last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+                error_message: None,
+            }),
+            offline: false,
+            claude_orgs: vec![],
+        };
+        let text = render_metrics(Some(&bundle));
+        assert!(text.contains("claudometer_up{provider=\"claude\"} 1\n"));
+        assert!(text.contains("claudometer_up{provider=\"codex\"} 0\n"));
+        assert!(text.contains("claudometer_session_percent{provider=\"claude\"} 25.5\n"));
+        assert!(
+            text.contains("claudometer_last_updated_timestamp{provider=\"claude\"} 1767740371\n")
+        );
+        assert!(!text.contains("claudometer_weekly_percent{"));
+        assert!(!text.contains("_percent{provider=\"codex\"}"));
+    }
+
+    #[test]
+    fn only_get_metrics_is_served() {
+        assert!(is_metrics_request("GET /metrics HTTP/1.1"));
+        assert!(is_metrics_request("GET /metrics?name[]=up HTTP/1.1"));
+        assert!(!is_metrics_request("POST /metrics HTTP/1.1"));
+        assert!(!is_metrics_request("GET / HTTP/1.1"));
+        assert!(!is_metrics_request(""));
+    }
+}
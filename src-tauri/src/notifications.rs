@@ -1,19 +1,147 @@
-use crate::provider_view::{view_claude, view_codex, ProviderOkView};
+use crate::burn_rate::{projected_ms_to_limit, recent_series, UsageSample};
+use crate::provider_view::{
+    namespace_legacy_dedupe_key, view_claude, view_codex, ProviderOkView, CODEX_PROVIDER_KEY,
+};
+use crate::refresh::rate_limit_wait_ms;
 use crate::settings::{
-    KEY_NOTIFY_ON_USAGE_RESET, KEY_SESSION_NEAR_LIMIT_NOTIFIED, KEY_SESSION_RESET_NOTIFIED,
+    SettingsStore, KEY_ALERT_TIERS, KEY_BURN_RATE_ALERT_ENABLED, KEY_BURN_RATE_HORIZON_MINUTES,
+    KEY_CLAUDE_NEAR_LIMIT_PERCENT, KEY_CODEX_NEAR_LIMIT_PERCENT, KEY_EXHAUSTION_ALERT_ENABLED,
+    KEY_NOTIFY_ON_RATE_LIMIT, KEY_NOTIFY_ON_RECOVERY, KEY_NOTIFY_ON_USAGE_RESET,
+    KEY_NOTIFY_SHOW_DELTA, KEY_QUIET_HOURS_END, KEY_QUIET_HOURS_START, KEY_RESPECT_DO_NOT_DISTURB,
+    KEY_SESSION_ALERT_TIER_NOTIFIED, KEY_SESSION_BURN_RATE_NOTIFIED,
+    KEY_SESSION_EXHAUSTION_NOTIFIED, KEY_SESSION_NEAR_LIMIT_NOTIFIED, KEY_SESSION_RESET_NOTIFIED,
+    KEY_WEEKLY_ALERT_TIER_NOTIFIED, KEY_WEEKLY_BURN_RATE_NOTIFIED, KEY_WEEKLY_EXHAUSTION_NOTIFIED,
     KEY_WEEKLY_NEAR_LIMIT_NOTIFIED, KEY_WEEKLY_RESET_NOTIFIED,
 };
+use crate::staleness::now_unix_ms;
 use crate::state::AppState;
-use crate::types::{ClaudeUsageSnapshot, CodexUsageSnapshot, UsageSnapshotBundle};
+use crate::types::{ClaudeUsageSnapshot, CodexUsageSnapshot, UsageSnapshotBundle, UsageStatus};
 use crate::usage_alerts::{
-    decide_near_limit_alerts, decide_usage_resets, DecideNearLimitAlertsParams,
-    DecideUsageResetsParams,
+    burn_rate_message, decide_near_limit_alerts, decide_tiered_alerts, decide_usage_resets,
+    exhaustion_message, near_limit_message, normalize_period_id, should_notify_burn_rate,
+    should_notify_exhaustion, tier_alert_message, DecideNearLimitAlertsParams,
+    DecideUsageResetsParams, NotifiedTier, DEFAULT_NEAR_LIMIT_THRESHOLD_PERCENT,
 };
+use chrono::{Local, NaiveTime};
 use serde_json::{Map as JsonMap, Value as JsonValue};
-use tauri::{AppHandle, Runtime};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager as _, Runtime};
 use tauri_plugin_notification::NotificationExt as _;
 
+/// OS Do Not Disturb / Focus state as far as we can tell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FocusState {
+    Active,
+    Inactive,
+    Unknown,
+}
+
+/// Parse macOS `~/Library/DoNotDisturb/DB/Assertions.json`: any assertion record means a Focus
+/// mode is on.
+fn parse_focus_assertions(json: &str) -> FocusState {
+    let Ok(value) = serde_json::from_str::<JsonValue>(json) else {
+        return FocusState::Unknown;
+    };
+    let Some(data) = value.get("data").and_then(|v| v.as_array()) else {
+        return FocusState::Unknown;
+    };
+    let active = data.iter().any(|entry| {
+        entry
+            .get("storeAssertionRecords")
+            .and_then(|v| v.as_array())
+            .is_some_and(|records| !records.is_empty())
+    });
+    if active {
+        FocusState::Active
+    } else {
+        FocusState::Inactive
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn current_focus_state() -> FocusState {
+    let Some(home) = std::env::var_os("HOME") else {
+        return FocusState::Unknown;
+    };
+    let path = std::path::Path::new(&home).join("Library/DoNotDisturb/DB/Assertions.json");
+    match std::fs::read_to_string(path) {
+        Ok(json) => parse_focus_assertions(&json),
+        Err(_) => FocusState::Unknown,
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn current_focus_state() -> FocusState {
+    FocusState::Unknown
+}
+
+/// Only a known-active Focus mode mutes delivery; callers still record their period baselines so
+/// muted alerts aren't replayed once Focus ends.
+fn should_suppress_delivery(focus: FocusState) -> bool {
+    focus == FocusState::Active
+}
+
+/// Time left on a snooze started from the tray; `None` once it has run out (or was never set).
+pub fn snooze_remaining(until: Option<Instant>, now: Instant) -> Option<Duration> {
+    until
+        .and_then(|until| until.checked_duration_since(now))
+        .filter(|d| !d.is_zero())
+}
+
+/// The tray toggle: clears an active snooze, otherwise snoozes for `duration` from `now`.
+pub fn toggle_snooze(until: Option<Instant>, now: Instant, duration: Duration) -> Option<Instant> {
+    match snooze_remaining(until, now) {
+        Some(_) => None,
+        None => Some(now + duration),
+    }
+}
+
+/// `HH:MM` (24h) from the quiet-hours settings; anything else disables quiet hours.
+pub(crate) fn parse_quiet_hours_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+/// Whether `now` falls in `[start, end)`. Windows where `end` is before `start` wrap past
+/// midnight (e.g. 22:00–07:00); `start == end` is an empty window.
+fn is_within_quiet_hours(now: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
+    if start <= end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+fn in_quiet_hours<R: Runtime>(settings: &SettingsStore<R>) -> bool {
+    let time = |key| {
+        settings
+            .get_string(key)
+            .as_deref()
+            .and_then(parse_quiet_hours_time)
+    };
+    match (time(KEY_QUIET_HOURS_START), time(KEY_QUIET_HOURS_END)) {
+        (Some(start), Some(end)) => is_within_quiet_hours(Local::now().time(), start, end),
+        _ => false,
+    }
+}
+
+fn delivery_suppressed<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let Some(state) = app.try_state::<AppState<R>>() else {
+        return false;
+    };
+    // Like Focus below, snooze and quiet hours only mute delivery: decisions and period baselines are still
+    // recorded, so nothing is replayed once the snooze ends.
+    if state.notification_snooze_remaining().is_some() || in_quiet_hours(&state.settings) {
+        return true;
+    }
+    // Skip reading the Focus state entirely unless the user opted in.
+    state.settings.get_bool(KEY_RESPECT_DO_NOT_DISTURB, false)
+        && should_suppress_delivery(current_focus_state())
+}
+
 async fn notify_near_limit<R: Runtime>(app: &AppHandle<R>, body: &str) {
+    if delivery_suppressed(app) {
+        return;
+    }
     let notification = app.notification().builder().title("Claudometer").body(body);
 
     #[cfg(target_os = "macos")]
@@ -26,6 +154,9 @@ async fn notify_near_limit<R: Runtime>(app: &AppHandle<R>, body: &str) {
 }
 
 async fn notify_usage_reset<R: Runtime>(app: &AppHandle<R>, body: &str) {
+    if delivery_suppressed(app) {
+        return;
+    }
     let notification = app.notification().builder().title("Claudometer").body(body);
 
     #[cfg(target_os = "macos")]
@@ -37,6 +168,41 @@ async fn notify_usage_reset<R: Runtime>(app: &AppHandle<R>, body: &str) {
     let _ = notification.show();
 }
 
+/// Plain notice (recovery, rate limiting, organization changes) without a sound.
+pub async fn notify_plain<R: Runtime>(app: &AppHandle<R>, body: &str) {
+    if delivery_suppressed(app) {
+        return;
+    }
+    let _ = app
+        .notification()
+        .builder()
+        .title("Claudometer")
+        .body(body)
+        .show();
+}
+
+/// Rewrite dedupe-map keys stored before they were namespaced by provider (`<org>` / `codex`)
+/// into `claude:<org>` / `codex:codex`. Idempotent; run once at startup.
+pub fn migrate_period_id_map_keys<R: Runtime>(settings: &SettingsStore<R>) {
+    for key in [
+        KEY_SESSION_NEAR_LIMIT_NOTIFIED,
+        KEY_WEEKLY_NEAR_LIMIT_NOTIFIED,
+        KEY_SESSION_RESET_NOTIFIED,
+        KEY_WEEKLY_RESET_NOTIFIED,
+    ] {
+        let Some(JsonValue::Object(map)) = settings.get_json(key) else {
+            continue;
+        };
+        let migrated: JsonMap<String, JsonValue> = map
+            .iter()
+            .map(|(k, v)| (namespace_legacy_dedupe_key(k), v.clone()))
+            .collect();
+        if migrated != map {
+            settings.set(key, JsonValue::Object(migrated));
+        }
+    }
+}
+
 fn read_period_id_map<R: Runtime>(state: &AppState<R>, key: &str) -> JsonMap<String, JsonValue> {
     match state.settings.get_json(key) {
         Some(JsonValue::Object(map)) => map,
@@ -55,18 +221,128 @@ fn map_set_org_period_id(map: &mut JsonMap<String, JsonValue>, org_id: &str, per
     map.insert(org_id.to_string(), JsonValue::String(period_id.to_string()));
 }
 
-async fn maybe_notify_ok_view<R: Runtime>(
+async fn maybe_notify_burn_rate<R: Runtime>(
     app: &AppHandle<R>,
     state: &AppState<R>,
-    cur: ProviderOkView<'_>,
+    cur: &ProviderOkView<'_>,
+    scope_key: &str,
+) {
+    let enabled = state.settings.get_bool(KEY_BURN_RATE_ALERT_ENABLED, false);
+    let exhaustion_enabled = state.settings.get_bool(KEY_EXHAUSTION_ALERT_ENABLED, false);
+    if !enabled && !exhaustion_enabled {
+        return;
+    }
+    let horizon_ms = state.settings.get_u64(KEY_BURN_RATE_HORIZON_MINUTES, 120) as i64 * 60_000;
+    // The current reading is already in the history (recorded before notifications run).
+    let (session, weekly) = recent_series(&state.usage_history(), cur.provider_key, now_unix_ms());
+
+    for (window, samples, resets_at, map_key, exhaustion_map_key) in [
+        (
+            "session",
+            session,
+            cur.session_resets_at,
+            KEY_SESSION_BURN_RATE_NOTIFIED,
+            KEY_SESSION_EXHAUSTION_NOTIFIED,
+        ),
+        (
+            "weekly",
+            weekly,
+            cur.weekly_resets_at,
+            KEY_WEEKLY_BURN_RATE_NOTIFIED,
+            KEY_WEEKLY_EXHAUSTION_NOTIFIED,
+        ),
+    ] {
+        let period_id = normalize_period_id(resets_at);
+        let series = WindowSeries {
+            window,
+            period_id: &period_id,
+            samples: &samples,
+        };
+        if exhaustion_enabled {
+            maybe_notify_exhaustion(app, state, cur, scope_key, &series, exhaustion_map_key).await;
+        }
+        if !enabled {
+            continue;
+        }
+
+        let mut map = read_period_id_map(state, map_key);
+        let last_notified = map_get_org_period_id(&map, scope_key);
+        if !should_notify_burn_rate(&period_id, &samples, horizon_ms, last_notified.as_deref()) {
+            continue;
+        }
+        let Some(projected_ms) = projected_ms_to_limit(&samples) else {
+            continue;
+        };
+
+        notify_near_limit(
+            app,
+            &burn_rate_message(cur.provider_label, window, projected_ms),
+        )
+        .await;
+        map_set_org_period_id(&mut map, scope_key, &period_id);
+        state.settings.set(map_key, JsonValue::Object(map));
+    }
+}
+
+/// One provider window's recent readings in its current period.
+struct WindowSeries<'a> {
+    window: &'a str,
+    period_id: &'a str,
+    samples: &'a [UsageSample],
+}
+
+/// "On track to run out before the reset" for one window, once per period.
+async fn maybe_notify_exhaustion<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &AppState<R>,
+    cur: &ProviderOkView<'_>,
+    scope_key: &str,
+    series: &WindowSeries<'_>,
+    map_key: &str,
+) {
+    let mut map = read_period_id_map(state, map_key);
+    let last_notified = map_get_org_period_id(&map, scope_key);
+    if !should_notify_exhaustion(series.period_id, series.samples, last_notified.as_deref()) {
+        return;
+    }
+    let Some(projected_ms) = projected_ms_to_limit(series.samples) else {
+        return;
+    };
+    notify_near_limit(
+        app,
+        &exhaustion_message(cur.provider_label, series.window, projected_ms),
+    )
+    .await;
+    map_set_org_period_id(&mut map, scope_key, series.period_id);
+    state.settings.set(map_key, JsonValue::Object(map));
+}
+
+/// Per-provider near-limit threshold (`claudeNearLimitPercent` / `codexNearLimitPercent`), used
+/// for both the session and weekly windows.
+fn near_limit_threshold<R: Runtime>(settings: &SettingsStore<R>, provider_key: &str) -> f64 {
+    let key = match provider_key {
+        CODEX_PROVIDER_KEY => KEY_CODEX_NEAR_LIMIT_PERCENT,
+        _ => KEY_CLAUDE_NEAR_LIMIT_PERCENT,
+    };
+    settings
+        .get_u64(key, DEFAULT_NEAR_LIMIT_THRESHOLD_PERCENT as u64)
+        .clamp(1, 100) as f64
+}
+
+async fn maybe_notify_near_limit<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &AppState<R>,
+    cur: &ProviderOkView<'_>,
+    scope_key: &str,
     prev_session: Option<f64>,
     prev_weekly: Option<f64>,
 ) {
     let session_map = read_period_id_map(state, KEY_SESSION_NEAR_LIMIT_NOTIFIED);
     let weekly_map = read_period_id_map(state, KEY_WEEKLY_NEAR_LIMIT_NOTIFIED);
-    let last_session_notified = map_get_org_period_id(&session_map, cur.scope_id);
-    let last_weekly_notified = map_get_org_period_id(&weekly_map, cur.scope_id);
+    let last_session_notified = map_get_org_period_id(&session_map, scope_key);
+    let last_weekly_notified = map_get_org_period_id(&weekly_map, scope_key);
 
+    let threshold = near_limit_threshold(&state.settings, cur.provider_key);
     let decision = decide_near_limit_alerts(DecideNearLimitAlertsParams {
         current_session_percent: cur.session_percent,
         current_weekly_percent: cur.weekly_percent,
@@ -76,45 +352,163 @@ async fn maybe_notify_ok_view<R: Runtime>(
         previous_weekly_percent: prev_weekly,
         last_notified_session_period_id: last_session_notified.as_deref(),
         last_notified_weekly_period_id: last_weekly_notified.as_deref(),
+        session_threshold: threshold,
+        weekly_threshold: threshold,
     });
 
+    let show_delta = state.settings.get_bool(KEY_NOTIFY_SHOW_DELTA, false);
+
     if let Some(session_period_id) = decision.session_period_id.as_deref() {
         notify_near_limit(
             app,
-            &format!(
-                "{} session usage is near the limit (>= 90%).",
-                cur.provider_label
+            &near_limit_message(
+                cur.provider_label,
+                "session",
+                threshold,
+                cur.session_percent,
+                prev_session,
+                show_delta,
             ),
         )
         .await;
         let mut map = session_map;
-        map_set_org_period_id(&mut map, cur.scope_id, session_period_id);
+        map_set_org_period_id(&mut map, scope_key, session_period_id);
         state
             .settings
             .set(KEY_SESSION_NEAR_LIMIT_NOTIFIED, JsonValue::Object(map));
     }
 
-    if let Some(weekly_period_id) = decision.weekly_period_id.as_deref() {
+    if let (Some(weekly_period_id), Some(weekly_percent)) =
+        (decision.weekly_period_id.as_deref(), cur.weekly_percent)
+    {
         notify_near_limit(
             app,
-            &format!(
-                "{} weekly usage is near the limit (>= 90%).",
-                cur.provider_label
+            &near_limit_message(
+                cur.provider_label,
+                "weekly",
+                threshold,
+                weekly_percent,
+                prev_weekly,
+                show_delta,
             ),
         )
         .await;
         let mut map = weekly_map;
-        map_set_org_period_id(&mut map, cur.scope_id, weekly_period_id);
+        map_set_org_period_id(&mut map, scope_key, weekly_period_id);
         state
             .settings
             .set(KEY_WEEKLY_NEAR_LIMIT_NOTIFIED, JsonValue::Object(map));
     }
+}
+
+/// Percents accepted as `alertTiers` thresholds.
+const ALERT_TIER_PERCENT_RANGE: std::ops::RangeInclusive<f64> = 1.0..=100.0;
+
+/// Whether `value` is an `alertTiers` list whose every entry `alert_tiers` would keep.
+pub(crate) fn alert_tiers_value_is_valid(value: &JsonValue) -> bool {
+    serde_json::from_value::<Vec<f64>>(value.clone())
+        .is_ok_and(|tiers| tiers.iter().all(|t| ALERT_TIER_PERCENT_RANGE.contains(t)))
+}
+
+/// Ascending thresholds from `alertTiers` (e.g. `[75, 90, 95]`); empty means the single
+/// near-limit alert is used instead.
+fn alert_tiers<R: Runtime>(settings: &SettingsStore<R>) -> Vec<f64> {
+    let mut tiers: Vec<f64> = settings
+        .get_json(KEY_ALERT_TIERS)
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+    tiers.retain(|t| ALERT_TIER_PERCENT_RANGE.contains(t));
+    tiers.sort_by(f64::total_cmp);
+    tiers.dedup();
+    tiers
+}
+
+fn map_get_notified_tier(
+    map: &JsonMap<String, JsonValue>,
+    scope_key: &str,
+) -> Option<(String, usize)> {
+    let entry = map.get(scope_key)?;
+    let period_id = entry.get("periodId")?.as_str()?.to_string();
+    let tier = entry.get("tier")?.as_u64()? as usize;
+    Some((period_id, tier))
+}
+
+async fn maybe_notify_alert_tiers<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &AppState<R>,
+    cur: &ProviderOkView<'_>,
+    scope_key: &str,
+    tiers: &[f64],
+) {
+    for (window, percent, resets_at, map_key) in [
+        (
+            "session",
+            Some(cur.session_percent),
+            cur.session_resets_at,
+            KEY_SESSION_ALERT_TIER_NOTIFIED,
+        ),
+        (
+            "weekly",
+            cur.weekly_percent,
+            cur.weekly_resets_at,
+            KEY_WEEKLY_ALERT_TIER_NOTIFIED,
+        ),
+    ] {
+        // Unknown weekly usage never alerts.
+        let Some(percent) = percent else {
+            continue;
+        };
+        let mut map = read_period_id_map(state, map_key);
+        let last = map_get_notified_tier(&map, scope_key);
+        let last = last.as_ref().map(|(period_id, tier)| NotifiedTier {
+            period_id,
+            tier: *tier,
+        });
+        let Some(decision) = decide_tiered_alerts(tiers, percent, resets_at, last) else {
+            continue;
+        };
+
+        notify_near_limit(
+            app,
+            &tier_alert_message(
+                cur.provider_label,
+                window,
+                decision.tier,
+                tiers.len(),
+                percent,
+            ),
+        )
+        .await;
+        map.insert(
+            scope_key.to_string(),
+            serde_json::json!({"periodId": decision.period_id, "tier": decision.tier}),
+        );
+        state.settings.set(map_key, JsonValue::Object(map));
+    }
+}
+
+async fn maybe_notify_ok_view<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &AppState<R>,
+    cur: ProviderOkView<'_>,
+    prev_session: Option<f64>,
+    prev_weekly: Option<f64>,
+) {
+    let scope_key = cur.dedupe_key();
+    let tiers = alert_tiers(&state.settings);
+    if tiers.is_empty() {
+        maybe_notify_near_limit(app, state, &cur, &scope_key, prev_session, prev_weekly).await;
+    } else {
+        maybe_notify_alert_tiers(app, state, &cur, &scope_key, &tiers).await;
+    }
+
+    maybe_notify_burn_rate(app, state, &cur, &scope_key).await;
 
     let notify_on_usage_reset = state.settings.get_bool(KEY_NOTIFY_ON_USAGE_RESET, false);
 
     let (last_seen_session, last_seen_weekly) = {
         let guard = state.reset_baseline_by_org.lock().await;
-        let baseline = guard.get(cur.scope_id);
+        let baseline = guard.get(&scope_key);
         (
             baseline.and_then(|b| b.session_period_id.clone()),
             baseline.and_then(|b| b.weekly_period_id.clone()),
@@ -123,8 +517,8 @@ async fn maybe_notify_ok_view<R: Runtime>(
 
     let session_reset_map = read_period_id_map(state, KEY_SESSION_RESET_NOTIFIED);
     let weekly_reset_map = read_period_id_map(state, KEY_WEEKLY_RESET_NOTIFIED);
-    let last_session_reset_notified = map_get_org_period_id(&session_reset_map, cur.scope_id);
-    let last_weekly_reset_notified = map_get_org_period_id(&weekly_reset_map, cur.scope_id);
+    let last_session_reset_notified = map_get_org_period_id(&session_reset_map, &scope_key);
+    let last_weekly_reset_notified = map_get_org_period_id(&weekly_reset_map, &scope_key);
 
     let reset_decision = decide_usage_resets(DecideUsageResetsParams {
         current_session_resets_at: cur.session_resets_at,
@@ -143,7 +537,7 @@ async fn maybe_notify_ok_view<R: Runtime>(
             )
             .await;
             let mut map = session_reset_map;
-            map_set_org_period_id(&mut map, cur.scope_id, session_period_id);
+            map_set_org_period_id(&mut map, &scope_key, session_period_id);
             state
                 .settings
                 .set(KEY_SESSION_RESET_NOTIFIED, JsonValue::Object(map));
@@ -156,7 +550,7 @@ async fn maybe_notify_ok_view<R: Runtime>(
             )
             .await;
             let mut map = weekly_reset_map;
-            map_set_org_period_id(&mut map, cur.scope_id, weekly_period_id);
+            map_set_org_period_id(&mut map, &scope_key, weekly_period_id);
             state
                 .settings
                 .set(KEY_WEEKLY_RESET_NOTIFIED, JsonValue::Object(map));
@@ -165,7 +559,7 @@ async fn maybe_notify_ok_view<R: Runtime>(
 
     {
         let mut guard = state.reset_baseline_by_org.lock().await;
-        let entry = guard.entry(cur.scope_id.to_string()).or_default();
+        let entry = guard.entry(scope_key.clone()).or_default();
         if let Some(s) = cur
             .session_resets_at
             .map(|s| s.trim())
@@ -183,6 +577,65 @@ async fn maybe_notify_ok_view<R: Runtime>(
     }
 }
 
+/// A provider came back: its previous snapshot was a failure (unauthorized, missing key, rate
+/// limited, error) and the current one is Ok. No previous snapshot (first fetch) never counts.
+fn recovered(previous: Option<UsageStatus>, current: Option<UsageStatus>) -> bool {
+    current == Some(UsageStatus::Ok) && previous.is_some_and(|status| status != UsageStatus::Ok)
+}
+
+/// For a provider now in `status`: whether to send the rate-limit notice, and whether it counts
+/// as sent afterwards. Fires once per rate-limited span and re-arms only once the provider is Ok.
+fn rate_limit_notice(status: UsageStatus, already_notified: bool) -> (bool, bool) {
+    match status {
+        UsageStatus::RateLimited => (!already_notified, true),
+        UsageStatus::Ok => (false, false),
+        _ => (false, already_notified),
+    }
+}
+
+fn rate_limit_message(provider_label: &str, backoff_ms: u64, next_retry: &str) -> String {
+    format!(
+        "{provider_label} is rate limiting usage requests. Polling has backed off to every {} minutes; next try around {next_retry}.",
+        backoff_ms.div_ceil(60_000)
+    )
+}
+
+async fn maybe_notify_rate_limited<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &AppState<R>,
+    provider_label: &'static str,
+    status: UsageStatus,
+    retry_after_seconds: Option<u64>,
+) {
+    let notify = {
+        let mut notified = state.rate_limit_notified.lock().await;
+        let (notify, now_notified) = rate_limit_notice(status, notified.contains(provider_label));
+        if now_notified {
+            notified.insert(provider_label);
+        } else {
+            notified.remove(provider_label);
+        }
+        notify
+    };
+    if !notify || !state.settings.get_bool(KEY_NOTIFY_ON_RATE_LIMIT, false) {
+        return;
+    }
+    let backoff_ms = rate_limit_wait_ms(
+        retry_after_seconds,
+        state.poll_timing().rate_limit_backoff_ms,
+    );
+    let next_retry = Local::now() + chrono::Duration::milliseconds(backoff_ms as i64);
+    notify_plain(
+        app,
+        &rate_limit_message(
+            provider_label,
+            backoff_ms,
+            &next_retry.format("%H:%M").to_string(),
+        ),
+    )
+    .await;
+}
+
 pub async fn maybe_notify_usage_bundle<R: Runtime>(
     app: &AppHandle<R>,
     state: &AppState<R>,
@@ -191,6 +644,47 @@ pub async fn maybe_notify_usage_bundle<R: Runtime>(
     notify_claude: bool,
     notify_codex: bool,
 ) {
+    if let (true, Some(claude)) = (notify_claude, current.claude.as_ref()) {
+        maybe_notify_rate_limited(
+            app,
+            state,
+            "Claude",
+            claude.status(),
+            claude.retry_after_seconds(),
+        )
+        .await;
+    }
+    if let (true, Some(codex)) = (notify_codex, current.codex.as_ref()) {
+        maybe_notify_rate_limited(
+            app,
+            state,
+            "Codex",
+            codex.status(),
+            codex.retry_after_seconds(),
+        )
+        .await;
+    }
+
+    if state.settings.get_bool(KEY_NOTIFY_ON_RECOVERY, false) {
+        let claude = (
+            notify_claude,
+            "Claude",
+            previous.and_then(|p| p.claude.as_ref()).map(|s| s.status()),
+            current.claude.as_ref().map(|s| s.status()),
+        );
+        let codex = (
+            notify_codex,
+            "Codex",
+            previous.and_then(|p| p.codex.as_ref()).map(|s| s.status()),
+            current.codex.as_ref().map(|s| s.status()),
+        );
+        for (enabled, label, prev, cur) in [claude, codex] {
+            if enabled && recovered(prev, cur) {
+                notify_plain(app, &format!("{label} usage tracking restored.")).await;
+            }
+        }
+    }
+
     if notify_claude {
         if let Some(cur) = current.claude.as_ref().and_then(view_claude) {
             let (prev_session, prev_weekly) = match previous.and_then(|p| p.claude.as_ref()) {
@@ -199,9 +693,7 @@ pub async fn maybe_notify_usage_bundle<R: Runtime>(
                     session_percent,
                     weekly_percent,
                     ..
-                }) if organization_id == cur.scope_id => {
-                    (Some(*session_percent), Some(*weekly_percent))
-                }
+                }) if organization_id == cur.scope_id => (Some(*session_percent), *weekly_percent),
                 _ => (None, None),
             };
             maybe_notify_ok_view(app, state, cur, prev_session, prev_weekly).await;
@@ -215,10 +707,134 @@ pub async fn maybe_notify_usage_bundle<R: Runtime>(
                     session_percent,
                     weekly_percent,
                     ..
-                }) => (Some(*session_percent), Some(*weekly_percent)),
+                }) => (Some(*session_percent), *weekly_percent),
                 _ => (None, None),
             };
             maybe_notify_ok_view(app, state, cur, prev_session, prev_weekly).await;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn focus_assertions_detect_active_focus() {
+        let active = r#"{"data":[{"storeAssertionRecords":[{"assertionDetails":{}}]}]}"#;
+        let inactive = r#"{"data":[{"storeAssertionRecords":[]}]}"#;
+        assert_eq!(parse_focus_assertions(active), FocusState::Active);
+        assert_eq!(parse_focus_assertions(inactive), FocusState::Inactive);
+        assert_eq!(parse_focus_assertions("not json"), FocusState::Unknown);
+    }
+
+    #[test]
+    fn do_not_disturb_suppresses_delivery_but_not_the_baseline() {
+        assert!(should_suppress_delivery(FocusState::Active));
+        assert!(!should_suppress_delivery(FocusState::Inactive));
+        assert!(!should_suppress_delivery(FocusState::Unknown));
+
+        // The alert decision (and therefore the recorded period id) is independent of delivery,
+        // so a muted alert is not replayed later in the same period.
+        let decision = decide_near_limit_alerts(DecideNearLimitAlertsParams {
+            current_session_percent: 95.0,
+            current_weekly_percent: Some(10.0),
+            current_session_resets_at: Some("2026-01-01T05:00:00.000Z"),
+            current_weekly_resets_at: Some("2026-01-08T00:00:00.000Z"),
+            previous_session_percent: None,
+            previous_weekly_percent: None,
+            last_notified_session_period_id: None,
+            last_notified_weekly_period_id: None,
+            session_threshold: 90.0,
+            weekly_threshold: 90.0,
+        });
+        assert_eq!(
+            decision.session_period_id.as_deref(),
+            Some("2026-01-01T05:00:00.000Z")
+        );
+        let mut map = JsonMap::new();
+        map_set_org_period_id(
+            &mut map,
+            "org-1",
+            decision.session_period_id.as_deref().unwrap(),
+        );
+        assert_eq!(
+            map_get_org_period_id(&map, "org-1").as_deref(),
+            Some("2026-01-01T05:00:00.000Z")
+        );
+    }
+
+    #[test]
+    fn snooze_toggles_and_expires() {
+        let now = Instant::now();
+        let hour = Duration::from_secs(3600);
+
+        let until = toggle_snooze(None, now, hour);
+        assert_eq!(snooze_remaining(until, now), Some(hour));
+        assert_eq!(
+            snooze_remaining(until, now + Duration::from_secs(600)),
+            Some(Duration::from_secs(3000))
+        );
+        assert_eq!(snooze_remaining(until, now + hour), None);
+
+        // Toggling while active clears it; toggling after expiry starts a new one.
+        assert_eq!(
+            toggle_snooze(until, now + Duration::from_secs(60), hour),
+            None
+        );
+        assert_eq!(
+            toggle_snooze(until, now + hour * 2, hour),
+            Some(now + hour * 3)
+        );
+    }
+
+    #[test]
+    fn quiet_hours_handle_same_day_and_midnight_wrap() {
+        let t = |s| parse_quiet_hours_time(s).unwrap();
+
+        // 22:00–07:00 wraps past midnight.
+        assert!(is_within_quiet_hours(t("03:00"), t("22:00"), t("07:00")));
+        assert!(is_within_quiet_hours(t("22:00"), t("22:00"), t("07:00")));
+        assert!(!is_within_quiet_hours(t("07:00"), t("22:00"), t("07:00")));
+        assert!(!is_within_quiet_hours(t("12:00"), t("22:00"), t("07:00")));
+
+        assert!(is_within_quiet_hours(t("13:30"), t("13:00"), t("14:00")));
+        assert!(!is_within_quiet_hours(t("14:00"), t("13:00"), t("14:00")));
+        assert!(!is_within_quiet_hours(t("08:00"), t("08:00"), t("08:00")));
+
+        assert_eq!(parse_quiet_hours_time("25:00"), None);
+        assert_eq!(parse_quiet_hours_time(""), None);
+    }
+
+    #[test]
+    fn recovery_fires_only_on_failure_to_ok() {
+        use UsageStatus::*;
+        for failed in [Unauthorized, MissingKey, RateLimited, Error, Offline] {
+            assert!(recovered(Some(failed), Some(Ok)));
+            assert!(!recovered(Some(Ok), Some(failed)));
+        }
+        assert!(!recovered(Some(Ok), Some(Ok)));
+        assert!(!recovered(None, Some(Ok)));
+        assert!(!recovered(Some(Error), None));
+    }
+
+    #[test]
+    fn rate_limit_notice_fires_once_per_span() {
+        use UsageStatus::*;
+        assert_eq!(rate_limit_notice(RateLimited, false), (true, true));
+        assert_eq!(rate_limit_notice(RateLimited, true), (false, true));
+        // A transient error mid-span doesn't re-arm it; only getting back to Ok does.
+        assert_eq!(rate_limit_notice(Error, true), (false, true));
+        assert_eq!(rate_limit_notice(Ok, true), (false, false));
+        assert_eq!(rate_limit_notice(RateLimited, false), (true, true));
+
+        assert_eq!(
+            rate_limit_message(
+                "Claude",
+                crate::refresh::PollTiming::default().rate_limit_backoff_ms,
+                "14:35"
+            ),
+            "Claude is rate limiting usage requests. Polling has backed off to every 5 minutes; next try around 14:35."
+        );
+    }
+}
@@ -1,10 +1,16 @@
 use crate::types::{ClaudeUsageSnapshot, CodexUsageSnapshot};
 
+pub(crate) const CLAUDE_PROVIDER_KEY: &str = "claude";
+pub(crate) const CODEX_PROVIDER_KEY: &str = "codex";
+
 pub struct ProviderOkView<'a> {
     pub provider_label: &'static str,
+    /// Stable provider prefix used to namespace persisted per-scope state.
+    pub provider_key: &'static str,
     pub scope_id: &'a str,
     pub session_percent: f64,
-    pub weekly_percent: f64,
+    /// `None` when the provider reported no weekly utilization.
+    pub weekly_percent: Option<f64>,
     pub session_resets_at: Option<&'a str>,
     pub weekly_resets_at: Option<&'a str>,
 }
@@ -21,6 +27,7 @@ pub fn view_claude(snapshot: &ClaudeUsageSnapshot) -> Option<ProviderOkView<'_>>
             ..
         } => Some(ProviderOkView {
             provider_label: "Claude",
+            provider_key: CLAUDE_PROVIDER_KEY,
             scope_id: organization_id,
             session_percent: *session_percent,
             weekly_percent: *weekly_percent,
@@ -39,8 +46,10 @@ pub fn view_codex(snapshot: &CodexUsageSnapshot) -> Option<ProviderOkView<'_>> {
             weekly_percent,
             weekly_resets_at,
             last_updated_at: _,
+            ..
         } => Some(ProviderOkView {
             provider_label: "Codex",
+            provider_key: CODEX_PROVIDER_KEY,
             scope_id: "codex",
             session_percent: *session_percent,
             weekly_percent: *weekly_percent,
@@ -50,3 +59,70 @@ pub fn view_codex(snapshot: &CodexUsageSnapshot) -> Option<ProviderOkView<'_>> {
         _ => None,
     }
 }
+
+impl ProviderOkView<'_> {
+    /// Key for dedupe maps and baselines, e.g. `claude:<org>` or `codex:codex`, so a Claude scope
+    /// can never collide with the Codex one.
+    pub fn dedupe_key(&self) -> String {
+        format!("{}:{}", self.provider_key, self.scope_id)
+    }
+}
+
+/// Map a dedupe key written before keys were namespaced to its namespaced form.
+/// Already-namespaced keys are returned unchanged, so this is safe to run repeatedly.
+pub fn namespace_legacy_dedupe_key(key: &str) -> String {
+    let namespaced = [CLAUDE_PROVIDER_KEY, CODEX_PROVIDER_KEY].iter().any(|p| {
+        key.strip_prefix(p)
+            .is_some_and(|rest| rest.starts_with(':'))
+    });
+    if namespaced {
+        key.to_string()
+    } else if key == CODEX_PROVIDER_KEY {
+        format!("{CODEX_PROVIDER_KEY}:{key}")
+    } else {
+        format!("{CLAUDE_PROVIDER_KEY}:{key}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dedupe_keys_do_not_collide_across_providers() {
+        let claude = ClaudeUsageSnapshot::Ok {
+            organization_id: "codex".to_string(),
+            session_percent: 10.0,
+            session_resets_at: None,
+            weekly_percent: Some(10.0),
+            weekly_resets_at: None,
+            models: vec![],
+            session_models: vec![],
+            last_updated_at: "2026-01-01T00:00:00Z".to_string(),
+            provider_timestamp: None,
+        };
+        let codex = CodexUsageSnapshot::Ok {
+            session_percent: 10.0,
+            session_resets_at: None,
+            weekly_percent: Some(10.0),
+            weekly_resets_at: None,
+            last_updated_at: "2026-01-01T00:00:00Z".to_string(),
+            provider_timestamp: None,
+        };
+        let claude_key = view_claude(&claude).unwrap().dedupe_key();
+        let codex_key = view_codex(&codex).unwrap().dedupe_key();
+        assert_eq!(claude_key, "claude:codex");
+        assert_eq!(codex_key, "codex:codex");
+    }
+
+    #[test]
+    fn namespace_legacy_dedupe_key_is_idempotent() {
+        assert_eq!(namespace_legacy_dedupe_key("codex"), "codex:codex");
+        assert_eq!(namespace_legacy_dedupe_key("org-123"), "claude:org-123");
+        assert_eq!(
+            namespace_legacy_dedupe_key("claude:org-123"),
+            "claude:org-123"
+        );
+        assert_eq!(namespace_legacy_dedupe_key("codex:codex"), "codex:codex");
+    }
+}
@@ -1,8 +1,12 @@
 use crate::claude::{read_cli_oauth_access_token, ClaudeWebErrorStatus, CliCredentialsError};
-use crate::codex::read_codex_oauth_credentials;
+use crate::codex::{read_codex_oauth_credentials, CodexCliOptions};
+use crate::settings::{
+    KEY_CODEX_CLI_KEEP_ALIVE, KEY_CODEX_CLI_PATH, KEY_CODEX_CLI_TIMEOUT_SECONDS,
+};
 use crate::state::AppState;
 use crate::types::{
-    ClaudeUsageSnapshot, CodexUsageSnapshot, CodexUsageSource, UsageSnapshotBundle, UsageSource,
+    AutoResolution, ClaudeOrgSnapshot, ClaudeOrganization, ClaudeUsageSnapshot, CodexUsageSnapshot,
+    CodexUsageSource, MissingOrgPolicy, UsageSnapshotBundle, UsageSource, UsageStatus,
 };
 use tauri::Runtime;
 
@@ -16,7 +20,12 @@ pub(crate) fn bundle(
     claude: Option<ClaudeUsageSnapshot>,
     codex: Option<CodexUsageSnapshot>,
 ) -> UsageSnapshotBundle {
-    UsageSnapshotBundle { claude, codex }
+    UsageSnapshotBundle {
+        claude,
+        codex,
+        offline: false,
+        claude_orgs: vec![],
+    }
 }
 
 pub(crate) fn claude_missing_key_snapshot() -> ClaudeUsageSnapshot {
@@ -27,6 +36,21 @@ pub(crate) fn claude_missing_key_snapshot() -> ClaudeUsageSnapshot {
     }
 }
 
+pub(crate) fn claude_timed_out_snapshot() -> ClaudeUsageSnapshot {
+    ClaudeUsageSnapshot::Error {
+        organization_id: None,
+        last_updated_at: now_iso(),
+        error_message: Some("Claude usage fetch timed out.".to_string()),
+    }
+}
+
+pub(crate) fn codex_timed_out_snapshot() -> CodexUsageSnapshot {
+    CodexUsageSnapshot::Error {
+        last_updated_at: now_iso(),
+        error_message: Some("Codex usage fetch timed out.".to_string()),
+    }
+}
+
 fn claude_unauthorized_snapshot(message: &str) -> ClaudeUsageSnapshot {
     ClaudeUsageSnapshot::Unauthorized {
         organization_id: None,
@@ -40,6 +64,7 @@ fn claude_rate_limited_snapshot(message: &str) -> ClaudeUsageSnapshot {
         organization_id: None,
         last_updated_at: now_iso(),
         error_message: Some(message.to_string()),
+        retry_after_seconds: None,
     }
 }
 
@@ -51,10 +76,54 @@ fn claude_error_snapshot(message: &str) -> ClaudeUsageSnapshot {
     }
 }
 
+const SELECTED_ORG_MISSING_MESSAGE: &str =
+    "The selected Claude organization is no longer available. Choose one in Settings.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum OrgChoice {
+    /// The stored selection still exists.
+    Keep(String),
+    /// Nothing was selected yet; the first organization is used.
+    Initial(String),
+    /// The stored selection disappeared and the policy allowed switching.
+    Switched {
+        to: String,
+        to_name: Option<String>,
+    },
+    /// The stored selection disappeared and the policy says to wait for the user.
+    Missing,
+    NoOrganizations,
+}
+
+pub(crate) fn choose_organization(
+    orgs: &[ClaudeOrganization],
+    stored: Option<&str>,
+    policy: MissingOrgPolicy,
+) -> OrgChoice {
+    if let Some(stored) = stored {
+        if orgs.iter().any(|o| o.id == stored) {
+            return OrgChoice::Keep(stored.to_string());
+        }
+    }
+
+    let Some(first) = orgs.first() else {
+        return OrgChoice::NoOrganizations;
+    };
+
+    match (stored, policy) {
+        (None, _) => OrgChoice::Initial(first.id.clone()),
+        (Some(_), MissingOrgPolicy::AutoSwitch) => OrgChoice::Switched {
+            to: first.id.clone(),
+            to_name: first.name.clone().filter(|n| !n.trim().is_empty()),
+        },
+        (Some(_), MissingOrgPolicy::Wait) => OrgChoice::Missing,
+    }
+}
+
 async fn resolve_organization_id<R: Runtime>(
     state: &AppState<R>,
     session_key: &str,
-) -> Result<Option<String>, ClaudeWebErrorStatus> {
+) -> Result<OrgChoice, ClaudeWebErrorStatus> {
     let orgs = state.get_organizations_cached(session_key).await?;
 
     {
@@ -62,25 +131,24 @@ async fn resolve_organization_id<R: Runtime>(
         *guard = orgs.clone();
     }
 
-    let stored = state.selected_org_id();
-    if let Some(stored) = stored {
-        if orgs.iter().any(|o| o.id == stored) {
-            return Ok(Some(stored));
-        }
-    }
-
-    let first = orgs.first().map(|o| o.id.clone());
-    if let Some(id) = &first {
+    let choice = choose_organization(
+        &orgs,
+        state.selected_org_id().as_deref(),
+        state.missing_org_policy(),
+    );
+    if let OrgChoice::Initial(id) | OrgChoice::Switched { to: id, .. } = &choice {
         state
             .settings
             .set(crate::settings::KEY_SELECTED_ORGANIZATION_ID, id.clone());
     }
-    Ok(first)
+    Ok(choice)
 }
 
 pub(crate) struct FetchSnapshot<T> {
     pub(crate) snapshot: T,
     pub(crate) keyring_error: bool,
+    /// Note for the user about the tracked organization changing or disappearing.
+    pub(crate) org_notice: Option<String>,
 }
 
 pub(crate) async fn fetch_claude_snapshot<R: Runtime>(
@@ -95,6 +163,7 @@ pub(crate) async fn fetch_claude_snapshot<R: Runtime>(
                     return FetchSnapshot {
                         snapshot: claude_missing_key_snapshot(),
                         keyring_error: false,
+                        org_notice: None,
                     };
                 }
                 Err(()) => {
@@ -107,34 +176,53 @@ pub(crate) async fn fetch_claude_snapshot<R: Runtime>(
                             ),
                         },
                         keyring_error: true,
+                        org_notice: None,
                     };
                 }
             };
 
-            let org_id = match resolve_organization_id(state, &session_key).await {
-                Ok(Some(id)) => id,
-                Ok(None) => {
+            let (org_id, org_notice) = match resolve_organization_id(state, &session_key).await {
+                Ok(OrgChoice::Keep(id) | OrgChoice::Initial(id)) => (id, None),
+                Ok(OrgChoice::Switched { to, to_name }) => {
+                    let notice = format!(
+                        "The selected Claude organization is no longer available; switched to {}.",
+                        to_name.as_deref().unwrap_or(&to)
+                    );
+                    (to, Some(notice))
+                }
+                Ok(OrgChoice::Missing) => {
+                    return FetchSnapshot {
+                        snapshot: claude_error_snapshot(SELECTED_ORG_MISSING_MESSAGE),
+                        keyring_error: false,
+                        org_notice: Some(SELECTED_ORG_MISSING_MESSAGE.to_string()),
+                    };
+                }
+                Ok(OrgChoice::NoOrganizations) => {
                     return FetchSnapshot {
                         snapshot: claude_error_snapshot("No organizations found."),
                         keyring_error: false,
+                        org_notice: None,
                     };
                 }
                 Err(ClaudeWebErrorStatus::Unauthorized) => {
                     return FetchSnapshot {
                         snapshot: claude_unauthorized_snapshot("Unauthorized."),
                         keyring_error: false,
+                        org_notice: None,
                     };
                 }
                 Err(ClaudeWebErrorStatus::RateLimited) => {
                     return FetchSnapshot {
                         snapshot: claude_rate_limited_snapshot("Rate limited."),
                         keyring_error: false,
+                        org_notice: None,
                     };
                 }
                 Err(ClaudeWebErrorStatus::Error) => {
                     return FetchSnapshot {
                         snapshot: claude_error_snapshot("Failed to fetch organizations."),
                         keyring_error: false,
+                        org_notice: None,
                     };
                 }
             };
@@ -145,6 +233,7 @@ pub(crate) async fn fetch_claude_snapshot<R: Runtime>(
                     .fetch_usage_snapshot(&session_key, &org_id)
                     .await,
                 keyring_error: false,
+                org_notice,
             }
         }
         UsageSource::Cli => {
@@ -156,6 +245,7 @@ pub(crate) async fn fetch_claude_snapshot<R: Runtime>(
                             "HOME is not set; cannot locate CLI credentials.",
                         ),
                         keyring_error: false,
+                        org_notice: None,
                     };
                 }
                 Err(CliCredentialsError::MissingFile | CliCredentialsError::MissingAccessToken) => {
@@ -164,6 +254,16 @@ pub(crate) async fn fetch_claude_snapshot<R: Runtime>(
                             "Claude CLI credentials not found. Run `claude login` and try again.",
                         ),
                         keyring_error: false,
+                        org_notice: None,
+                    };
+                }
+                Err(CliCredentialsError::Expired) => {
+                    return FetchSnapshot {
+                        snapshot: claude_unauthorized_snapshot(
+                            "Claude CLI session expired. Run `claude` to renew it and try again.",
+                        ),
+                        keyring_error: false,
+                        org_notice: None,
                     };
                 }
                 Err(CliCredentialsError::InvalidJson) => {
@@ -172,6 +272,7 @@ pub(crate) async fn fetch_claude_snapshot<R: Runtime>(
                             "Claude CLI credentials are invalid. Re-authenticate (run `claude login`).",
                         ),
                         keyring_error: false,
+                        org_notice: None,
                     };
                 }
             };
@@ -179,36 +280,251 @@ pub(crate) async fn fetch_claude_snapshot<R: Runtime>(
             FetchSnapshot {
                 snapshot: state.claude.fetch_oauth_usage_snapshot(&access_token).await,
                 keyring_error: false,
+                org_notice: None,
             }
         }
     }
 }
 
+/// Usage of every organization besides the selected one, when `trackAllOrgs` is on. Runs after
+/// `fetch_claude_snapshot`, which refreshes `state.organizations`; only the web source has
+/// organizations, so this is empty otherwise.
+pub(crate) async fn fetch_other_claude_orgs<R: Runtime>(
+    state: &AppState<R>,
+) -> Vec<ClaudeOrgSnapshot> {
+    if !state.track_all_orgs() || state.usage_source() != UsageSource::Web {
+        return vec![];
+    }
+    let remember = state.remember_session_key();
+    let Ok(Some(session_key)) = state.claude_session_key.get_current(remember).await else {
+        return vec![];
+    };
+    let selected = state.selected_org_id();
+    let orgs = state.organizations.lock().await.clone();
+
+    // One at a time: these are extra requests on top of the usual cycle, so keep them gentle.
+    let mut out = Vec::new();
+    for organization in orgs
+        .into_iter()
+        .filter(|o| selected.as_deref() != Some(o.id.as_str()))
+    {
+        let snapshot = state
+            .claude
+            .fetch_usage_snapshot(&session_key, &organization.id)
+            .await;
+        out.push(ClaudeOrgSnapshot {
+            organization,
+            snapshot,
+        });
+    }
+    out
+}
+
+/// Sources the Auto setting tries before falling back to the CLI: OAuth, then the web cookie,
+/// each only when its credentials are present.
+fn codex_auto_order(has_oauth: bool, has_cookie: bool) -> Vec<CodexUsageSource> {
+    let mut order = Vec::new();
+    if has_oauth {
+        order.push(CodexUsageSource::Oauth);
+    }
+    if has_cookie {
+        order.push(CodexUsageSource::Web);
+    }
+    order
+}
+
+/// Whether Auto moves on after `snapshot`. Rate limits and outages would hit the next source
+/// as well, so only credential and parse failures fall through.
+fn codex_auto_should_try_next(snapshot: &CodexUsageSnapshot) -> bool {
+    matches!(
+        snapshot.status(),
+        UsageStatus::Unauthorized | UsageStatus::MissingKey | UsageStatus::Error
+    )
+}
+
 pub(crate) async fn fetch_codex_snapshot<R: Runtime>(
     state: &AppState<R>,
 ) -> FetchSnapshot<CodexUsageSnapshot> {
-    match state.codex_usage_source() {
+    let (fetched, resolution) = match state.codex_usage_source() {
+        CodexUsageSource::Auto => {
+            let (fetched, resolution) = fetch_codex_auto(state).await;
+            (fetched, Some(resolution))
+        }
+        source => (fetch_codex_from(state, source).await, None),
+    };
+    *state.codex_auto_resolution.lock().await = resolution;
+    fetched
+}
+
+async fn fetch_codex_auto<R: Runtime>(
+    state: &AppState<R>,
+) -> (FetchSnapshot<CodexUsageSnapshot>, AutoResolution) {
+    let has_cookie = matches!(
+        state
+            .codex_cookie
+            .get_current(state.remember_codex_cookie())
+            .await,
+        Ok(Some(_))
+    );
+    let mut attempts = Vec::new();
+    for source in codex_auto_order(read_codex_oauth_credentials().is_ok(), has_cookie) {
+        let fetched = fetch_codex_from(state, source).await;
+        attempts.push((source, fetched.snapshot.status()));
+        if !codex_auto_should_try_next(&fetched.snapshot) {
+            return (fetched, AutoResolution { source, attempts });
+        }
+    }
+    let fetched = fetch_codex_from(state, CodexUsageSource::Cli).await;
+    attempts.push((CodexUsageSource::Cli, fetched.snapshot.status()));
+    let resolution = AutoResolution {
+        source: CodexUsageSource::Cli,
+        attempts,
+    };
+    (fetched, resolution)
+}
+
+/// How the CLI source is launched, from the `codexCli*` settings.
+pub(crate) fn codex_cli_options<R: Runtime>(state: &AppState<R>) -> CodexCliOptions {
+    CodexCliOptions::from_settings(
+        state.settings.get_string(KEY_CODEX_CLI_PATH),
+        state.settings.get_u64(KEY_CODEX_CLI_TIMEOUT_SECONDS, 12),
+        state.settings.get_bool(KEY_CODEX_CLI_KEEP_ALIVE, false),
+    )
+}
+
+async fn fetch_codex_from<R: Runtime>(
+    state: &AppState<R>,
+    source: CodexUsageSource,
+) -> FetchSnapshot<CodexUsageSnapshot> {
+    let snapshot = match source {
         CodexUsageSource::Oauth => match read_codex_oauth_credentials() {
-            Ok(creds) => FetchSnapshot {
-                snapshot: state
-                    .codex
-                    .fetch_oauth_usage_snapshot(&creds.access_token, creds.account_id.as_deref())
-                    .await,
-                keyring_error: false,
+            Ok(creds) => state.codex.fetch_oauth_usage_snapshot(&creds).await,
+            Err(_) => CodexUsageSnapshot::Unauthorized {
+                last_updated_at: now_iso(),
+                error_message: Some(
+                    "Codex credentials not found. Run `codex` to log in.".to_string(),
+                ),
             },
-            Err(_) => FetchSnapshot {
-                snapshot: CodexUsageSnapshot::Unauthorized {
+        },
+        CodexUsageSource::Web => {
+            let remember = state.remember_codex_cookie();
+            match state.codex_cookie.get_current(remember).await {
+                Ok(Some(cookie)) => state.codex.fetch_web_usage_snapshot(&cookie).await,
+                Ok(None) => CodexUsageSnapshot::MissingKey {
                     last_updated_at: now_iso(),
-                    error_message: Some(
-                        "Codex credentials not found. Run `codex` to log in.".to_string(),
-                    ),
+                    error_message: Some("Codex cookie is not configured.".to_string()),
                 },
-                keyring_error: false,
-            },
-        },
-        CodexUsageSource::Cli => FetchSnapshot {
-            snapshot: state.codex.fetch_cli_usage_snapshot("codex").await,
-            keyring_error: false,
-        },
+                Err(()) => {
+                    return FetchSnapshot {
+                        snapshot: CodexUsageSnapshot::MissingKey {
+                            last_updated_at: now_iso(),
+                            error_message: Some(
+                                "OS keychain/secret service is unavailable.".to_string(),
+                            ),
+                        },
+                        keyring_error: true,
+                        org_notice: None,
+                    };
+                }
+            }
+        }
+        // Auto is resolved to one of the others by `fetch_codex_snapshot`.
+        CodexUsageSource::Cli | CodexUsageSource::Auto => {
+            state
+                .codex
+                .fetch_cli_usage_snapshot(&codex_cli_options(state))
+                .await
+        }
+    };
+    FetchSnapshot {
+        snapshot,
+        keyring_error: false,
+        org_notice: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codex_auto_tries_oauth_then_the_cookie() {
+        assert_eq!(
+            codex_auto_order(true, true),
+            vec![CodexUsageSource::Oauth, CodexUsageSource::Web]
+        );
+        assert_eq!(codex_auto_order(false, true), vec![CodexUsageSource::Web]);
+        assert_eq!(codex_auto_order(true, false), vec![CodexUsageSource::Oauth]);
+        assert!(codex_auto_order(false, false).is_empty());
+    }
+
+    #[test]
+    fn codex_auto_falls_through_only_on_credential_or_parse_failures() {
+        let at = now_iso();
+        let unauthorized = CodexUsageSnapshot::Unauthorized {
+            last_updated_at: at.clone(),
+            error_message: None,
+        };
+        let rate_limited = CodexUsageSnapshot::RateLimited {
+            last_updated_at: at.clone(),
+            error_message: None,
+            retry_after_seconds: None,
+        };
+        let offline = CodexUsageSnapshot::Offline {
+            last_updated_at: at.clone(),
+            error_message: None,
+        };
+        let ok = CodexUsageSnapshot::Ok {
+            session_percent: 10.0,
+            session_resets_at: None,
+            weekly_percent: None,
+            weekly_resets_at: None,
+            last_updated_at: at,
+            provider_timestamp: None,
+        };
+        assert!(codex_auto_should_try_next(&unauthorized));
+        assert!(!codex_auto_should_try_next(&rate_limited));
+        assert!(!codex_auto_should_try_next(&offline));
+        assert!(!codex_auto_should_try_next(&ok));
+    }
+
+    fn org(id: &str, name: Option<&str>) -> ClaudeOrganization {
+        ClaudeOrganization {
+            id: id.to_string(),
+            name: name.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn choose_organization_keeps_existing_selection() {
+        let orgs = vec![org("a", None), org("b", None)];
+        assert_eq!(
+            choose_organization(&orgs, Some("b"), MissingOrgPolicy::Wait),
+            OrgChoice::Keep("b".to_string())
+        );
+        assert_eq!(
+            choose_organization(&orgs, None, MissingOrgPolicy::Wait),
+            OrgChoice::Initial("a".to_string())
+        );
+    }
+
+    #[test]
+    fn choose_organization_detects_deleted_selection_per_policy() {
+        let orgs = vec![org("a", Some("Team A"))];
+        assert_eq!(
+            choose_organization(&orgs, Some("gone"), MissingOrgPolicy::AutoSwitch),
+            OrgChoice::Switched {
+                to: "a".to_string(),
+                to_name: Some("Team A".to_string())
+            }
+        );
+        assert_eq!(
+            choose_organization(&orgs, Some("gone"), MissingOrgPolicy::Wait),
+            OrgChoice::Missing
+        );
+        assert_eq!(
+            choose_organization(&[], Some("gone"), MissingOrgPolicy::AutoSwitch),
+            OrgChoice::NoOrganizations
+        );
     }
 }
@@ -0,0 +1,305 @@
+use crate::provider_view::{CLAUDE_PROVIDER_KEY, CODEX_PROVIDER_KEY};
+use crate::redact::redact_secrets;
+use crate::types::{
+    ClaudeOrgSnapshot, ClaudeUsageSnapshot, CodexUsageSnapshot, IpcErrorCode, RefreshErrorEvent,
+    UsageSnapshotBundle, UsageStatus,
+};
+
+/// Result of one provider's fetch task. `None` means the provider is not tracked.
+#[derive(Debug, Clone)]
+pub(crate) enum ProviderUpdate {
+    Claude(Option<ClaudeUsageSnapshot>),
+    Codex(Option<CodexUsageSnapshot>),
+    /// The other tracked Claude organizations (see `fetch_other_claude_orgs`). Never notifies
+    /// and never counts as a success for the selected organization.
+    ClaudeOrgs(Vec<ClaudeOrgSnapshot>),
+}
+
+impl ProviderUpdate {
+    /// `(notify_claude, notify_codex)` for notifying only about the provider that changed.
+    pub(crate) fn notify_flags(&self) -> (bool, bool) {
+        match self {
+            Self::Claude(snapshot) => (snapshot.is_some(), false),
+            Self::Codex(snapshot) => (false, snapshot.is_some()),
+            Self::ClaudeOrgs(_) => (false, false),
+        }
+    }
+
+    /// `(claude_ok, codex_ok)`: whether this update is a successful fetch for its provider.
+    pub(crate) fn success_flags(&self) -> (bool, bool) {
+        match self {
+            Self::Claude(snapshot) => (
+                matches!(snapshot, Some(ClaudeUsageSnapshot::Ok { .. })),
+                false,
+            ),
+            Self::Codex(snapshot) => (
+                false,
+                matches!(snapshot, Some(CodexUsageSnapshot::Ok { .. })),
+            ),
+            Self::ClaudeOrgs(_) => (false, false),
+        }
+    }
+
+    pub(crate) fn provider_key(&self) -> &'static str {
+        match self {
+            Self::Claude(_) | Self::ClaudeOrgs(_) => CLAUDE_PROVIDER_KEY,
+            Self::Codex(_) => CODEX_PROVIDER_KEY,
+        }
+    }
+
+    /// Whether the fetch failed only because the network was unreachable.
+    fn is_offline(&self) -> bool {
+        match self {
+            Self::Claude(Some(snapshot)) => snapshot.status() == UsageStatus::Offline,
+            Self::Codex(Some(snapshot)) => snapshot.status() == UsageStatus::Offline,
+            _ => false,
+        }
+    }
+}
+
+/// Replace one provider's slot in `latest`, keeping the other provider's last snapshot. When the
+/// update failed because we're offline, the provider's last Ok snapshot is kept instead and the
+/// bundle is marked `offline` until a fetch succeeds again.
+pub(crate) fn merge_provider_update(
+    latest: Option<&UsageSnapshotBundle>,
+    update: ProviderUpdate,
+) -> UsageSnapshotBundle {
+    let mut merged = latest.cloned().unwrap_or(UsageSnapshotBundle {
+        claude: None,
+        codex: None,
+        offline: false,
+        claude_orgs: vec![],
+    });
+    if let ProviderUpdate::ClaudeOrgs(orgs) = update {
+        merged.claude_orgs = orgs;
+        return merged;
+    }
+    let has_last_ok = match update {
+        ProviderUpdate::Claude(_) => matches!(merged.claude, Some(ClaudeUsageSnapshot::Ok { .. })),
+        ProviderUpdate::Codex(_) => matches!(merged.codex, Some(CodexUsageSnapshot::Ok { .. })),
+        ProviderUpdate::ClaudeOrgs(_) => false,
+    };
+    if update.is_offline() && has_last_ok {
+        merged.offline = true;
+        return merged;
+    }
+    let (claude_ok, codex_ok) = update.success_flags();
+    if claude_ok || codex_ok {
+        merged.offline = false;
+    }
+    match update {
+        ProviderUpdate::Claude(snapshot) => {
+            if snapshot.is_none() {
+                merged.claude_orgs.clear();
+            }
+            merged.claude = snapshot;
+        }
+        ProviderUpdate::Codex(snapshot) => merged.codex = snapshot,
+        ProviderUpdate::ClaudeOrgs(_) => {}
+    }
+    merged
+}
+
+fn provider_status(
+    bundle: &UsageSnapshotBundle,
+    provider_key: &str,
+) -> Option<(UsageStatus, Option<&str>)> {
+    if provider_key == CLAUDE_PROVIDER_KEY {
+        bundle
+            .claude
+            .as_ref()
+            .map(|s| (s.status(), s.error_message()))
+    } else {
+        bundle
+            .codex
+            .as_ref()
+            .map(|s| (s.status(), s.error_message()))
+    }
+}
+
+/// The `refresh:error` payload when `provider_key` moved into Unauthorized, RateLimited, Error or
+/// Offline between `previous` and `merged`; `None` while it stays in the same state.
+pub(crate) fn refresh_error_event(
+    provider_key: &'static str,
+    previous: Option<&UsageSnapshotBundle>,
+    merged: &UsageSnapshotBundle,
+) -> Option<RefreshErrorEvent> {
+    let (status, message) = provider_status(merged, provider_key)?;
+    let code = match status {
+        UsageStatus::Unauthorized => IpcErrorCode::Unauthorized,
+        UsageStatus::RateLimited => IpcErrorCode::RateLimited,
+        UsageStatus::Error | UsageStatus::Offline => IpcErrorCode::Network,
+        UsageStatus::Ok | UsageStatus::MissingKey => return None,
+    };
+    let previous_status = previous
+        .and_then(|bundle| provider_status(bundle, provider_key))
+        .map(|(status, _)| status);
+    if previous_status == Some(status) {
+        return None;
+    }
+    Some(RefreshErrorEvent {
+        provider: provider_key.to_string(),
+        code,
+        message: redact_secrets(message.unwrap_or("Refresh failed.")).into_owned(),
+    })
+}
+
+/// Whether every enabled provider failed on the keychain, given each provider's
+/// `(enabled, keyring_error)` from the concurrent fetches. One provider still working means the
+/// refresh as a whole succeeded.
+pub(crate) fn keyring_unavailable(claude: (bool, bool), codex: (bool, bool)) -> bool {
+    let keyring_errors = claude.1 as u8 + codex.1 as u8;
+    let enabled_providers = claude.0 as u8 + codex.0 as u8;
+    keyring_errors > 0 && enabled_providers > 0 && keyring_errors >= enabled_providers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_client::OFFLINE_ERROR_MESSAGE;
+
+    fn codex_ok(session_percent: f64) -> CodexUsageSnapshot {
+        CodexUsageSnapshot::Ok {
+            session_percent,
+            session_resets_at: None,
+            weekly_percent: Some(10.0),
+            weekly_resets_at: None,
+            last_updated_at: "2026-01-01T00:00:00Z".to_string(),
+            provider_timestamp: None,
+        }
+    }
+
+    fn claude_error() -> ClaudeUsageSnapshot {
+        ClaudeUsageSnapshot::Error {
+            organization_id: None,
+            last_updated_at: "2026-01-01T00:00:00Z".to_string(),
+            error_message: Some("timed out".to_string()),
+        }
+    }
+
+    #[test]
+    fn offline_update_keeps_last_ok_values_until_a_fetch_succeeds() {
+        let offline = || {
+            ProviderUpdate::Codex(Some(CodexUsageSnapshot::Offline {
+                last_updated_at: "2026-01-01T00:05:00Z".to_string(),
+                error_message: Some(OFFLINE_ERROR_MESSAGE.to_string()),
+            }))
+        };
+        let first = merge_provider_update(None, ProviderUpdate::Codex(Some(codex_ok(20.0))));
+        let kept = merge_provider_update(Some(&first), offline());
+        assert!(kept.offline);
+        assert!(matches!(
+            kept.codex,
+            Some(CodexUsageSnapshot::Ok { session_percent, .. }) if session_percent == 20.0
+        ));
+
+        let back = merge_provider_update(Some(&kept), ProviderUpdate::Codex(Some(codex_ok(25.0))));
+        assert!(!back.offline);
+
+        // Nothing to fall back to, and other failures, still surface as they are.
+        let never_ok = merge_provider_update(None, offline());
+        assert!(!never_ok.offline);
+        assert!(matches!(
+            never_ok.codex,
+            Some(CodexUsageSnapshot::Offline { .. })
+        ));
+        let claude =
+            merge_provider_update(Some(&first), ProviderUpdate::Claude(Some(claude_error())));
+        assert!(!claude.offline);
+    }
+
+    #[test]
+    fn refresh_error_event_fires_on_transition_only() {
+        let ok = merge_provider_update(None, ProviderUpdate::Codex(Some(codex_ok(20.0))));
+        let failed = merge_provider_update(
+            Some(&ok),
+            ProviderUpdate::Codex(Some(CodexUsageSnapshot::Unauthorized {
+                last_updated_at: "2026-01-01T00:05:00Z".to_string(),
+                error_message: Some("Cookie: sessionKey=sk-ant-sid01-abc".to_string()),
+            })),
+        );
+
+        let event = refresh_error_event(CODEX_PROVIDER_KEY, Some(&ok), &failed).unwrap();
+        assert_eq!(event.provider, "codex");
+        assert!(matches!(event.code, IpcErrorCode::Unauthorized));
+        assert!(!event.message.contains("sk-ant-sid01-abc"));
+
+        assert!(refresh_error_event(CODEX_PROVIDER_KEY, Some(&failed), &failed).is_none());
+        assert!(refresh_error_event(CODEX_PROVIDER_KEY, Some(&failed), &ok).is_none());
+        assert!(refresh_error_event(CLAUDE_PROVIDER_KEY, Some(&ok), &failed).is_none());
+    }
+
+    #[test]
+    fn provider_updates_merge_independently() {
+        let first = merge_provider_update(None, ProviderUpdate::Codex(Some(codex_ok(20.0))));
+        assert!(first.claude.is_none());
+
+        // A slow/failed Claude update keeps the Codex data already published.
+        let second =
+            merge_provider_update(Some(&first), ProviderUpdate::Claude(Some(claude_error())));
+        assert!(matches!(
+            second.claude,
+            Some(ClaudeUsageSnapshot::Error { .. })
+        ));
+        assert!(matches!(
+            second.codex,
+            Some(CodexUsageSnapshot::Ok { session_percent, .. }) if session_percent == 20.0
+        ));
+
+        let third =
+            merge_provider_update(Some(&second), ProviderUpdate::Codex(Some(codex_ok(35.0))));
+        assert!(matches!(
+            third.claude,
+            Some(ClaudeUsageSnapshot::Error { .. })
+        ));
+        assert!(matches!(
+            third.codex,
+            Some(CodexUsageSnapshot::Ok { session_percent, .. }) if session_percent == 35.0
+        ));
+    }
+
+    #[test]
+    fn keyring_is_unavailable_only_when_every_enabled_provider_failed() {
+        assert!(keyring_unavailable((true, true), (true, true)));
+        assert!(keyring_unavailable((true, true), (false, false)));
+        assert!(!keyring_unavailable((true, true), (true, false)));
+        assert!(!keyring_unavailable((true, false), (true, false)));
+        assert!(!keyring_unavailable((false, false), (false, false)));
+    }
+
+    #[test]
+    fn other_org_updates_only_replace_the_org_list() {
+        let org = || ClaudeOrgSnapshot {
+            organization: crate::types::ClaudeOrganization {
+                id: "org-2".to_string(),
+                name: Some("Team".to_string()),
+            },
+            snapshot: claude_error(),
+        };
+        let first = merge_provider_update(None, ProviderUpdate::Codex(Some(codex_ok(20.0))));
+        let update = ProviderUpdate::ClaudeOrgs(vec![org()]);
+        assert_eq!(update.notify_flags(), (false, false));
+        assert_eq!(update.success_flags(), (false, false));
+        let with_orgs = merge_provider_update(Some(&first), update);
+        assert_eq!(with_orgs.claude_orgs.len(), 1);
+        assert!(with_orgs.claude.is_none());
+        assert!(with_orgs.codex.is_some());
+        assert!(refresh_error_event(CLAUDE_PROVIDER_KEY, Some(&first), &with_orgs).is_none());
+
+        // Untracking Claude drops the other organizations too.
+        let untracked = merge_provider_update(Some(&with_orgs), ProviderUpdate::Claude(None));
+        assert!(untracked.claude_orgs.is_empty());
+    }
+
+    #[test]
+    fn untracked_provider_update_clears_its_slot() {
+        let latest = merge_provider_update(None, ProviderUpdate::Codex(Some(codex_ok(20.0))));
+        let update = ProviderUpdate::Codex(None);
+        assert_eq!(update.notify_flags(), (false, false));
+        assert!(merge_provider_update(Some(&latest), update).codex.is_none());
+        assert_eq!(
+            ProviderUpdate::Claude(Some(claude_error())).notify_flags(),
+            (true, false)
+        );
+    }
+}
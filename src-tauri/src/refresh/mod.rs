@@ -1,7 +1,15 @@
 mod fetch;
+mod merge;
 mod policy;
+mod power;
 mod refresh_loop;
+mod wake;
 
 pub use refresh_loop::spawn_refresh_loop;
+pub use wake::spawn_wake_refresh;
 
 pub(crate) use fetch::{bundle, claude_missing_key_snapshot};
+pub(crate) use policy::{
+    min_refresh_interval_seconds, next_jitter_fraction, rate_limit_wait_ms, PollTiming,
+    JITTER_RATIO_RANGE, RATE_LIMIT_BACKOFF_SECONDS_RANGE,
+};
@@ -1,4 +1,79 @@
+use super::power::PowerSource;
 use crate::types::{UsageSnapshotBundle, UsageStatus};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+/// Polling interval while any provider is rate limited (jittered by ±20%).
+pub(crate) const RATE_LIMIT_BACKOFF_MS: u64 = 5 * 60 * 1000;
+
+/// Factor applied to the configured interval while on battery with battery saver enabled.
+pub(crate) const BATTERY_SAVER_MULTIPLIER: u64 = 3;
+
+/// Jitter applied to the rate-limit backoff, wider than normal polling to spread retries.
+const RATE_LIMIT_JITTER_RATIO: f64 = 0.2;
+
+/// Shortest refresh interval, to avoid hammering the upstream APIs.
+const MIN_REFRESH_INTERVAL_SECONDS: u64 = 30;
+/// Shortest refresh interval in debug mode (`CLAUDOMETER_DEBUG`).
+const DEBUG_MIN_REFRESH_INTERVAL_SECONDS: u64 = 5;
+
+pub(crate) fn min_refresh_interval_seconds(debug_mode: bool) -> u64 {
+    if debug_mode {
+        DEBUG_MIN_REFRESH_INTERVAL_SECONDS
+    } else {
+        MIN_REFRESH_INTERVAL_SECONDS
+    }
+}
+
+/// Accepted range for `refreshJitterRatio`.
+pub(crate) const JITTER_RATIO_RANGE: std::ops::RangeInclusive<f64> = 0.0..=0.5;
+/// Accepted range for `rateLimitBackoffSeconds`.
+pub(crate) const RATE_LIMIT_BACKOFF_SECONDS_RANGE: std::ops::RangeInclusive<u64> = 30..=3600;
+
+/// User-tunable polling schedule: interval, jitter around it, and the backoff while rate limited.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PollTiming {
+    pub(crate) refresh_interval_seconds: u64,
+    /// Each delay moves up to this fraction of the interval either way.
+    pub(crate) jitter_ratio: f64,
+    pub(crate) rate_limit_backoff_ms: u64,
+}
+
+impl Default for PollTiming {
+    fn default() -> Self {
+        Self {
+            refresh_interval_seconds: 60,
+            jitter_ratio: 0.1,
+            rate_limit_backoff_ms: RATE_LIMIT_BACKOFF_MS,
+        }
+    }
+}
+
+impl PollTiming {
+    /// Build from stored settings, clamping out-of-range values (e.g. edited by hand).
+    pub(crate) fn from_settings(
+        refresh_interval_seconds: u64,
+        jitter_ratio: f64,
+        rate_limit_backoff_seconds: u64,
+        debug_mode: bool,
+    ) -> Self {
+        let jitter_ratio = if jitter_ratio.is_nan() {
+            Self::default().jitter_ratio
+        } else {
+            jitter_ratio.clamp(*JITTER_RATIO_RANGE.start(), *JITTER_RATIO_RANGE.end())
+        };
+        let backoff_seconds = rate_limit_backoff_seconds.clamp(
+            *RATE_LIMIT_BACKOFF_SECONDS_RANGE.start(),
+            *RATE_LIMIT_BACKOFF_SECONDS_RANGE.end(),
+        );
+        Self {
+            refresh_interval_seconds: refresh_interval_seconds
+                .max(min_refresh_interval_seconds(debug_mode)),
+            jitter_ratio,
+            rate_limit_backoff_ms: backoff_seconds * 1000,
+        }
+    }
+}
 
 pub(crate) fn should_pause_polling(
     track_claude: bool,
@@ -27,18 +102,73 @@ pub(crate) fn should_pause_polling(
     }
 }
 
-fn compute_next_delay_ms_with_nanos(base_ms: u64, ratio: f64, nanos: i128) -> u64 {
-    let frac = ((nanos % 1000) as f64) / 1000.0;
-    let delta = (frac * 2.0 - 1.0) * (base_ms as f64 * ratio);
+/// Counter behind `next_jitter_fraction`, advanced once per draw.
+static JITTER_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Uniform value in `[0, 1)` from a SplitMix64 sequence seeded once from the clock, so two
+/// delays computed in quick succession don't land on the same offset.
+pub(crate) fn next_jitter_fraction() -> f64 {
+    const GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+    static SEED: OnceLock<u64> = OnceLock::new();
+    let seed = *SEED.get_or_init(|| time::OffsetDateTime::now_utc().unix_timestamp_nanos() as u64);
+    let step = JITTER_COUNTER
+        .fetch_add(1, Ordering::Relaxed)
+        .wrapping_add(1);
+    let mut z = seed.wrapping_add(step.wrapping_mul(GAMMA));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    // Top 53 bits fill an f64 mantissa exactly.
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// `fraction` in `[0, 1]` maps linearly onto `[-ratio, +ratio]` of `base_ms`.
+fn compute_next_delay_ms_with_fraction(base_ms: u64, ratio: f64, fraction: f64) -> u64 {
+    let delta = (fraction * 2.0 - 1.0) * (base_ms as f64 * ratio);
     ((base_ms as f64 + delta).max(1000.0)) as u64
 }
 
-pub(crate) fn compute_next_delay_ms(
+/// The wait a server asked for via `Retry-After` (kept within the backoff range), plus up to
+/// `RATE_LIMIT_JITTER_RATIO` more so clients told the same time don't all return at once. Never
+/// earlier than asked, unless it asked for less than the range allows.
+fn retry_after_delay_ms(seconds: u64, fraction: f64) -> u64 {
+    let seconds = seconds.clamp(
+        *RATE_LIMIT_BACKOFF_SECONDS_RANGE.start(),
+        *RATE_LIMIT_BACKOFF_SECONDS_RANGE.end(),
+    );
+    (seconds as f64 * 1000.0 * (1.0 + RATE_LIMIT_JITTER_RATIO * fraction)) as u64
+}
+
+/// How long polling waits after a rate limit, before jitter: the server's `Retry-After` when it
+/// sent one, otherwise the configured backoff.
+pub(crate) fn rate_limit_wait_ms(retry_after_seconds: Option<u64>, backoff_ms: u64) -> u64 {
+    retry_after_seconds.map_or(backoff_ms, |seconds| retry_after_delay_ms(seconds, 0.0))
+}
+
+fn interval_multiplier(battery_saver: bool, power: PowerSource) -> u64 {
+    if battery_saver && power == PowerSource::Battery {
+        BATTERY_SAVER_MULTIPLIER
+    } else {
+        1
+    }
+}
+
+fn configured_base_ms(
     refresh_interval_seconds: u64,
+    battery_saver: bool,
+    power: PowerSource,
+) -> u64 {
+    refresh_interval_seconds * 1000 * interval_multiplier(battery_saver, power)
+}
+
+pub(crate) fn compute_next_delay_ms(
+    timing: PollTiming,
+    battery_saver: bool,
+    power: PowerSource,
     snapshot: &UsageSnapshotBundle,
 ) -> u64 {
-    let base_seconds = refresh_interval_seconds.max(30);
-    let configured_base_ms = base_seconds * 1000;
+    let configured_base_ms =
+        configured_base_ms(timing.refresh_interval_seconds, battery_saver, power);
 
     let any_rate_limited = snapshot
         .claude
@@ -49,22 +179,43 @@ pub(crate) fn compute_next_delay_ms(
             .as_ref()
             .is_some_and(|s| s.status() == UsageStatus::RateLimited);
 
+    let retry_after = snapshot
+        .claude
+        .as_ref()
+        .and_then(|s| s.retry_after_seconds())
+        .max(
+            snapshot
+                .codex
+                .as_ref()
+                .and_then(|s| s.retry_after_seconds()),
+        );
+    if let Some(seconds) = retry_after {
+        return retry_after_delay_ms(seconds, next_jitter_fraction());
+    }
+
     let (base_ms, ratio) = if any_rate_limited {
-        (5 * 60 * 1000, 0.2)
+        (timing.rate_limit_backoff_ms, RATE_LIMIT_JITTER_RATIO)
     } else {
-        (configured_base_ms, 0.1)
+        (configured_base_ms, timing.jitter_ratio)
     };
 
-    let nanos = time::OffsetDateTime::now_utc().unix_timestamp_nanos();
-    compute_next_delay_ms_with_nanos(base_ms, ratio, nanos)
+    compute_next_delay_ms_with_fraction(base_ms, ratio, next_jitter_fraction())
 }
 
+/// `None` waits for explicit refresh requests only: while polling is paused from the tray (which
+/// takes precedence), or while every tracked provider needs user action.
 pub(crate) fn compute_next_delay_for_latest(
+    manually_paused: bool,
     track_claude: bool,
     track_codex: bool,
-    refresh_interval_seconds: u64,
+    timing: PollTiming,
+    battery_saver: bool,
+    power: PowerSource,
     snapshot: Option<&UsageSnapshotBundle>,
 ) -> Option<u64> {
+    if manually_paused {
+        return None;
+    }
     let Some(snapshot) = snapshot else {
         return Some(60_000);
     };
@@ -72,7 +223,12 @@ pub(crate) fn compute_next_delay_for_latest(
     if should_pause_polling(track_claude, track_codex, snapshot) {
         None
     } else {
-        Some(compute_next_delay_ms(refresh_interval_seconds, snapshot))
+        Some(compute_next_delay_ms(
+            timing,
+            battery_saver,
+            power,
+            snapshot,
+        ))
     }
 }
 
@@ -90,10 +246,12 @@ mod tests {
                 organization_id: "org".to_string(),
                 session_percent: 10.0,
                 session_resets_at: None,
-                weekly_percent: 10.0,
+                weekly_percent: Some(10.0),
                 weekly_resets_at: None,
                 models: vec![],
+                session_models: vec![],
                 last_updated_at: "2026-01-01T00:00:00Z".to_string(),
+                provider_timestamp: None,
             },
             UsageStatus::Unauthorized => ClaudeUsageSnapshot::Unauthorized {
                 organization_id: None,
@@ -109,21 +267,28 @@ mod tests {
                 organization_id: None,
                 last_updated_at: "2026-01-01T00:00:00Z".to_string(),
                 error_message: None,
+                retry_after_seconds: None,
             },
             UsageStatus::Error => ClaudeUsageSnapshot::Error {
                 organization_id: None,
                 last_updated_at: "2026-01-01T00:00:00Z".to_string(),
                 error_message: None,
             },
+            UsageStatus::Offline => ClaudeUsageSnapshot::Offline {
+                organization_id: None,
+                last_updated_at: "2026-01-01T00:00:00Z".to_string(),
+                error_message: None,
+            },
         });
 
         let codex = codex.map(|s| match s {
             UsageStatus::Ok => CodexUsageSnapshot::Ok {
                 session_percent: 10.0,
                 session_resets_at: None,
-                weekly_percent: 10.0,
+                weekly_percent: Some(10.0),
                 weekly_resets_at: None,
                 last_updated_at: "2026-01-01T00:00:00Z".to_string(),
+                provider_timestamp: None,
             },
             UsageStatus::Unauthorized => CodexUsageSnapshot::Unauthorized {
                 last_updated_at: "2026-01-01T00:00:00Z".to_string(),
@@ -136,14 +301,24 @@ mod tests {
             UsageStatus::RateLimited => CodexUsageSnapshot::RateLimited {
                 last_updated_at: "2026-01-01T00:00:00Z".to_string(),
                 error_message: None,
+                retry_after_seconds: None,
             },
             UsageStatus::Error => CodexUsageSnapshot::Error {
                 last_updated_at: "2026-01-01T00:00:00Z".to_string(),
                 error_message: None,
             },
+            UsageStatus::Offline => CodexUsageSnapshot::Offline {
+                last_updated_at: "2026-01-01T00:00:00Z".to_string(),
+                error_message: None,
+            },
         });
 
-        UsageSnapshotBundle { claude, codex }
+        UsageSnapshotBundle {
+            claude,
+            codex,
+            offline: false,
+            claude_orgs: vec![],
+        }
     }
 
     #[test]
@@ -162,19 +337,98 @@ mod tests {
     }
 
     #[test]
-    fn compute_next_delay_ms_with_nanos_is_bounded() {
+    fn compute_next_delay_ms_with_fraction_is_bounded() {
         let base_ms = 60_000_u64;
 
-        let slow = compute_next_delay_ms_with_nanos(base_ms, 0.1, 0);
-        assert!(slow <= base_ms);
+        let slow = compute_next_delay_ms_with_fraction(base_ms, 0.1, 0.0);
+        assert_eq!(slow, 54_000);
 
-        let fast = compute_next_delay_ms_with_nanos(base_ms, 0.1, 999);
-        assert!(fast >= base_ms);
+        let fast = compute_next_delay_ms_with_fraction(base_ms, 0.1, 1.0);
+        assert_eq!(fast, 66_000);
 
-        let min = compute_next_delay_ms_with_nanos(500, 0.1, 0);
+        let min = compute_next_delay_ms_with_fraction(500, 0.1, 0.0);
         assert!(min >= 1000);
     }
 
+    #[test]
+    fn jitter_fraction_covers_the_full_range() {
+        let samples: Vec<f64> = (0..10_000).map(|_| next_jitter_fraction()).collect();
+        assert!(samples.iter().all(|f| (0.0..1.0).contains(f)));
+
+        let min = samples.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = samples.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        assert!(min < 0.01 && max > 0.99, "min {min}, max {max}");
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        assert!((mean - 0.5).abs() < 0.02, "mean {mean}");
+
+        // Each tenth of the range gets close to its share.
+        let mut buckets = [0usize; 10];
+        for f in &samples {
+            buckets[(f * 10.0) as usize] += 1;
+        }
+        assert!(
+            buckets.iter().all(|&n| (800..=1200).contains(&n)),
+            "{buckets:?}"
+        );
+    }
+
+    #[test]
+    fn poll_timing_drives_jitter_and_backoff() {
+        let timing = PollTiming::from_settings(120, 0.0, 600, false);
+        let ok = bundle_with_status(Some(UsageStatus::Ok), None);
+        let limited = bundle_with_status(Some(UsageStatus::RateLimited), None);
+        // No jitter: the delay is exactly the interval.
+        assert_eq!(
+            compute_next_delay_ms(timing, false, PowerSource::Unknown, &ok),
+            120_000
+        );
+        let backoff = compute_next_delay_ms(timing, false, PowerSource::Unknown, &limited);
+        assert!((480_000..=720_000).contains(&backoff));
+
+        assert_eq!(
+            compute_next_delay_ms_with_fraction(60_000, 0.5, 0.0),
+            30_000
+        );
+        assert_eq!(
+            compute_next_delay_ms_with_fraction(60_000, 0.05, 0.0),
+            57_000
+        );
+
+        // Hand-edited values are clamped into range.
+        let clamped = PollTiming::from_settings(60, 2.0, 1, false);
+        assert_eq!(clamped.jitter_ratio, 0.5);
+        assert_eq!(clamped.rate_limit_backoff_ms, 30_000);
+        assert_eq!(
+            PollTiming::from_settings(60, 0.1, 300, false),
+            PollTiming::default()
+        );
+    }
+
+    #[test]
+    fn retry_after_replaces_the_fixed_backoff() {
+        assert_eq!(retry_after_delay_ms(120, 0.0), 120_000);
+        assert_eq!(retry_after_delay_ms(120, 1.0), 144_000);
+        // Out-of-range requests are held to the backoff range.
+        assert_eq!(retry_after_delay_ms(1, 0.0), 30_000);
+        assert_eq!(retry_after_delay_ms(86_400, 0.0), 3_600_000);
+        assert_eq!(rate_limit_wait_ms(Some(120), 300_000), 120_000);
+        assert_eq!(rate_limit_wait_ms(None, 300_000), 300_000);
+
+        let mut snapshot = bundle_with_status(Some(UsageStatus::Ok), None);
+        snapshot.codex = Some(CodexUsageSnapshot::RateLimited {
+            last_updated_at: "2026-01-01T00:00:00Z".to_string(),
+            error_message: None,
+            retry_after_seconds: Some(90),
+        });
+        let delay = compute_next_delay_ms(
+            PollTiming::default(),
+            false,
+            PowerSource::Unknown,
+            &snapshot,
+        );
+        assert!((90_000..=108_000).contains(&delay), "{delay}");
+    }
+
     #[test]
     fn compute_next_delay_for_latest_returns_none_when_paused() {
         let snapshot = bundle_with_status(
@@ -182,8 +436,57 @@ mod tests {
             Some(UsageStatus::Unauthorized),
         );
         assert_eq!(
-            compute_next_delay_for_latest(true, true, 60, Some(&snapshot)),
+            compute_next_delay_for_latest(
+                false,
+                true,
+                true,
+                PollTiming::default(),
+                false,
+                PowerSource::Unknown,
+                Some(&snapshot)
+            ),
             None
         );
     }
+
+    #[test]
+    fn manual_pause_takes_precedence_over_polling() {
+        let snapshot = bundle_with_status(Some(UsageStatus::Ok), None);
+        let delay = |paused, snapshot| {
+            compute_next_delay_for_latest(
+                paused,
+                true,
+                false,
+                PollTiming::default(),
+                false,
+                PowerSource::Unknown,
+                snapshot,
+            )
+        };
+        assert!(delay(false, Some(&snapshot)).is_some());
+        assert_eq!(delay(true, Some(&snapshot)), None);
+        assert_eq!(delay(true, None), None);
+    }
+
+    #[test]
+    fn refresh_interval_floor_is_lower_in_debug_mode() {
+        let interval = |seconds, debug| {
+            PollTiming::from_settings(seconds, 0.1, 300, debug).refresh_interval_seconds
+        };
+        assert_eq!(interval(10, false), 30);
+        assert_eq!(interval(10, true), 10);
+        assert_eq!(interval(1, true), 5);
+        assert_eq!(interval(90, false), 90);
+    }
+
+    #[test]
+    fn battery_saver_multiplies_base_delay_only_on_battery() {
+        assert_eq!(configured_base_ms(60, false, PowerSource::Battery), 60_000);
+        assert_eq!(configured_base_ms(60, true, PowerSource::Ac), 60_000);
+        assert_eq!(configured_base_ms(60, true, PowerSource::Unknown), 60_000);
+        assert_eq!(
+            configured_base_ms(60, true, PowerSource::Battery),
+            60_000 * BATTERY_SAVER_MULTIPLIER
+        );
+    }
 }
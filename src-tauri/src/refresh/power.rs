@@ -0,0 +1,85 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PowerSource {
+    Ac,
+    Battery,
+    Unknown,
+}
+
+#[cfg(any(target_os = "macos", test))]
+fn parse_pmset_batt(output: &str) -> PowerSource {
+    // First line looks like: "Now drawing from 'Battery Power'" / "Now drawing from 'AC Power'".
+    let first = output.lines().next().unwrap_or_default();
+    if first.contains("'Battery Power'") {
+        PowerSource::Battery
+    } else if first.contains("'AC Power'") {
+        PowerSource::Ac
+    } else {
+        PowerSource::Unknown
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) async fn current_power_source() -> PowerSource {
+    let Ok(output) = tokio::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .await
+    else {
+        return PowerSource::Unknown;
+    };
+    if !output.status.success() {
+        return PowerSource::Unknown;
+    }
+    parse_pmset_batt(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) async fn current_power_source() -> PowerSource {
+    let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") else {
+        return PowerSource::Unknown;
+    };
+
+    let read = |path: &std::path::Path, name: &str| {
+        std::fs::read_to_string(path.join(name))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    let mut has_battery = false;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match read(&path, "type").as_str() {
+            "Mains" | "USB" if read(&path, "online") == "1" => return PowerSource::Ac,
+            // Peripheral batteries (mice, keyboards, headsets) report `scope` = `Device`.
+            "Battery" if read(&path, "scope") != "Device" => has_battery = true,
+            _ => {}
+        }
+    }
+
+    if has_battery {
+        PowerSource::Battery
+    } else {
+        PowerSource::Unknown
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub(crate) async fn current_power_source() -> PowerSource {
+    PowerSource::Unknown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_pmset_batt_reads_power_source() {
+        let battery = "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=1234)\t81%; discharging; 5:12 remaining present: true\n";
+        assert_eq!(parse_pmset_batt(battery), PowerSource::Battery);
+        assert_eq!(
+            parse_pmset_batt("Now drawing from 'AC Power'\n"),
+            PowerSource::Ac
+        );
+        assert_eq!(parse_pmset_batt(""), PowerSource::Unknown);
+    }
+}
@@ -1,10 +1,87 @@
-use super::fetch::{bundle, fetch_claude_snapshot, fetch_codex_snapshot};
+use super::fetch::{
+    claude_timed_out_snapshot, codex_cli_options, codex_timed_out_snapshot, fetch_claude_snapshot,
+    fetch_codex_snapshot, fetch_other_claude_orgs, FetchSnapshot,
+};
+use super::merge::{
+    keyring_unavailable, merge_provider_update, refresh_error_event, ProviderUpdate,
+};
 use super::policy::compute_next_delay_for_latest;
-use crate::notifications::maybe_notify_usage_bundle;
-use crate::state::{AppState, RefreshRequest};
-use crate::types::{IpcErrorCode, IpcResult};
+use super::power::{current_power_source, PowerSource};
+use crate::notifications::{maybe_notify_usage_bundle, notify_plain};
+use crate::state::{emit_refresh_error, AppState, RefreshRequest};
+use crate::types::{ClaudeUsageSnapshot, IpcErrorCode, IpcResult};
+use std::future::Future;
+use std::time::Duration;
 use tauri::{AppHandle, Runtime};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Mutex};
+
+/// Floor for one provider's fetch budget (see `provider_fetch_timeout`).
+const MIN_PROVIDER_FETCH_TIMEOUT: Duration = Duration::from_secs(45);
+/// Room on top of the request timeouts for retry backoff and the Codex CLI probe.
+const PROVIDER_FETCH_SLACK: Duration = Duration::from_secs(5);
+
+/// Upper bound for one provider's fetch so a hung request/CLI child can't stall the cycle. Derived
+/// from the `httpTimeoutSeconds` request timeout so a long setting isn't cut short: a fetch may
+/// make two requests back to back (organizations then usage, or a primary then a fallback URL).
+fn provider_fetch_timeout(request_timeout: Duration) -> Duration {
+    (request_timeout * 2 + PROVIDER_FETCH_SLACK).max(MIN_PROVIDER_FETCH_TIMEOUT)
+}
+
+/// `provider_fetch_timeout` plus the Codex CLI's two RPCs (initialize, then rate limits) at the
+/// `codexCliTimeoutSeconds` limit each: Auto can try OAuth and the web cookie before the CLI.
+fn codex_fetch_timeout(request_timeout: Duration, cli_rpc_timeout: Duration) -> Duration {
+    provider_fetch_timeout(request_timeout) + cli_rpc_timeout * 2
+}
+
+async fn fetch_with_timeout<T>(
+    limit: Duration,
+    fetch: impl Future<Output = FetchSnapshot<T>>,
+    timed_out: impl FnOnce() -> T,
+) -> FetchSnapshot<T> {
+    tokio::time::timeout(limit, fetch)
+        .await
+        .unwrap_or_else(|_| FetchSnapshot {
+            snapshot: timed_out(),
+            keyring_error: false,
+            org_notice: None,
+        })
+}
+
+/// Merge one provider's result into the latest bundle and publish it (peaks, notifications,
+/// tray/UI). `publish_lock` serializes providers so neither overwrites the other's update.
+async fn publish_provider_update<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &AppState<R>,
+    publish_lock: &Mutex<()>,
+    update: ProviderUpdate,
+) {
+    let _guard = publish_lock.lock().await;
+    let previous = state.latest_snapshot.lock().await.clone();
+    let (notify_claude, notify_codex) = update.notify_flags();
+    let (claude_ok, codex_ok) = update.success_flags();
+    state.record_last_success(claude_ok, codex_ok).await;
+    let provider_key = update.provider_key();
+    let snapshot = merge_provider_update(previous.as_ref(), update);
+    if let Some(event) = refresh_error_event(provider_key, previous.as_ref(), &snapshot) {
+        emit_refresh_error(app, &event);
+    }
+
+    state.record_daily_peaks(&snapshot);
+    state.record_usage_history(&snapshot);
+    if claude_ok || codex_ok {
+        state.persist_snapshot(&snapshot);
+    }
+    maybe_notify_usage_bundle(
+        app,
+        state,
+        previous.as_ref(),
+        &snapshot,
+        notify_claude,
+        notify_codex,
+    )
+    .await;
+    state.update_snapshot(app, Some(snapshot)).await;
+}
 
 async fn refresh_once<R: Runtime>(app: &AppHandle<R>, state: &AppState<R>) -> IpcResult<()> {
     let previous = state.latest_snapshot.lock().await.clone();
@@ -31,53 +108,141 @@ async fn refresh_once<R: Runtime>(app: &AppHandle<R>, state: &AppState<R>) -> Ip
         return IpcResult::ok(());
     }
 
-    let mut keyring_errors = 0_u8;
+    // Each provider fetches independently and publishes as soon as it finishes, so a slow one
+    // never holds back the other's update.
+    let publish_lock = Mutex::new(());
 
-    let claude = if notify_claude {
-        let result = fetch_claude_snapshot(state).await;
-        if result.keyring_error {
-            keyring_errors += 1;
+    let claude_task = async {
+        if !notify_claude {
+            publish_provider_update(app, state, &publish_lock, ProviderUpdate::Claude(None)).await;
+            return false;
         }
-        Some(result.snapshot)
-    } else {
-        None
-    };
+        let result = fetch_with_timeout(
+            provider_fetch_timeout(state.http_timeouts().request),
+            fetch_claude_snapshot(state),
+            claude_timed_out_snapshot,
+        )
+        .await;
+        let selected_ok = matches!(result.snapshot, ClaudeUsageSnapshot::Ok { .. });
+        match result.org_notice {
+            Some(notice) => {
+                if state.set_claude_org_notice(notice.clone()).await {
+                    notify_plain(app, &notice).await;
+                }
+            }
+            // Only a successful fetch proves the selected organization resolved again; a transient
+            // failure keeps the notice so it is not delivered twice.
+            None if selected_ok => {
+                state.clear_claude_org_notice().await;
+            }
+            None => {}
+        }
+        publish_provider_update(
+            app,
+            state,
+            &publish_lock,
+            ProviderUpdate::Claude(Some(result.snapshot)),
+        )
+        .await;
 
-    let codex = if notify_codex {
-        let result = fetch_codex_snapshot(state).await;
-        if result.keyring_error {
-            keyring_errors += 1;
+        // The selected organization is already shown; the others follow when they finish. They are
+        // skipped when the selected one failed (an auth or rate-limit failure would hit them too),
+        // and a timeout keeps their last known values instead of clearing them.
+        if selected_ok {
+            if let Ok(other_orgs) = tokio::time::timeout(
+                provider_fetch_timeout(state.http_timeouts().request),
+                fetch_other_claude_orgs(state),
+            )
+            .await
+            {
+                let had_other_orgs = state
+                    .latest_snapshot
+                    .lock()
+                    .await
+                    .as_ref()
+                    .is_some_and(|s| !s.claude_orgs.is_empty());
+                if !other_orgs.is_empty() || had_other_orgs {
+                    publish_provider_update(
+                        app,
+                        state,
+                        &publish_lock,
+                        ProviderUpdate::ClaudeOrgs(other_orgs),
+                    )
+                    .await;
+                }
+            }
         }
-        Some(result.snapshot)
-    } else {
-        None
+        result.keyring_error
     };
 
-    let snapshot = bundle(claude, codex);
-    maybe_notify_usage_bundle(
-        app,
-        state,
-        previous.as_ref(),
-        &snapshot,
-        notify_claude,
-        notify_codex,
-    )
-    .await;
-    state.update_snapshot(app, Some(snapshot)).await;
-
-    if keyring_errors > 0 {
-        let enabled_providers = notify_claude as u8 + notify_codex as u8;
-        if enabled_providers > 0 && keyring_errors >= enabled_providers {
-            return IpcResult::err(
-                IpcErrorCode::Keyring,
-                "OS keychain/secret service is unavailable.",
-            );
+    let codex_task = async {
+        if !notify_codex {
+            publish_provider_update(app, state, &publish_lock, ProviderUpdate::Codex(None)).await;
+            return false;
         }
+        let result = fetch_with_timeout(
+            codex_fetch_timeout(
+                state.http_timeouts().request,
+                codex_cli_options(state).rpc_timeout,
+            ),
+            fetch_codex_snapshot(state),
+            codex_timed_out_snapshot,
+        )
+        .await;
+        publish_provider_update(
+            app,
+            state,
+            &publish_lock,
+            ProviderUpdate::Codex(Some(result.snapshot)),
+        )
+        .await;
+        result.keyring_error
+    };
+
+    let (claude_keyring_error, codex_keyring_error) = tokio::join!(claude_task, codex_task);
+    if keyring_unavailable(
+        (notify_claude, claude_keyring_error),
+        (notify_codex, codex_keyring_error),
+    ) {
+        return IpcResult::err(
+            IpcErrorCode::Keyring,
+            "OS keychain/secret service is unavailable.",
+        );
     }
 
     IpcResult::ok(())
 }
 
+async fn next_delay_for_latest<R: Runtime>(state: &AppState<R>) -> Option<u64> {
+    let battery_saver = state.battery_saver_enabled();
+    let power = if battery_saver {
+        current_power_source().await
+    } else {
+        PowerSource::Unknown
+    };
+    let latest = state.latest_snapshot.lock().await.clone();
+    compute_next_delay_for_latest(
+        state.polling_paused().await,
+        state.track_claude_enabled(),
+        state.track_codex_enabled(),
+        state.poll_timing(),
+        battery_saver,
+        power,
+        latest.as_ref(),
+    )
+}
+
+/// `refresh_once`, or `None` if shutdown was requested first (the fetch is dropped mid-flight).
+async fn refresh_unless_shutdown<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &AppState<R>,
+) -> Option<IpcResult<()>> {
+    tokio::select! {
+        result = refresh_once(app, state) => Some(result),
+        _ = state.refresh.shutdown_requested() => None,
+    }
+}
+
 pub fn spawn_refresh_loop<R: Runtime>(
     app: AppHandle<R>,
     state: AppState<R>,
@@ -92,47 +257,76 @@ pub fn spawn_refresh_loop<R: Runtime>(
                   req = rx.recv() => {
                     if req.is_none() { break; }
                     let req = req.unwrap();
-                    let result = refresh_once(&app, &state).await;
-                    let latest = state.latest_snapshot.lock().await.clone();
-                    next_delay_ms = compute_next_delay_for_latest(
-                        state.track_claude_enabled(),
-                        state.track_codex_enabled(),
-                        state.refresh_interval_seconds(),
-                        latest.as_ref(),
-                    );
+                    let Some(result) = refresh_unless_shutdown(&app, &state).await else { break; };
+                    next_delay_ms = next_delay_for_latest(&state).await;
                     if let Some(tx) = req.respond_to {
                       let _ = tx.send(result);
                     }
                   }
                   _ = tokio::time::sleep(std::time::Duration::from_millis(delay_ms)) => {
-                    let _ = refresh_once(&app, &state).await;
-                    let latest = state.latest_snapshot.lock().await.clone();
-                    next_delay_ms = compute_next_delay_for_latest(
-                        state.track_claude_enabled(),
-                        state.track_codex_enabled(),
-                        state.refresh_interval_seconds(),
-                        latest.as_ref(),
-                    );
+                    // Paused from the tray while this timer was pending.
+                    if !state.polling_paused().await
+                      && refresh_unless_shutdown(&app, &state).await.is_none()
+                    {
+                      break;
+                    }
+                    next_delay_ms = next_delay_for_latest(&state).await;
                   }
+                  _ = state.refresh.shutdown_requested() => break,
                 }
             } else {
-                let req = rx.recv().await;
-                if req.is_none() {
+                let req = tokio::select! {
+                    req = rx.recv() => req,
+                    _ = state.refresh.shutdown_requested() => None,
+                };
+                let Some(req) = req else {
                     break;
-                }
-                let req = req.unwrap();
-                let result = refresh_once(&app, &state).await;
-                let latest = state.latest_snapshot.lock().await.clone();
-                next_delay_ms = compute_next_delay_for_latest(
-                    state.track_claude_enabled(),
-                    state.track_codex_enabled(),
-                    state.refresh_interval_seconds(),
-                    latest.as_ref(),
-                );
+                };
+                let Some(result) = refresh_unless_shutdown(&app, &state).await else {
+                    break;
+                };
+                next_delay_ms = next_delay_for_latest(&state).await;
                 if let Some(tx) = req.respond_to {
                     let _ = tx.send(result);
                 }
             }
         }
+
+        // Don't leave the Codex app-server running after the app is gone.
+        state.codex.shutdown().await;
+        drop(rx);
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provider_fetch_timeout_leaves_room_for_two_requests() {
+        assert_eq!(
+            provider_fetch_timeout(Duration::from_secs(5)),
+            MIN_PROVIDER_FETCH_TIMEOUT
+        );
+        assert_eq!(
+            provider_fetch_timeout(Duration::from_secs(40)),
+            Duration::from_secs(85)
+        );
+        assert_eq!(
+            provider_fetch_timeout(Duration::from_secs(120)),
+            Duration::from_secs(245)
+        );
+    }
+
+    #[test]
+    fn codex_fetch_timeout_leaves_room_for_the_cli_rpcs() {
+        assert_eq!(
+            codex_fetch_timeout(Duration::from_secs(40), Duration::from_secs(12)),
+            Duration::from_secs(109)
+        );
+        assert_eq!(
+            codex_fetch_timeout(Duration::from_secs(40), Duration::from_secs(120)),
+            Duration::from_secs(325)
+        );
+    }
+}
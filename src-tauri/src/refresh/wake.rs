@@ -0,0 +1,129 @@
+use crate::state::AppState;
+use std::time::Duration;
+use tauri::Runtime;
+use tokio::sync::mpsc;
+
+/// Wake events within this window collapse into one refresh. It also gives the network a moment
+/// to come back after resume, so the refresh doesn't land on a dead connection.
+const WAKE_DEBOUNCE: Duration = Duration::from_secs(3);
+
+/// How often Linux checks the wall clock and network interfaces.
+#[cfg(any(target_os = "linux", test))]
+const LINUX_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Wall-clock time beyond the poll interval that counts as having been suspended.
+#[cfg(any(target_os = "linux", test))]
+const SUSPEND_SLACK: Duration = Duration::from_secs(30);
+
+/// Refresh right away when the machine wakes from sleep or (on Linux) the network comes back,
+/// instead of waiting for the next scheduled tick. The timed loop's cadence is unchanged, and
+/// nothing is fetched while polling is paused from the tray.
+pub fn spawn_wake_refresh<R: Runtime>(state: AppState<R>) {
+    let (tx, mut rx) = mpsc::unbounded_channel::<()>();
+    watch_platform(tx);
+    tauri::async_runtime::spawn(async move {
+        while rx.recv().await.is_some() {
+            tokio::time::sleep(WAKE_DEBOUNCE).await;
+            while rx.try_recv().is_ok() {}
+            if state.polling_paused().await {
+                continue;
+            }
+            let _ = state.refresh.refresh_now().await;
+        }
+    });
+}
+
+/// `tokio::time::sleep` runs on the monotonic clock, which stops while suspended; the wall clock
+/// keeps going, so a much longer wall-clock gap than the sleep means we were asleep.
+#[cfg(any(target_os = "linux", test))]
+fn resumed_from_suspend(wall_elapsed: Duration, slept: Duration) -> bool {
+    wall_elapsed > slept + SUSPEND_SLACK
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn interface_is_online(name: &str, operstate: &str) -> bool {
+    name != "lo" && operstate.trim() == "up"
+}
+
+#[cfg(target_os = "linux")]
+fn network_online() -> bool {
+    let Ok(entries) = std::fs::read_dir("/sys/class/net") else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        let operstate = std::fs::read_to_string(entry.path().join("operstate")).unwrap_or_default();
+        interface_is_online(&entry.file_name().to_string_lossy(), &operstate)
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn watch_platform(tx: mpsc::UnboundedSender<()>) {
+    use block2::RcBlock;
+    use objc2_app_kit::{NSWorkspace, NSWorkspaceDidWakeNotification};
+    use objc2_foundation::NSNotification;
+    use std::ptr::NonNull;
+
+    let block = RcBlock::new(move |_: NonNull<NSNotification>| {
+        let _ = tx.send(());
+    });
+    let center = NSWorkspace::sharedWorkspace().notificationCenter();
+    // Safety: the notification name is a framework constant, and the block only sends on a
+    // channel, so it's fine to run on whichever thread posts the notification.
+    let observer = unsafe {
+        center.addObserverForName_object_queue_usingBlock(
+            Some(NSWorkspaceDidWakeNotification),
+            None,
+            None,
+            &block,
+        )
+    };
+    // Observe for the life of the app.
+    std::mem::forget(observer);
+}
+
+#[cfg(target_os = "linux")]
+fn watch_platform(tx: mpsc::UnboundedSender<()>) {
+    use std::time::SystemTime;
+
+    tauri::async_runtime::spawn(async move {
+        let mut online = network_online();
+        let mut last_check = SystemTime::now();
+        loop {
+            tokio::time::sleep(LINUX_POLL_INTERVAL).await;
+            let now = SystemTime::now();
+            let wall_elapsed = now.duration_since(last_check).unwrap_or_default();
+            last_check = now;
+            let was_online = std::mem::replace(&mut online, network_online());
+            let reconnected = online && !was_online;
+            if (resumed_from_suspend(wall_elapsed, LINUX_POLL_INTERVAL) || reconnected)
+                && tx.send(()).is_err()
+            {
+                break;
+            }
+        }
+    });
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn watch_platform(_tx: mpsc::UnboundedSender<()>) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_suspend_and_online_interfaces() {
+        assert!(!resumed_from_suspend(
+            Duration::from_secs(6),
+            LINUX_POLL_INTERVAL
+        ));
+        assert!(resumed_from_suspend(
+            Duration::from_secs(600),
+            LINUX_POLL_INTERVAL
+        ));
+
+        assert!(interface_is_online("wlan0", "up\n"));
+        assert!(!interface_is_online("wlan0", "down\n"));
+        assert!(!interface_is_online("lo", "up\n"));
+    }
+}
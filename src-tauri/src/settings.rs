@@ -1,24 +1,135 @@
+use crate::types::{LevelSymbols, TrayColors};
 use serde_json::json;
 use std::{collections::HashMap, sync::Arc, time::Duration};
 use tauri::Runtime;
 use tauri_plugin_store::{JsonValue, Store, StoreBuilder};
 
 const SETTINGS_STORE_FILE: &str = "claudometer-settings.json";
+/// Retained usage data (history, the startup cache), kept apart so per-refresh writes never
+/// rewrite the settings.
+const USAGE_DATA_STORE_FILE: &str = "claudometer-usage-data.json";
+/// Usage data changes on every refresh; coalesce those writes instead of saving each one.
+const USAGE_DATA_SAVE_DEBOUNCE: Duration = Duration::from_secs(5);
 
 pub const KEY_REFRESH_INTERVAL_SECONDS: &str = "refreshIntervalSeconds";
 pub const KEY_SELECTED_ORGANIZATION_ID: &str = "selectedOrganizationId";
 pub const KEY_REMEMBER_SESSION_KEY: &str = "rememberSessionKey";
 pub const KEY_NOTIFY_ON_USAGE_RESET: &str = "notifyOnUsageReset";
+pub const KEY_NOTIFY_ON_RECOVERY: &str = "notifyOnRecovery";
+pub const KEY_NOTIFY_ON_RATE_LIMIT: &str = "notifyOnRateLimit";
+pub const KEY_NOTIFY_SHOW_DELTA: &str = "notifyShowDelta";
+pub const KEY_RESPECT_DO_NOT_DISTURB: &str = "respectDoNotDisturb";
+pub const KEY_TRAY_COLORS: &str = "trayColors";
+pub const KEY_COLORBLIND_MODE: &str = "colorblindMode";
+pub const KEY_CLAUDE_WEEKLY_AGGREGATE: &str = "claudeWeeklyAggregate";
+pub const KEY_LEVEL_SYMBOLS: &str = "levelSymbols";
+pub const KEY_BURN_RATE_ALERT_ENABLED: &str = "burnRateAlertEnabled";
+pub const KEY_CLAUDE_NEAR_LIMIT_PERCENT: &str = "claudeNearLimitPercent";
+pub const KEY_CODEX_NEAR_LIMIT_PERCENT: &str = "codexNearLimitPercent";
+pub const KEY_BURN_RATE_HORIZON_MINUTES: &str = "burnRateHorizonMinutes";
+/// Notify when the current pace would hit 100% before the window resets.
+pub const KEY_EXHAUSTION_ALERT_ENABLED: &str = "exhaustionAlertEnabled";
 pub const KEY_USAGE_SOURCE: &str = "usageSource";
 pub const KEY_CODEX_USAGE_SOURCE: &str = "codexUsageSource";
+pub const KEY_REMEMBER_CODEX_COOKIE: &str = "rememberCodexCookie";
+pub const KEY_CODEX_CLI_KEEP_ALIVE: &str = "codexCliKeepAlive";
+/// Path to the `codex` binary for the CLI source; empty means `codex` from PATH.
+pub const KEY_CODEX_CLI_PATH: &str = "codexCliPath";
+pub const KEY_CODEX_CLI_TIMEOUT_SECONDS: &str = "codexCliTimeoutSeconds";
+/// Proxy URL for both API clients; overrides `HTTPS_PROXY` and friends. Read at startup.
+pub const KEY_HTTP_PROXY: &str = "httpProxy";
+/// Claude API base URL for gateways; empty means `https://claude.ai/api`. Read at startup.
+pub const KEY_CLAUDE_BASE_URL: &str = "claudeBaseUrl";
+/// Serve `/metrics` on `127.0.0.1:metricsPort` for Prometheus scrapers. Read at startup.
+pub const KEY_METRICS_ENABLED: &str = "metricsEnabled";
+pub const KEY_METRICS_PORT: &str = "metricsPort";
 pub const KEY_TRACK_CLAUDE_ENABLED: &str = "trackClaudeEnabled";
 pub const KEY_TRACK_CODEX_ENABLED: &str = "trackCodexEnabled";
 pub const KEY_AUTOSTART_ENABLED: &str = "autostartEnabled";
 pub const KEY_CHECK_UPDATES_ON_STARTUP: &str = "checkUpdatesOnStartup";
+pub const KEY_HIDE_ZERO_MODELS: &str = "hideZeroModels";
+pub const KEY_SHOW_WEEKLY_IN_MENU: &str = "showWeeklyInMenu";
+pub const KEY_SHOW_MODELS_IN_MENU: &str = "showModelsInMenu";
+pub const KEY_MAX_MODELS_IN_MENU: &str = "maxModelsInMenu";
+pub const KEY_BATTERY_SAVER: &str = "batterySaver";
+pub const KEY_REFRESH_JITTER_RATIO: &str = "refreshJitterRatio";
+pub const KEY_RATE_LIMIT_BACKOFF_SECONDS: &str = "rateLimitBackoffSeconds";
+/// Total time allowed per API request (the connect timeout stays at most 10s).
+pub const KEY_HTTP_TIMEOUT_SECONDS: &str = "httpTimeoutSeconds";
+/// Set from the tray; stops timed refreshes (manual refreshes still run) until resumed.
+pub const KEY_POLLING_PAUSED: &str = "pollingPaused";
+pub const KEY_DAILY_PEAKS: &str = "dailyPeaks";
+pub const KEY_CACHE_LAST_SNAPSHOT: &str = "cacheLastSnapshot";
+pub const KEY_USAGE_HISTORY: &str = "usageHistory";
+pub const KEY_USAGE_HISTORY_RETENTION_HOURS: &str = "usageHistoryRetentionHours";
+/// Last successfully fetched bundle, shown on startup until the first refresh completes.
+pub const KEY_CACHED_SNAPSHOT: &str = "cachedSnapshot";
+pub const KEY_MISSING_ORG_POLICY: &str = "missingOrgPolicy";
+/// Also fetch every other Claude organization (web source only), not just the selected one.
+pub const KEY_TRACK_ALL_ORGS: &str = "trackAllOrgs";
+pub const KEY_PROVIDER_ORDER: &str = "providerOrder";
+/// Refresh as soon as the machine wakes from sleep or the network reconnects.
+pub const KEY_REFRESH_ON_WAKE: &str = "refreshOnWake";
+pub const KEY_RATE_LIMIT_COLOR: &str = "rateLimitColor";
+pub const KEY_SHOW_WINDOW_PACING: &str = "showWindowPacing";
+pub const KEY_LAST_UPDATED_DISPLAY: &str = "lastUpdatedDisplay";
+pub const KEY_TRAY_METRIC: &str = "trayMetric";
+pub const KEY_TRAY_LEVEL_METRIC: &str = "trayLevelMetric";
+pub const KEY_SHOW_RESET_COUNTDOWN: &str = "showResetCountdown";
+pub const KEY_TRAY_CLICK_ACTION: &str = "trayClickAction";
+pub const KEY_ASCII_TRAY_TITLE: &str = "asciiTrayTitle";
+pub const KEY_TRAY_TITLE_TEMPLATE: &str = "trayTitleTemplate";
+/// Provider labels in the tray title (and the `{claude_label}`/`{codex_label}` template tokens).
+pub const KEY_CLAUDE_LABEL: &str = "claudeLabel";
+pub const KEY_CODEX_LABEL: &str = "codexLabel";
+/// Usage dashboards opened from the tray (e.g. an enterprise console); blank for the defaults.
+pub const KEY_CLAUDE_USAGE_PAGE_URL: &str = "claudeUsagePageUrl";
+pub const KEY_CODEX_USAGE_PAGE_URL: &str = "codexUsagePageUrl";
+pub const KEY_TRAY_ICON_BADGE: &str = "trayIconBadge";
+pub const KEY_TRAY_WARN_PERCENT: &str = "trayWarnPercent";
+pub const KEY_TRAY_CRITICAL_PERCENT: &str = "trayCriticalPercent";
 pub const KEY_SESSION_NEAR_LIMIT_NOTIFIED: &str = "sessionNearLimitNotifiedPeriodIdByOrg";
 pub const KEY_WEEKLY_NEAR_LIMIT_NOTIFIED: &str = "weeklyNearLimitNotifiedPeriodIdByOrg";
 pub const KEY_SESSION_RESET_NOTIFIED: &str = "sessionResetNotifiedPeriodIdByOrg";
 pub const KEY_WEEKLY_RESET_NOTIFIED: &str = "weeklyResetNotifiedPeriodIdByOrg";
+pub const KEY_SESSION_BURN_RATE_NOTIFIED: &str = "sessionBurnRateNotifiedPeriodIdByScope";
+pub const KEY_WEEKLY_BURN_RATE_NOTIFIED: &str = "weeklyBurnRateNotifiedPeriodIdByScope";
+pub const KEY_SESSION_EXHAUSTION_NOTIFIED: &str = "sessionExhaustionNotifiedPeriodIdByScope";
+pub const KEY_WEEKLY_EXHAUSTION_NOTIFIED: &str = "weeklyExhaustionNotifiedPeriodIdByScope";
+pub const KEY_ALERT_TIERS: &str = "alertTiers";
+pub const KEY_NOTIFICATION_SNOOZE_MINUTES: &str = "notificationSnoozeMinutes";
+pub const KEY_QUIET_HOURS_START: &str = "quietHoursStart";
+pub const KEY_QUIET_HOURS_END: &str = "quietHoursEnd";
+pub const KEY_SESSION_ALERT_TIER_NOTIFIED: &str = "sessionAlertTierNotifiedByScope";
+pub const KEY_WEEKLY_ALERT_TIER_NOTIFIED: &str = "weeklyAlertTierNotifiedByScope";
+
+/// Keys holding app state (caches, history, notification bookkeeping) rather than user choices,
+/// plus the proxy URL, which may embed credentials. Left out of settings export/import.
+const NON_PORTABLE_KEYS: &[&str] = &[
+    KEY_HTTP_PROXY,
+    KEY_POLLING_PAUSED,
+    KEY_DAILY_PEAKS,
+    KEY_USAGE_HISTORY,
+    KEY_CACHED_SNAPSHOT,
+    KEY_SESSION_NEAR_LIMIT_NOTIFIED,
+    KEY_WEEKLY_NEAR_LIMIT_NOTIFIED,
+    KEY_SESSION_RESET_NOTIFIED,
+    KEY_WEEKLY_RESET_NOTIFIED,
+    KEY_SESSION_BURN_RATE_NOTIFIED,
+    KEY_WEEKLY_BURN_RATE_NOTIFIED,
+    KEY_SESSION_EXHAUSTION_NOTIFIED,
+    KEY_WEEKLY_EXHAUSTION_NOTIFIED,
+    KEY_SESSION_ALERT_TIER_NOTIFIED,
+    KEY_WEEKLY_ALERT_TIER_NOTIFIED,
+];
+
+/// `CLAUDOMETER_DEBUG=1`: shows the debug tray menu and lowers the refresh interval floor.
+pub(crate) fn debug_mode_enabled() -> bool {
+    matches!(
+        std::env::var("CLAUDOMETER_DEBUG").as_deref(),
+        Ok("1") | Ok("true") | Ok("yes") | Ok("on")
+    )
+}
 
 fn defaults() -> HashMap<String, JsonValue> {
     HashMap::from([
@@ -28,16 +139,106 @@ fn defaults() -> HashMap<String, JsonValue> {
         (KEY_SELECTED_ORGANIZATION_ID.to_string(), json!("")),
         (KEY_REMEMBER_SESSION_KEY.to_string(), json!(false)),
         (KEY_CODEX_USAGE_SOURCE.to_string(), json!("oauth")),
+        (KEY_REMEMBER_CODEX_COOKIE.to_string(), json!(false)),
+        (KEY_CODEX_CLI_KEEP_ALIVE.to_string(), json!(false)),
+        (KEY_CODEX_CLI_PATH.to_string(), json!("")),
+        (KEY_CODEX_CLI_TIMEOUT_SECONDS.to_string(), json!(12)),
+        (KEY_METRICS_ENABLED.to_string(), json!(false)),
+        (KEY_METRICS_PORT.to_string(), json!(9464)),
+        (KEY_HTTP_PROXY.to_string(), json!("")),
+        (KEY_CLAUDE_BASE_URL.to_string(), json!("")),
         (KEY_NOTIFY_ON_USAGE_RESET.to_string(), json!(false)),
+        (KEY_NOTIFY_ON_RECOVERY.to_string(), json!(false)),
+        (KEY_NOTIFY_ON_RATE_LIMIT.to_string(), json!(false)),
+        (KEY_NOTIFY_SHOW_DELTA.to_string(), json!(false)),
+        (KEY_RESPECT_DO_NOT_DISTURB.to_string(), json!(false)),
+        (KEY_TRAY_COLORS.to_string(), json!(TrayColors::default())),
+        (KEY_COLORBLIND_MODE.to_string(), json!("off")),
+        (KEY_CLAUDE_WEEKLY_AGGREGATE.to_string(), json!("max")),
+        (
+            KEY_LEVEL_SYMBOLS.to_string(),
+            json!(LevelSymbols::default()),
+        ),
+        (KEY_BURN_RATE_ALERT_ENABLED.to_string(), json!(false)),
+        (KEY_CLAUDE_NEAR_LIMIT_PERCENT.to_string(), json!(90)),
+        (KEY_CODEX_NEAR_LIMIT_PERCENT.to_string(), json!(90)),
+        (KEY_BURN_RATE_HORIZON_MINUTES.to_string(), json!(120)),
+        (KEY_EXHAUSTION_ALERT_ENABLED.to_string(), json!(false)),
         (KEY_AUTOSTART_ENABLED.to_string(), json!(false)),
         (KEY_CHECK_UPDATES_ON_STARTUP.to_string(), json!(true)),
+        (KEY_HIDE_ZERO_MODELS.to_string(), json!(false)),
+        (KEY_SHOW_WEEKLY_IN_MENU.to_string(), json!(true)),
+        (KEY_SHOW_MODELS_IN_MENU.to_string(), json!(true)),
+        (KEY_MAX_MODELS_IN_MENU.to_string(), json!(5)),
+        (KEY_BATTERY_SAVER.to_string(), json!(false)),
+        (KEY_REFRESH_JITTER_RATIO.to_string(), json!(0.1)),
+        (KEY_RATE_LIMIT_BACKOFF_SECONDS.to_string(), json!(300)),
+        (KEY_HTTP_TIMEOUT_SECONDS.to_string(), json!(40)),
+        (KEY_POLLING_PAUSED.to_string(), json!(false)),
+        (KEY_DAILY_PEAKS.to_string(), json!({})),
+        (KEY_CACHE_LAST_SNAPSHOT.to_string(), json!(true)),
+        (KEY_USAGE_HISTORY_RETENTION_HOURS.to_string(), json!(24)),
+        (KEY_MISSING_ORG_POLICY.to_string(), json!("auto_switch")),
+        (KEY_TRACK_ALL_ORGS.to_string(), json!(false)),
+        (KEY_PROVIDER_ORDER.to_string(), json!("claude_first")),
+        (KEY_REFRESH_ON_WAKE.to_string(), json!(true)),
+        (KEY_RATE_LIMIT_COLOR.to_string(), json!("gray")),
+        (KEY_SHOW_WINDOW_PACING.to_string(), json!(false)),
+        (KEY_LAST_UPDATED_DISPLAY.to_string(), json!("checked_at")),
+        (KEY_TRAY_METRIC.to_string(), json!("session")),
+        (KEY_TRAY_LEVEL_METRIC.to_string(), json!("title")),
+        (KEY_SHOW_RESET_COUNTDOWN.to_string(), json!(true)),
+        (KEY_TRAY_CLICK_ACTION.to_string(), json!("menu")),
+        (KEY_ASCII_TRAY_TITLE.to_string(), json!(false)),
+        (KEY_TRAY_TITLE_TEMPLATE.to_string(), json!("")),
+        (KEY_CLAUDE_LABEL.to_string(), json!("CL")),
+        (KEY_CODEX_LABEL.to_string(), json!("CX")),
+        (KEY_CLAUDE_USAGE_PAGE_URL.to_string(), json!("")),
+        (KEY_CODEX_USAGE_PAGE_URL.to_string(), json!("")),
+        (KEY_TRAY_ICON_BADGE.to_string(), json!(false)),
+        (KEY_TRAY_WARN_PERCENT.to_string(), json!(50)),
+        (KEY_TRAY_CRITICAL_PERCENT.to_string(), json!(70)),
         (KEY_SESSION_NEAR_LIMIT_NOTIFIED.to_string(), json!({})),
         (KEY_WEEKLY_NEAR_LIMIT_NOTIFIED.to_string(), json!({})),
         (KEY_SESSION_RESET_NOTIFIED.to_string(), json!({})),
         (KEY_WEEKLY_RESET_NOTIFIED.to_string(), json!({})),
+        (KEY_SESSION_BURN_RATE_NOTIFIED.to_string(), json!({})),
+        (KEY_WEEKLY_BURN_RATE_NOTIFIED.to_string(), json!({})),
+        (KEY_SESSION_EXHAUSTION_NOTIFIED.to_string(), json!({})),
+        (KEY_WEEKLY_EXHAUSTION_NOTIFIED.to_string(), json!({})),
+        (KEY_ALERT_TIERS.to_string(), json!([])),
+        (KEY_NOTIFICATION_SNOOZE_MINUTES.to_string(), json!(60)),
+        (KEY_QUIET_HOURS_START.to_string(), json!("")),
+        (KEY_QUIET_HOURS_END.to_string(), json!("")),
+        (KEY_SESSION_ALERT_TIER_NOTIFIED.to_string(), json!({})),
+        (KEY_WEEKLY_ALERT_TIER_NOTIFIED.to_string(), json!({})),
     ])
 }
 
+/// Defaults for the keys that settings export writes and import accepts.
+pub(crate) fn portable_defaults() -> HashMap<String, JsonValue> {
+    let mut map = defaults();
+    map.retain(|key, _| !NON_PORTABLE_KEYS.contains(&key.as_str()));
+    map
+}
+
+/// Keys kept in the usage data store rather than the settings file.
+const USAGE_DATA_KEYS: &[&str] = &[KEY_USAGE_HISTORY, KEY_CACHED_SNAPSHOT];
+
+/// Move usage data that older versions kept in the settings file into the usage data store.
+/// Idempotent; run once at startup.
+pub fn migrate_usage_data<R: Runtime>(settings: &SettingsStore<R>, usage_data: &SettingsStore<R>) {
+    for &key in USAGE_DATA_KEYS {
+        let Some(value) = settings.get_json(key) else {
+            continue;
+        };
+        if usage_data.get_json(key).is_none() {
+            usage_data.set(key, value);
+        }
+        settings.remove(key);
+    }
+}
+
 pub struct SettingsStore<R: Runtime> {
     store: Arc<Store<R>>,
 }
@@ -52,7 +253,32 @@ impl<R: Runtime> Clone for SettingsStore<R> {
 
 impl<R: Runtime> SettingsStore<R> {
     pub fn new(app: &tauri::AppHandle<R>) -> tauri_plugin_store::Result<Self> {
-        let store = StoreBuilder::new(app, SETTINGS_STORE_FILE)
+        let path = crate::data_dir::store_path(
+            crate::data_dir::data_dir_override().as_deref(),
+            SETTINGS_STORE_FILE,
+        );
+        Self::open(app, path)
+    }
+
+    /// The usage data store (see `USAGE_DATA_STORE_FILE`). It has no defaults: absent keys mean
+    /// nothing has been recorded yet.
+    pub fn usage_data(app: &tauri::AppHandle<R>) -> tauri_plugin_store::Result<Self> {
+        let path = crate::data_dir::store_path(
+            crate::data_dir::data_dir_override().as_deref(),
+            USAGE_DATA_STORE_FILE,
+        );
+        let store = StoreBuilder::new(app, path)
+            .auto_save(USAGE_DATA_SAVE_DEBOUNCE)
+            .build()?;
+        Ok(Self { store })
+    }
+
+    /// Store backed by the file at `path` (resolved by the store plugin when relative).
+    pub(crate) fn open(
+        app: &tauri::AppHandle<R>,
+        path: std::path::PathBuf,
+    ) -> tauri_plugin_store::Result<Self> {
+        let store = StoreBuilder::new(app, path)
             .defaults(defaults())
             .auto_save(Duration::from_millis(200))
             .build()?;
@@ -91,7 +317,17 @@ impl<R: Runtime> SettingsStore<R> {
         self.store.set(key.to_string(), value.into());
     }
 
+    /// All stored key/value pairs (non-sensitive by design; secrets live in the OS keyring).
+    pub fn entries(&self) -> Vec<(String, JsonValue)> {
+        self.store.entries()
+    }
+
     pub fn remove(&self, key: &str) {
         let _ = self.store.delete(key);
     }
+
+    /// Drop every stored value, leaving only the defaults.
+    pub fn reset(&self) {
+        self.store.reset();
+    }
 }
@@ -0,0 +1,144 @@
+use crate::types::{UsageSnapshotBundle, UsageStatus};
+use chrono::DateTime;
+
+/// A provider is stale once this many refresh intervals pass without a successful fetch.
+const STALE_AFTER_INTERVALS: i64 = 3;
+
+/// Unix-ms timestamp of the last successful (Ok) fetch per provider.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LastSuccess {
+    pub claude: Option<i64>,
+    pub codex: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProviderStaleness {
+    pub claude: Option<StaleSince>,
+    pub codex: Option<StaleSince>,
+}
+
+/// Marker for a stale provider, carrying when it last succeeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaleSince {
+    pub last_success_ms: i64,
+}
+
+fn stale_since(
+    last_success_ms: Option<i64>,
+    now_ms: i64,
+    interval_secs: u64,
+) -> Option<StaleSince> {
+    // Never succeeded: the error state already explains it, so don't also flag staleness.
+    let last_success_ms = last_success_ms?;
+    let threshold_ms = STALE_AFTER_INTERVALS * interval_secs as i64 * 1000;
+    (now_ms - last_success_ms > threshold_ms).then_some(StaleSince { last_success_ms })
+}
+
+fn ok_timestamp_ms(status: UsageStatus, last_updated_at: &str) -> Option<i64> {
+    if status != UsageStatus::Ok {
+        return None;
+    }
+    DateTime::parse_from_rfc3339(last_updated_at)
+        .ok()
+        .map(|dt| dt.timestamp_millis())
+}
+
+impl LastSuccess {
+    /// Success times recorded in a cached bundle, so restored values get flagged stale once
+    /// they age past the threshold.
+    pub fn from_snapshot(snapshot: &UsageSnapshotBundle) -> Self {
+        Self {
+            claude: snapshot
+                .claude
+                .as_ref()
+                .and_then(|s| ok_timestamp_ms(s.status(), s.last_updated_at())),
+            codex: snapshot
+                .codex
+                .as_ref()
+                .and_then(|s| ok_timestamp_ms(s.status(), s.last_updated_at())),
+        }
+    }
+
+    pub fn staleness(&self, now_ms: i64, interval_secs: u64) -> ProviderStaleness {
+        ProviderStaleness {
+            claude: stale_since(self.claude, now_ms, interval_secs),
+            codex: stale_since(self.codex, now_ms, interval_secs),
+        }
+    }
+}
+
+pub fn now_unix_ms() -> i64 {
+    (time::OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE_MS: i64 = 60_000;
+
+    #[test]
+    fn only_the_stale_provider_is_flagged() {
+        let now = 100 * MINUTE_MS;
+        let last = LastSuccess {
+            claude: Some(now - 10 * MINUTE_MS),
+            codex: Some(now - MINUTE_MS),
+        };
+        let staleness = last.staleness(now, 60);
+        assert_eq!(
+            staleness.claude,
+            Some(StaleSince {
+                last_success_ms: now - 10 * MINUTE_MS
+            })
+        );
+        assert_eq!(staleness.codex, None);
+
+        let swapped = LastSuccess {
+            claude: last.codex,
+            codex: last.claude,
+        }
+        .staleness(now, 60);
+        assert_eq!(swapped.claude, None);
+        assert!(swapped.codex.is_some());
+    }
+
+    #[test]
+    fn cached_snapshot_seeds_last_success_from_ok_providers() {
+        use crate::types::{ClaudeUsageSnapshot, CodexUsageSnapshot};
+
+        let bundle = UsageSnapshotBundle {
+            claude: Some(ClaudeUsageSnapshot::Error {
+                organization_id: None,
+                last_updated_at: "2026-01-01T00:00:00Z".to_string(),
+                error_message: None,
+            }),
+            codex: Some(CodexUsageSnapshot::Ok {
+                session_percent: 20.0,
+                session_resets_at: None,
+                weekly_percent: Some(10.0),
+                weekly_resets_at: None,
+                last_updated_at: "2026-01-01T00:01:00Z".to_string(),
+                provider_timestamp: None,
+            }),
+            offline: false,
+            claude_orgs: vec![],
+        };
+        assert_eq!(
+            LastSuccess::from_snapshot(&bundle),
+            LastSuccess {
+                claude: None,
+                codex: Some(1_767_225_660_000),
+            }
+        );
+    }
+
+    #[test]
+    fn never_succeeded_or_within_threshold_is_not_stale() {
+        let now = 100 * MINUTE_MS;
+        let last = LastSuccess {
+            claude: None,
+            codex: Some(now - 3 * MINUTE_MS),
+        };
+        assert_eq!(last.staleness(now, 60), ProviderStaleness::default());
+    }
+}
@@ -1,19 +1,41 @@
 use super::{RefreshBus, SecretManager};
+use crate::burn_rate::burn_rates;
 use crate::claude::{cli_credentials_available, ClaudeApiClient, ClaudeWebErrorStatus};
 use crate::codex::CodexApiClient;
+use crate::daily_peaks::{local_date_key, DailyPeaks};
+use crate::http_client::HttpTimeouts;
+use crate::notifications::{snooze_remaining, toggle_snooze};
+use crate::refresh::PollTiming;
 use crate::settings::{
-    SettingsStore, KEY_CODEX_USAGE_SOURCE, KEY_REFRESH_INTERVAL_SECONDS, KEY_REMEMBER_SESSION_KEY,
-    KEY_SELECTED_ORGANIZATION_ID, KEY_TRACK_CLAUDE_ENABLED, KEY_TRACK_CODEX_ENABLED,
-    KEY_USAGE_SOURCE,
+    debug_mode_enabled, SettingsStore, KEY_ASCII_TRAY_TITLE, KEY_BATTERY_SAVER,
+    KEY_CACHED_SNAPSHOT, KEY_CACHE_LAST_SNAPSHOT, KEY_CLAUDE_LABEL, KEY_CLAUDE_USAGE_PAGE_URL,
+    KEY_CLAUDE_WEEKLY_AGGREGATE, KEY_CODEX_LABEL, KEY_CODEX_USAGE_PAGE_URL, KEY_CODEX_USAGE_SOURCE,
+    KEY_COLORBLIND_MODE, KEY_DAILY_PEAKS, KEY_HIDE_ZERO_MODELS, KEY_HTTP_TIMEOUT_SECONDS,
+    KEY_LAST_UPDATED_DISPLAY, KEY_LEVEL_SYMBOLS, KEY_MAX_MODELS_IN_MENU, KEY_MISSING_ORG_POLICY,
+    KEY_NOTIFICATION_SNOOZE_MINUTES, KEY_POLLING_PAUSED, KEY_PROVIDER_ORDER,
+    KEY_RATE_LIMIT_BACKOFF_SECONDS, KEY_RATE_LIMIT_COLOR, KEY_REFRESH_INTERVAL_SECONDS,
+    KEY_REFRESH_JITTER_RATIO, KEY_REMEMBER_CODEX_COOKIE, KEY_REMEMBER_SESSION_KEY,
+    KEY_SELECTED_ORGANIZATION_ID, KEY_SHOW_MODELS_IN_MENU, KEY_SHOW_RESET_COUNTDOWN,
+    KEY_SHOW_WEEKLY_IN_MENU, KEY_SHOW_WINDOW_PACING, KEY_TRACK_ALL_ORGS, KEY_TRACK_CLAUDE_ENABLED,
+    KEY_TRACK_CODEX_ENABLED, KEY_TRAY_CLICK_ACTION, KEY_TRAY_COLORS, KEY_TRAY_CRITICAL_PERCENT,
+    KEY_TRAY_ICON_BADGE, KEY_TRAY_LEVEL_METRIC, KEY_TRAY_METRIC, KEY_TRAY_TITLE_TEMPLATE,
+    KEY_TRAY_WARN_PERCENT, KEY_USAGE_HISTORY, KEY_USAGE_HISTORY_RETENTION_HOURS, KEY_USAGE_SOURCE,
+};
+use crate::staleness::{now_unix_ms, LastSuccess};
+use crate::tray::{
+    usage_page_url, LevelThresholds, TrayExtras, TrayOptions, TrayPalette, TrayUi,
+    DEFAULT_CLAUDE_USAGE_PAGE_URL, DEFAULT_CODEX_USAGE_PAGE_URL,
 };
-use crate::tray::TrayUi;
 use crate::types::{
-    ClaudeModelUsage, ClaudeOrganization, ClaudeUsageSnapshot, CodexUsageSnapshot,
-    CodexUsageSource, UsageSnapshotBundle, UsageSource,
+    AutoResolution, ClaudeModelUsage, ClaudeOrganization, ClaudeUsageSnapshot, CodexUsageSnapshot,
+    CodexUsageSource, ColorblindMode, LastUpdatedDisplay, MissingOrgPolicy, ProviderOrder,
+    RefreshErrorEvent, TrayClickAction, TrayColors, TrayLevelMetric, TrayMetric,
+    UsageHistorySample, UsageSnapshotBundle, UsageSource, WeeklyAggregate,
 };
-use std::collections::HashMap;
+use crate::usage_history;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, EventTarget, Runtime};
 use tokio::sync::Mutex;
 
@@ -63,7 +85,7 @@ impl DebugOverride {
             organization_id: self.organization_id.clone(),
             session_percent: self.session_percent,
             session_resets_at: Some(self.session_resets_at.clone()),
-            weekly_percent: self.weekly_percent,
+            weekly_percent: Some(self.weekly_percent),
             weekly_resets_at: Some(self.weekly_resets_at.clone()),
             models: vec![
                 ClaudeModelUsage {
@@ -77,7 +99,9 @@ impl DebugOverride {
                     resets_at: Some(self.weekly_resets_at.clone()),
                 },
             ],
+            session_models: vec![],
             last_updated_at: now_iso(),
+            provider_timestamp: None,
         }
     }
 
@@ -85,9 +109,10 @@ impl DebugOverride {
         CodexUsageSnapshot::Ok {
             session_percent: self.session_percent,
             session_resets_at: Some(self.session_resets_at.clone()),
-            weekly_percent: self.weekly_percent,
+            weekly_percent: Some(self.weekly_percent),
             weekly_resets_at: Some(self.weekly_resets_at.clone()),
             last_updated_at: now_iso(),
+            provider_timestamp: None,
         }
     }
 
@@ -95,13 +120,70 @@ impl DebugOverride {
         UsageSnapshotBundle {
             claude: track_claude.then(|| self.claude_snapshot()),
             codex: track_codex.then(|| self.codex_snapshot()),
+            offline: false,
+            claude_orgs: vec![],
         }
     }
 }
 
+/// The bundle cached by `AppState::persist_snapshot`, unless caching was turned off (which
+/// also drops any stale copy).
+pub fn load_cached_snapshot<R: Runtime>(
+    settings: &SettingsStore<R>,
+    usage_data: &SettingsStore<R>,
+) -> Option<UsageSnapshotBundle> {
+    if !settings.get_bool(KEY_CACHE_LAST_SNAPSHOT, true) {
+        usage_data.remove(KEY_CACHED_SNAPSHOT);
+        return None;
+    }
+    usage_data
+        .get_json(KEY_CACHED_SNAPSHOT)
+        .and_then(|v| serde_json::from_value(v).ok())
+}
+
+/// What `persist_snapshot` stores for `snapshot`: only its Ok provider slots, keeping the cached
+/// slot of a provider whose fetch failed so an error never replaces the last good values.
+fn cacheable_snapshot(
+    snapshot: &UsageSnapshotBundle,
+    cached: Option<UsageSnapshotBundle>,
+) -> UsageSnapshotBundle {
+    let (cached_claude, cached_codex, cached_orgs) = match cached {
+        Some(cached) => (cached.claude, cached.codex, cached.claude_orgs),
+        None => (None, None, vec![]),
+    };
+    let claude_orgs: Vec<_> = snapshot
+        .claude_orgs
+        .iter()
+        .filter(|org| matches!(org.snapshot, ClaudeUsageSnapshot::Ok { .. }))
+        .cloned()
+        .collect();
+    UsageSnapshotBundle {
+        claude: snapshot
+            .claude
+            .clone()
+            .filter(|s| matches!(s, ClaudeUsageSnapshot::Ok { .. }))
+            .or(cached_claude),
+        codex: snapshot
+            .codex
+            .clone()
+            .filter(|s| matches!(s, CodexUsageSnapshot::Ok { .. }))
+            .or(cached_codex),
+        offline: false,
+        claude_orgs: if claude_orgs.is_empty() {
+            cached_orgs
+        } else {
+            claude_orgs
+        },
+    }
+}
+
 pub struct AppState<R: tauri::Runtime> {
     pub settings: SettingsStore<R>,
+    /// Retained usage data (the usage history and the startup cache), separate from the user's
+    /// settings file.
+    pub usage_data: SettingsStore<R>,
     pub claude_session_key: SecretManager,
+    pub codex_cookie: SecretManager,
     pub claude: Arc<ClaudeApiClient>,
     pub codex: Arc<CodexApiClient>,
     pub organizations: Arc<Mutex<Vec<ClaudeOrganization>>>,
@@ -109,6 +191,19 @@ pub struct AppState<R: tauri::Runtime> {
     pub latest_snapshot: Arc<Mutex<Option<UsageSnapshotBundle>>>,
     pub reset_baseline_by_org: Arc<Mutex<HashMap<String, UsageResetBaseline>>>,
     pub debug_override: Arc<Mutex<DebugOverride>>,
+    /// Set when the selected Claude organization disappeared upstream; cleared on settings save
+    /// and once a refresh fetches the organization without a notice.
+    pub claude_org_notice: Arc<Mutex<Option<String>>>,
+    pub last_success: Arc<Mutex<LastSuccess>>,
+    /// Notifications are muted until this instant (toggled from the tray). A std mutex so the
+    /// synchronous delivery check can read it.
+    pub notifications_snoozed_until: Arc<std::sync::Mutex<Option<Instant>>>,
+    /// Timed refreshes are paused from the tray (persisted as `pollingPaused`).
+    pub paused: Arc<Mutex<bool>>,
+    /// Providers whose current rate-limited span was already announced.
+    pub rate_limit_notified: Arc<Mutex<HashSet<&'static str>>>,
+    /// How the last Codex refresh on the Auto source was resolved (`None` on other sources).
+    pub codex_auto_resolution: Arc<Mutex<Option<AutoResolution>>>,
     pub tray: TrayUi<R>,
     pub refresh: RefreshBus,
 }
@@ -117,7 +212,9 @@ impl<R: tauri::Runtime> Clone for AppState<R> {
     fn clone(&self) -> Self {
         Self {
             settings: self.settings.clone(),
+            usage_data: self.usage_data.clone(),
             claude_session_key: self.claude_session_key.clone(),
+            codex_cookie: self.codex_cookie.clone(),
             claude: self.claude.clone(),
             codex: self.codex.clone(),
             organizations: self.organizations.clone(),
@@ -125,6 +222,12 @@ impl<R: tauri::Runtime> Clone for AppState<R> {
             latest_snapshot: self.latest_snapshot.clone(),
             reset_baseline_by_org: self.reset_baseline_by_org.clone(),
             debug_override: self.debug_override.clone(),
+            claude_org_notice: self.claude_org_notice.clone(),
+            last_success: self.last_success.clone(),
+            notifications_snoozed_until: self.notifications_snoozed_until.clone(),
+            paused: self.paused.clone(),
+            rate_limit_notified: self.rate_limit_notified.clone(),
+            codex_auto_resolution: self.codex_auto_resolution.clone(),
             tray: self.tray.clone(),
             refresh: self.refresh.clone(),
         }
@@ -160,10 +263,33 @@ impl<R: tauri::Runtime> AppState<R> {
         *cache = None;
     }
 
+    /// Forget everything learned since startup: organizations, reset baselines, samples,
+    /// notices, the latest snapshot, pause and snooze. Each lock is released before the next is
+    /// taken, so this can't deadlock against the refresh loop.
+    pub async fn clear_runtime_state(&self) {
+        self.organizations.lock().await.clear();
+        self.invalidate_orgs_cache().await;
+        self.reset_baseline_by_org.lock().await.clear();
+        self.clear_claude_org_notice().await;
+        *self.last_success.lock().await = LastSuccess::default();
+        *self.paused.lock().await = false;
+        self.rate_limit_notified.lock().await.clear();
+        *self.codex_auto_resolution.lock().await = None;
+        *self
+            .notifications_snoozed_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = None;
+        *self.latest_snapshot.lock().await = None;
+    }
+
     pub fn remember_session_key(&self) -> bool {
         self.settings.get_bool(KEY_REMEMBER_SESSION_KEY, false)
     }
 
+    pub fn remember_codex_cookie(&self) -> bool {
+        self.settings.get_bool(KEY_REMEMBER_CODEX_COOKIE, false)
+    }
+
     pub fn track_claude_enabled(&self) -> bool {
         self.settings.get_bool(KEY_TRACK_CLAUDE_ENABLED, true)
     }
@@ -172,6 +298,10 @@ impl<R: tauri::Runtime> AppState<R> {
         self.settings.get_bool(KEY_TRACK_CODEX_ENABLED, true)
     }
 
+    pub fn track_all_orgs(&self) -> bool {
+        self.settings.get_bool(KEY_TRACK_ALL_ORGS, false)
+    }
+
     pub fn selected_org_id(&self) -> Option<String> {
         self.settings.get_string(KEY_SELECTED_ORGANIZATION_ID)
     }
@@ -193,19 +323,312 @@ impl<R: tauri::Runtime> AppState<R> {
     }
 
     pub fn codex_usage_source(&self) -> CodexUsageSource {
-        match self.settings.get_string(KEY_CODEX_USAGE_SOURCE).as_deref() {
-            Some("oauth") => CodexUsageSource::Oauth,
-            Some("cli") => CodexUsageSource::Cli,
-            _ => CodexUsageSource::Oauth,
+        self.settings
+            .get_string(KEY_CODEX_USAGE_SOURCE)
+            .as_deref()
+            .and_then(CodexUsageSource::from_setting_value)
+            .unwrap_or(CodexUsageSource::Oauth)
+    }
+
+    pub fn missing_org_policy(&self) -> MissingOrgPolicy {
+        self.settings
+            .get_string(KEY_MISSING_ORG_POLICY)
+            .as_deref()
+            .and_then(MissingOrgPolicy::from_setting_value)
+            .unwrap_or(MissingOrgPolicy::AutoSwitch)
+    }
+
+    /// Store the organization notice; returns true when it differs from the current one.
+    pub async fn set_claude_org_notice(&self, notice: String) -> bool {
+        let mut guard = self.claude_org_notice.lock().await;
+        if guard.as_deref() == Some(notice.as_str()) {
+            return false;
         }
+        *guard = Some(notice);
+        true
+    }
+
+    /// Stamp providers whose latest fetch succeeded (drives per-provider staleness).
+    pub async fn record_last_success(&self, claude_ok: bool, codex_ok: bool) {
+        let now = now_unix_ms();
+        let mut guard = self.last_success.lock().await;
+        if claude_ok {
+            guard.claude = Some(now);
+        }
+        if codex_ok {
+            guard.codex = Some(now);
+        }
+    }
+
+    pub async fn clear_claude_org_notice(&self) {
+        let mut guard = self.claude_org_notice.lock().await;
+        *guard = None;
     }
 
     pub fn refresh_interval_seconds(&self) -> u64 {
         self.settings.get_u64(KEY_REFRESH_INTERVAL_SECONDS, 60)
     }
+
+    pub(crate) fn http_timeouts(&self) -> HttpTimeouts {
+        HttpTimeouts::from_seconds(self.settings.get_u64(KEY_HTTP_TIMEOUT_SECONDS, 40))
+    }
+
+    /// Rebuild both API clients' HTTP clients after the timeout setting changed. A client that
+    /// can't be rebuilt keeps its previous timeouts.
+    pub(crate) fn apply_http_timeouts(&self) {
+        let timeouts = self.http_timeouts();
+        let _ = self.claude.set_timeouts(timeouts);
+        let _ = self.codex.set_timeouts(timeouts);
+    }
+
+    pub(crate) fn poll_timing(&self) -> PollTiming {
+        let defaults = PollTiming::default();
+        PollTiming::from_settings(
+            self.refresh_interval_seconds(),
+            self.settings
+                .get_json(KEY_REFRESH_JITTER_RATIO)
+                .and_then(|v| v.as_f64())
+                .unwrap_or(defaults.jitter_ratio),
+            self.settings.get_u64(
+                KEY_RATE_LIMIT_BACKOFF_SECONDS,
+                defaults.rate_limit_backoff_ms / 1000,
+            ),
+            debug_mode_enabled(),
+        )
+    }
+
+    pub fn battery_saver_enabled(&self) -> bool {
+        self.settings.get_bool(KEY_BATTERY_SAVER, false)
+    }
+
+    fn stored_daily_peaks(&self) -> DailyPeaks {
+        self.settings
+            .get_json(KEY_DAILY_PEAKS)
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn notification_snooze_remaining(&self) -> Option<Duration> {
+        let until = *self
+            .notifications_snoozed_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        snooze_remaining(until, Instant::now())
+    }
+
+    /// Snooze notifications for `notificationSnoozeMinutes`, or resume them if already snoozed.
+    pub fn toggle_notification_snooze(&self) {
+        let minutes = self
+            .settings
+            .get_u64(KEY_NOTIFICATION_SNOOZE_MINUTES, 60)
+            .max(1);
+        let mut until = self
+            .notifications_snoozed_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        *until = toggle_snooze(*until, Instant::now(), Duration::from_secs(minutes * 60));
+    }
+
+    pub async fn polling_paused(&self) -> bool {
+        *self.paused.lock().await
+    }
+
+    /// Pause or resume timed refreshes and persist the choice. Returns the new paused state.
+    pub async fn toggle_polling_paused(&self) -> bool {
+        let mut paused = self.paused.lock().await;
+        *paused = !*paused;
+        self.settings.set(KEY_POLLING_PAUSED, *paused);
+        *paused
+    }
+
+    /// Today's usage peaks (empty right after local midnight).
+    pub fn daily_peaks(&self) -> DailyPeaks {
+        self.stored_daily_peaks().for_date(&local_date_key())
+    }
+
+    fn usage_history_retention_ms(&self) -> i64 {
+        let hours = self
+            .settings
+            .get_u64(KEY_USAGE_HISTORY_RETENTION_HOURS, 24)
+            .clamp(1, 24 * 7);
+        hours as i64 * 60 * 60 * 1000
+    }
+
+    fn stored_usage_history(&self) -> Vec<UsageHistorySample> {
+        self.usage_data
+            .get_json(KEY_USAGE_HISTORY)
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default()
+    }
+
+    fn store_usage_history(&self, samples: &[UsageHistorySample]) {
+        if let Ok(value) = serde_json::to_value(samples) {
+            self.usage_data.set(KEY_USAGE_HISTORY, value);
+        }
+    }
+
+    /// Samples within the retention window, oldest first. Read-only: expired samples are only
+    /// dropped from the store when the next fetch is recorded.
+    pub fn usage_history(&self) -> Vec<UsageHistorySample> {
+        let mut samples = self.stored_usage_history();
+        usage_history::prune(
+            &mut samples,
+            now_unix_ms(),
+            self.usage_history_retention_ms(),
+        );
+        samples
+    }
+
+    pub fn record_usage_history(&self, snapshot: &UsageSnapshotBundle) {
+        let mut samples = self.stored_usage_history();
+        if usage_history::record(
+            &mut samples,
+            snapshot,
+            now_unix_ms(),
+            self.usage_history_retention_ms(),
+        ) {
+            self.store_usage_history(&samples);
+        }
+    }
+
+    /// Remember `snapshot` for the next launch (see `load_cached_snapshot`).
+    pub fn persist_snapshot(&self, snapshot: &UsageSnapshotBundle) {
+        if !self.settings.get_bool(KEY_CACHE_LAST_SNAPSHOT, true) {
+            return;
+        }
+        let cached = load_cached_snapshot(&self.settings, &self.usage_data);
+        if let Ok(value) = serde_json::to_value(cacheable_snapshot(snapshot, cached)) {
+            self.usage_data.set(KEY_CACHED_SNAPSHOT, value);
+        }
+    }
+
+    pub fn record_daily_peaks(&self, snapshot: &UsageSnapshotBundle) {
+        let mut peaks = self.stored_daily_peaks();
+        if !peaks.record(&local_date_key(), snapshot) {
+            return;
+        }
+        if let Ok(value) = serde_json::to_value(&peaks) {
+            self.settings.set(KEY_DAILY_PEAKS, value);
+        }
+    }
+
+    pub fn claude_usage_page_url(&self) -> String {
+        usage_page_url(
+            self.settings
+                .get_string(KEY_CLAUDE_USAGE_PAGE_URL)
+                .as_deref(),
+            DEFAULT_CLAUDE_USAGE_PAGE_URL,
+        )
+    }
+
+    pub fn codex_usage_page_url(&self) -> String {
+        usage_page_url(
+            self.settings
+                .get_string(KEY_CODEX_USAGE_PAGE_URL)
+                .as_deref(),
+            DEFAULT_CODEX_USAGE_PAGE_URL,
+        )
+    }
+
+    pub fn tray_click_action(&self) -> TrayClickAction {
+        self.settings
+            .get_string(KEY_TRAY_CLICK_ACTION)
+            .as_deref()
+            .and_then(TrayClickAction::from_setting_value)
+            .unwrap_or_default()
+    }
+
+    pub fn tray_colors(&self) -> TrayColors {
+        self.settings
+            .get_json(KEY_TRAY_COLORS)
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn tray_options(&self) -> TrayOptions {
+        TrayOptions {
+            hide_zero_models: self.settings.get_bool(KEY_HIDE_ZERO_MODELS, false),
+            hide_menu_weekly: !self.settings.get_bool(KEY_SHOW_WEEKLY_IN_MENU, true),
+            hide_menu_models: !self.settings.get_bool(KEY_SHOW_MODELS_IN_MENU, true),
+            max_menu_models: self.settings.get_u64(KEY_MAX_MODELS_IN_MENU, 5) as usize,
+            provider_order: self
+                .settings
+                .get_string(KEY_PROVIDER_ORDER)
+                .as_deref()
+                .and_then(ProviderOrder::from_setting_value)
+                .unwrap_or_default(),
+            metric: self
+                .settings
+                .get_string(KEY_TRAY_METRIC)
+                .as_deref()
+                .and_then(TrayMetric::from_setting_value)
+                .unwrap_or_default(),
+            level_metric: self
+                .settings
+                .get_string(KEY_TRAY_LEVEL_METRIC)
+                .as_deref()
+                .and_then(TrayLevelMetric::from_setting_value)
+                .unwrap_or_default(),
+            click_action: self.tray_click_action(),
+            rate_limited_red: self.settings.get_string(KEY_RATE_LIMIT_COLOR).as_deref()
+                == Some("red"),
+            thresholds: LevelThresholds {
+                warn_percent: self.settings.get_u64(KEY_TRAY_WARN_PERCENT, 50) as f64,
+                critical_percent: self.settings.get_u64(KEY_TRAY_CRITICAL_PERCENT, 70) as f64,
+            },
+            show_window_pacing: self.settings.get_bool(KEY_SHOW_WINDOW_PACING, false),
+            title_template: self.settings.get_string(KEY_TRAY_TITLE_TEMPLATE),
+            claude_label: self
+                .settings
+                .get_string(KEY_CLAUDE_LABEL)
+                .filter(|l| !l.trim().is_empty()),
+            codex_label: self
+                .settings
+                .get_string(KEY_CODEX_LABEL)
+                .filter(|l| !l.trim().is_empty()),
+            ascii_title: self.settings.get_bool(KEY_ASCII_TRAY_TITLE, false),
+            icon_badge: self.settings.get_bool(KEY_TRAY_ICON_BADGE, false),
+            show_reset_countdown: self.settings.get_bool(KEY_SHOW_RESET_COUNTDOWN, true),
+            last_updated_display: self
+                .settings
+                .get_string(KEY_LAST_UPDATED_DISPLAY)
+                .as_deref()
+                .and_then(LastUpdatedDisplay::from_setting_value)
+                .unwrap_or_default(),
+            palette: TrayPalette::from_colors(&self.tray_colors()),
+            colorblind_mode: self
+                .settings
+                .get_string(KEY_COLORBLIND_MODE)
+                .as_deref()
+                .and_then(ColorblindMode::from_setting_value)
+                .unwrap_or_default(),
+            weekly_aggregate: self
+                .settings
+                .get_string(KEY_CLAUDE_WEEKLY_AGGREGATE)
+                .as_deref()
+                .and_then(WeeklyAggregate::from_setting_value)
+                .unwrap_or_default(),
+            level_symbols: self
+                .settings
+                .get_json(KEY_LEVEL_SYMBOLS)
+                .and_then(|v| serde_json::from_value(v).ok())
+                .unwrap_or_default(),
+        }
+    }
 }
 
 const SNAPSHOT_EVENT: &str = "snapshot:updated";
+const REFRESH_ERROR_EVENT: &str = "refresh:error";
+
+/// Broadcast `snapshot` to every window listening for `snapshot:updated`.
+pub fn emit_snapshot<R: Runtime>(app: &AppHandle<R>, snapshot: Option<&UsageSnapshotBundle>) {
+    let _ = app.emit_to(EventTarget::any(), SNAPSHOT_EVENT, snapshot);
+}
+
+/// Tell every window that a provider just started failing (see `refresh_error_event`).
+pub fn emit_refresh_error<R: Runtime>(app: &AppHandle<R>, event: &RefreshErrorEvent) {
+    let _ = app.emit_to(EventTarget::any(), REFRESH_ERROR_EVENT, event);
+}
 
 impl<R: Runtime> AppState<R> {
     pub async fn update_snapshot(&self, app: &AppHandle<R>, snapshot: Option<UsageSnapshotBundle>) {
@@ -217,8 +640,117 @@ impl<R: Runtime> AppState<R> {
         self.tray.update_snapshot(
             self.track_claude_enabled(),
             self.track_codex_enabled(),
+            &self.tray_options(),
+            &TrayExtras {
+                peaks: self.daily_peaks(),
+                claude_org_notice: self.claude_org_notice.lock().await.clone(),
+                staleness: self
+                    .last_success
+                    .lock()
+                    .await
+                    .staleness(now_unix_ms(), self.refresh_interval_seconds()),
+                notifications_snoozed_for: self.notification_snooze_remaining(),
+                polling_paused: self.polling_paused().await,
+                organizations: if self.usage_source() == UsageSource::Web {
+                    self.organizations.lock().await.clone()
+                } else {
+                    vec![]
+                },
+                selected_organization_id: self.selected_org_id(),
+                burn_rates: burn_rates(&self.usage_history(), now_unix_ms()),
+            },
             snapshot.as_ref(),
         );
-        let _ = app.emit_to(EventTarget::any(), SNAPSHOT_EVENT, snapshot);
+        emit_snapshot(app, snapshot.as_ref());
+    }
+
+    /// Re-fire `snapshot:updated` with the current snapshot so a (re)opened window can re-sync.
+    pub async fn emit_latest_snapshot(&self, app: &AppHandle<R>) {
+        let latest = self.latest_snapshot.lock().await.clone();
+        emit_snapshot(app, latest.as_ref());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tauri::Listener as _;
+
+    #[test]
+    fn emit_snapshot_sends_the_current_bundle() {
+        let app = tauri::test::mock_app();
+        let received = Arc::new(std::sync::Mutex::new(None::<String>));
+        let sink = received.clone();
+        app.listen_any(SNAPSHOT_EVENT, move |event| {
+            *sink.lock().unwrap() = Some(event.payload().to_string());
+        });
+
+        let bundle = UsageSnapshotBundle {
+            claude: None,
+            codex: Some(CodexUsageSnapshot::Ok {
+                session_percent: 42.0,
+                session_resets_at: None,
+                weekly_percent: Some(10.0),
+                weekly_resets_at: None,
+                last_updated_at: "2026-01-01T00:00:00Z".to_string(),
+                provider_timestamp: None,
+            }),
+            offline: false,
+            claude_orgs: vec![],
+        };
+        emit_snapshot(app.handle(), Some(&bundle));
+
+        let payload = received.lock().unwrap().clone().expect("event emitted");
+        assert_eq!(payload, serde_json::to_string(&bundle).unwrap());
+    }
+
+    #[test]
+    fn cacheable_snapshot_keeps_only_ok_slots() {
+        let codex_ok = |percent| CodexUsageSnapshot::Ok {
+            session_percent: percent,
+            session_resets_at: None,
+            weekly_percent: None,
+            weekly_resets_at: None,
+            last_updated_at: "2026-01-01T00:00:00Z".to_string(),
+            provider_timestamp: None,
+        };
+        let cached = UsageSnapshotBundle {
+            claude: None,
+            codex: Some(codex_ok(10.0)),
+            offline: false,
+            claude_orgs: vec![],
+        };
+        let failed = UsageSnapshotBundle {
+            claude: Some(ClaudeUsageSnapshot::Error {
+                organization_id: None,
+                last_updated_at: "2026-01-01T00:01:00Z".to_string(),
+                error_message: Some("Failed to fetch organizations.".to_string()),
+            }),
+            codex: Some(CodexUsageSnapshot::Error {
+                last_updated_at: "2026-01-01T00:01:00Z".to_string(),
+                error_message: None,
+            }),
+            offline: true,
+            claude_orgs: vec![],
+        };
+
+        let stored = cacheable_snapshot(&failed, Some(cached));
+        assert!(stored.claude.is_none());
+        assert!(matches!(
+            stored.codex,
+            Some(CodexUsageSnapshot::Ok { session_percent, .. }) if session_percent == 10.0
+        ));
+        assert!(!stored.offline);
+
+        let refreshed = UsageSnapshotBundle {
+            codex: Some(codex_ok(20.0)),
+            ..failed
+        };
+        let stored = cacheable_snapshot(&refreshed, None);
+        assert!(stored.claude.is_none());
+        assert!(matches!(
+            stored.codex,
+            Some(CodexUsageSnapshot::Ok { session_percent, .. }) if session_percent == 20.0
+        ));
     }
 }
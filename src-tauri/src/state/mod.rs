@@ -2,6 +2,9 @@ mod app_state;
 mod refresh_bus;
 mod secret_manager;
 
-pub use app_state::{AppState, DebugOverride};
+pub use app_state::{emit_refresh_error, load_cached_snapshot, AppState, DebugOverride};
 pub use refresh_bus::{RefreshBus, RefreshRequest};
-pub use secret_manager::{SecretManager, KEYRING_USER_CLAUDE_SESSION_KEY};
+pub use secret_manager::{
+    SecretManager, KEYRING_USER_CLAUDE_SESSION_KEY, KEYRING_USER_CODEX_COOKIE,
+    LEGACY_KEYRING_USERS_CLAUDE_SESSION_KEY,
+};
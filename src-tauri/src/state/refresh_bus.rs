@@ -1,9 +1,15 @@
 use crate::types::{IpcErrorCode, IpcResult};
-use tokio::sync::{mpsc, oneshot};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, Notify};
+
+/// How long quitting waits for the refresh loop to wind down before exiting anyway.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
 
 #[derive(Clone)]
 pub struct RefreshBus {
     tx: mpsc::UnboundedSender<RefreshRequest>,
+    shutdown: Arc<Notify>,
 }
 
 pub struct RefreshRequest {
@@ -12,7 +18,24 @@ pub struct RefreshRequest {
 
 impl RefreshBus {
     pub(crate) fn new(tx: mpsc::UnboundedSender<RefreshRequest>) -> Self {
-        Self { tx }
+        Self {
+            tx,
+            shutdown: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Resolves once `shutdown` has been called. The request is remembered, so a loop that isn't
+    /// waiting at that moment still sees it next time it checks.
+    pub(crate) async fn shutdown_requested(&self) {
+        self.shutdown.notified().await
+    }
+
+    /// Stop the refresh loop, abandoning any fetch in flight, and wait (up to `SHUTDOWN_TIMEOUT`)
+    /// until it has cleaned up.
+    pub async fn shutdown(&self) {
+        self.shutdown.notify_one();
+        // The loop drops its receiver on the way out.
+        let _ = tokio::time::timeout(SHUTDOWN_TIMEOUT, self.tx.closed()).await;
     }
 
     pub async fn refresh_now(&self) -> IpcResult<()> {
@@ -30,3 +53,27 @@ impl RefreshBus {
             .unwrap_or_else(|_| IpcResult::err(IpcErrorCode::Unknown, "Refresh loop failed."))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn shutdown_is_seen_by_a_late_waiter_and_waits_for_the_receiver() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let bus = RefreshBus::new(tx);
+        let loop_bus = bus.clone();
+        let stopped = tokio::spawn(async move {
+            // Busy elsewhere when shutdown is requested.
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            loop_bus.shutdown_requested().await;
+            drop(rx);
+        });
+
+        tokio::time::timeout(Duration::from_secs(1), bus.shutdown())
+            .await
+            .unwrap();
+        stopped.await.unwrap();
+        assert!(matches!(bus.refresh_now().await, IpcResult::Err { .. }));
+    }
+}
@@ -1,21 +1,49 @@
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use zeroize::{Zeroize, Zeroizing};
 
 const KEYRING_SERVICE: &str = "com.softaworks.claudometer";
 pub const KEYRING_USER_CLAUDE_SESSION_KEY: &str = "claude_session_key";
+/// Earlier names of `KEYRING_USER_CLAUDE_SESSION_KEY`, newest first. Add the old name here when
+/// renaming it so saved keys are carried over on the next launch.
+pub const LEGACY_KEYRING_USERS_CLAUDE_SESSION_KEY: &[&str] = &[];
+pub const KEYRING_USER_CODEX_COOKIE: &str = "codex_cookie";
+
+/// Cleans up a raw secret; the error is a user-facing message that never echoes the value.
+type Normalize = fn(&str) -> Result<String, &'static str>;
 
 #[derive(Clone)]
 pub struct SecretManager {
     user: &'static str,
-    in_memory: Arc<Mutex<Option<String>>>,
+    /// Empty when unset. Overwritten with zeros whenever it's replaced or cleared, and on drop.
+    in_memory: Arc<Mutex<Zeroizing<String>>>,
+    /// Environment variable consulted after the in-memory value and before the keyring.
+    env_fallback: Option<(&'static str, Normalize)>,
 }
 
 impl SecretManager {
     pub fn new(user: &'static str) -> Self {
         Self {
             user,
-            in_memory: Arc::new(Mutex::new(None)),
+            in_memory: Arc::new(Mutex::new(Zeroizing::new(String::new()))),
+            env_fallback: None,
+        }
+    }
+
+    /// Also read the secret from `var` (cleaned up by `normalize`), for setups that manage
+    /// secrets outside the OS keyring.
+    pub fn with_env_fallback(mut self, var: &'static str, normalize: Normalize) -> Self {
+        self.env_fallback = Some((var, normalize));
+        self
+    }
+
+    fn env_value(&self) -> Option<Zeroizing<String>> {
+        let (var, normalize) = self.env_fallback?;
+        let raw = Zeroizing::new(std::env::var(var).ok()?);
+        if raw.trim().is_empty() {
+            return None;
         }
+        normalize(&raw).ok().map(Zeroizing::new)
     }
 
     fn entry(&self) -> Result<keyring::Entry, keyring::Error> {
@@ -39,19 +67,27 @@ impl SecretManager {
     }
 
     pub async fn set_in_memory(&self, value: Option<String>) {
+        let value = value.map(Zeroizing::new);
         let mut guard = self.in_memory.lock().await;
-        *guard = value.and_then(|v| {
-            let trimmed = v.trim().to_string();
-            if trimmed.is_empty() {
-                None
-            } else {
-                Some(trimmed)
-            }
-        });
+        guard.zeroize();
+        if let Some(value) = value.as_deref() {
+            guard.push_str(value.trim());
+        }
     }
 
-    pub async fn get_current(&self, remember: bool) -> Result<Option<String>, ()> {
-        if let Some(value) = self.in_memory.lock().await.clone() {
+    /// The secret is zeroed when the returned value is dropped, so keep it only as long as needed.
+    ///
+    /// Precedence: the in-memory value (entered this session), then the env fallback, then the
+    /// keyring when `remember` is on. The env var is read every time and never copied elsewhere.
+    pub async fn get_current(&self, remember: bool) -> Result<Option<Zeroizing<String>>, ()> {
+        {
+            let guard = self.in_memory.lock().await;
+            if !guard.is_empty() {
+                return Ok(Some(guard.clone()));
+            }
+        }
+
+        if let Some(value) = self.env_value() {
             return Ok(Some(value));
         }
 
@@ -61,13 +97,13 @@ impl SecretManager {
 
         let entry = self.entry().map_err(|_| ())?;
 
-        match entry.get_password() {
+        match entry.get_password().map(Zeroizing::new) {
             Ok(pwd) => {
-                let trimmed = pwd.trim().to_string();
+                let trimmed = Zeroizing::new(pwd.trim().to_string());
                 if trimmed.is_empty() {
                     Ok(None)
                 } else {
-                    self.set_in_memory(Some(trimmed.clone())).await;
+                    self.set_in_memory(Some(trimmed.to_string())).await;
                     Ok(Some(trimmed))
                 }
             }
@@ -78,6 +114,15 @@ impl SecretManager {
         }
     }
 
+    /// Move a secret saved under `old_user` to this manager's entry, unless one is already
+    /// there. Failures are ignored so startup never depends on them.
+    pub fn migrate_from(&self, old_user: &str) {
+        let entries = self
+            .entry()
+            .and_then(|current| Ok((current, keyring::Entry::new(KEYRING_SERVICE, old_user)?)));
+        let _ = entries.and_then(|(current, legacy)| migrate_entry(&current, &legacy));
+    }
+
     pub async fn remember(&self, value: &str) -> Result<(), ()> {
         let entry = self.entry().map_err(|_| ())?;
         entry.set_password(value).map_err(|_| ())?;
@@ -97,3 +142,132 @@ impl SecretManager {
         Ok(())
     }
 }
+
+/// Copy `legacy` into an empty `current`, then delete `legacy`. Returns whether anything moved.
+fn migrate_entry(current: &keyring::Entry, legacy: &keyring::Entry) -> keyring::Result<bool> {
+    match current.get_password() {
+        Ok(existing) if !Zeroizing::new(existing).trim().is_empty() => return Ok(false),
+        Ok(_) | Err(keyring::Error::NoEntry) => {}
+        Err(e) => return Err(e),
+    }
+    let value = match legacy.get_password() {
+        Ok(value) => Zeroizing::new(value),
+        Err(keyring::Error::NoEntry) => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    if value.trim().is_empty() {
+        return Ok(false);
+    }
+    current.set_password(&value)?;
+    match legacy.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(true),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn clearing_the_in_memory_secret_zeroes_its_buffer() {
+        let manager = SecretManager::new("test");
+        manager
+            .set_in_memory(Some("  sk-ant-sid01-secret  ".to_string()))
+            .await;
+        assert_eq!(
+            manager
+                .get_current(false)
+                .await
+                .unwrap()
+                .as_deref()
+                .map(String::as_str),
+            Some("sk-ant-sid01-secret")
+        );
+
+        let (ptr, capacity) = {
+            let guard = manager.in_memory.lock().await;
+            (guard.as_ptr(), guard.capacity())
+        };
+        manager.set_in_memory(None).await;
+
+        let guard = manager.in_memory.lock().await;
+        assert!(guard.is_empty());
+        // Zeroizing keeps the allocation, so the old bytes can be inspected in place.
+        assert_eq!(guard.as_ptr(), ptr);
+        // Safety: the string still owns `capacity` bytes at `ptr` and we hold the lock.
+        let buffer = unsafe { std::slice::from_raw_parts(ptr, capacity) };
+        assert!(buffer.iter().all(|&b| b == 0));
+        drop(guard);
+
+        assert_eq!(manager.get_current(false).await, Ok(None));
+    }
+
+    #[test]
+    fn migration_moves_a_legacy_entry_once() {
+        use keyring::credential::CredentialBuilderApi as _;
+        let builder = keyring::mock::default_credential_builder();
+        let entry = |user: &str| {
+            keyring::Entry::new_with_credential(builder.build(None, KEYRING_SERVICE, user).unwrap())
+        };
+
+        let current = entry("new_user");
+        let legacy = entry("old_user");
+        // Nothing saved anywhere: a no-op rather than an error.
+        assert!(!migrate_entry(&current, &legacy).unwrap());
+
+        legacy.set_password("sk-ant-legacy").unwrap();
+        assert!(migrate_entry(&current, &legacy).unwrap());
+        assert_eq!(current.get_password().unwrap(), "sk-ant-legacy");
+        assert!(matches!(
+            legacy.get_password(),
+            Err(keyring::Error::NoEntry)
+        ));
+        assert!(!migrate_entry(&current, &legacy).unwrap());
+
+        // An existing entry is never overwritten.
+        legacy.set_password("sk-ant-stale").unwrap();
+        assert!(!migrate_entry(&current, &legacy).unwrap());
+        assert_eq!(current.get_password().unwrap(), "sk-ant-legacy");
+    }
+
+    #[tokio::test]
+    async fn env_fallback_sits_between_memory_and_keyring() {
+        const VAR: &str = "CLAUDOMETER_TEST_SECRET_PRECEDENCE";
+        let current = |manager: &SecretManager| {
+            let manager = manager.clone();
+            async move {
+                manager
+                    .get_current(true)
+                    .await
+                    .unwrap()
+                    .map(|v| v.to_string())
+            }
+        };
+        let manager = SecretManager::new("test").with_env_fallback(VAR, |raw| {
+            let trimmed = raw.trim();
+            if trimmed.contains(' ') {
+                Err("invalid")
+            } else {
+                Ok(trimmed.to_string())
+            }
+        });
+
+        std::env::set_var(VAR, "  from-env \n");
+        // Env wins over the keyring, so `remember` never reaches it here.
+        assert_eq!(current(&manager).await.as_deref(), Some("from-env"));
+
+        manager.set_in_memory(Some("typed-in".to_string())).await;
+        assert_eq!(current(&manager).await.as_deref(), Some("typed-in"));
+        manager.set_in_memory(None).await;
+        assert_eq!(current(&manager).await.as_deref(), Some("from-env"));
+
+        // Invalid or blank values are skipped rather than used.
+        std::env::set_var(VAR, "two words");
+        assert_eq!(manager.env_value(), None);
+        std::env::set_var(VAR, "   ");
+        assert_eq!(manager.env_value(), None);
+        std::env::remove_var(VAR);
+        assert_eq!(manager.env_value(), None);
+    }
+}
@@ -0,0 +1,101 @@
+use super::colors::Rgba;
+use crate::bitmap_font::{glyph, GLYPH_HEIGHT, GLYPH_WIDTH};
+
+/// Width and height of the badge bitmap (an 18pt menu bar icon at 2x).
+pub const BADGE_SIZE: u32 = 36;
+
+/// Fill for error/unknown states, independent of the user's palette.
+pub const NEUTRAL_GRAY: Rgba = Rgba {
+    red: 0.55,
+    green: 0.55,
+    blue: 0.55,
+    alpha: 1.0,
+};
+
+/// RGBA8 pixels for a filled circle in `fill` with the rounded `percent` drawn in white on top.
+/// `None` draws the circle alone.
+pub fn badge_pixels(percent: Option<f64>, fill: Rgba) -> Vec<u8> {
+    let size = BADGE_SIZE as usize;
+    let mut pixels = vec![0u8; size * size * 4];
+    let to_byte = |c: f64| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    let center = size as f64 / 2.0;
+    let radius = center - 1.0;
+    for y in 0..size {
+        for x in 0..size {
+            let dx = x as f64 + 0.5 - center;
+            let dy = y as f64 + 0.5 - center;
+            // Linear falloff over the rim pixel keeps the edge from looking jagged.
+            let coverage = (radius - (dx * dx + dy * dy).sqrt() + 0.5).clamp(0.0, 1.0);
+            if coverage > 0.0 {
+                let i = (y * size + x) * 4;
+                pixels[i..i + 4].copy_from_slice(&[
+                    to_byte(fill.red),
+                    to_byte(fill.green),
+                    to_byte(fill.blue),
+                    to_byte(fill.alpha * coverage),
+                ]);
+            }
+        }
+    }
+
+    let Some(percent) = percent else {
+        return pixels;
+    };
+    let text = (percent.clamp(0.0, 100.0).round() as u32).to_string();
+    let glyphs = text.chars().count();
+    // "100" needs a smaller scale to stay inside the circle.
+    let scale = if glyphs >= 3 { 1 } else { 2 };
+    let advance = GLYPH_WIDTH + 1;
+    let left = (size - (glyphs * advance - 1) * scale) / 2;
+    let top = (size - GLYPH_HEIGHT * scale) / 2;
+    for (n, c) in text.chars().enumerate() {
+        for (col, bits) in glyph(c).iter().enumerate() {
+            for row in (0..GLYPH_HEIGHT).filter(|row| bits & (1 << row) != 0) {
+                for sy in 0..scale {
+                    for sx in 0..scale {
+                        let x = left + (n * advance + col) * scale + sx;
+                        let y = top + row * scale + sy;
+                        let i = (y * size + x) * 4;
+                        pixels[i..i + 4].copy_from_slice(&[255, 255, 255, 255]);
+                    }
+                }
+            }
+        }
+    }
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(pixels: &[u8], x: usize, y: usize) -> &[u8] {
+        let i = (y * BADGE_SIZE as usize + x) * 4;
+        &pixels[i..i + 4]
+    }
+
+    #[test]
+    fn badge_draws_circle_and_white_digits() {
+        let red = Rgba {
+            red: 1.0,
+            green: 0.0,
+            blue: 0.0,
+            alpha: 1.0,
+        };
+        let dot = badge_pixels(None, red);
+        assert_eq!(dot.len(), (BADGE_SIZE * BADGE_SIZE * 4) as usize);
+        assert_eq!(pixel(&dot, 0, 0), &[0, 0, 0, 0]);
+        assert_eq!(pixel(&dot, 18, 18), &[255, 0, 0, 255]);
+
+        // "88" lights the center of each digit's middle row.
+        let badge = badge_pixels(Some(88.0), red);
+        assert_eq!(pixel(&badge, 0, 0), &[0, 0, 0, 0]);
+        assert_eq!(pixel(&badge, 12, 18), &[255, 255, 255, 255]);
+        assert_eq!(pixel(&badge, 24, 18), &[255, 255, 255, 255]);
+        assert_eq!(pixel(&badge, 18, 18), &[255, 0, 0, 255]);
+
+        // Out-of-range values clamp to three digits and still fit.
+        assert_eq!(badge_pixels(Some(250.0), red).len(), dot.len());
+    }
+}
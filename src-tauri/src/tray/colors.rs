@@ -0,0 +1,134 @@
+use crate::types::TrayColors;
+
+/// sRGB color with components in `0.0..=1.0`, ready for `NSColor::colorWithSRGBRed_green_blue_alpha`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgba {
+    pub red: f64,
+    pub green: f64,
+    pub blue: f64,
+    pub alpha: f64,
+}
+
+/// Parse `#RRGGBB` or `#RRGGBBAA` (leading `#` optional, case-insensitive).
+pub fn parse_hex_color(value: &str) -> Option<Rgba> {
+    let hex = value.trim();
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if !matches!(hex.len(), 6 | 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let component = |i: usize| -> Option<f64> {
+        let byte = u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()?;
+        Some(byte as f64 / 255.0)
+    };
+    Some(Rgba {
+        red: component(0)?,
+        green: component(2)?,
+        blue: component(4)?,
+        alpha: if hex.len() == 8 { component(6)? } else { 1.0 },
+    })
+}
+
+/// The first field of `colors` that isn't a valid hex color, for save-time validation.
+pub fn first_invalid_color(colors: &TrayColors) -> Option<&'static str> {
+    [
+        ("ok", &colors.ok),
+        ("warn", &colors.warn),
+        ("critical", &colors.critical),
+        ("unknown", &colors.unknown),
+    ]
+    .into_iter()
+    .find(|(_, value)| parse_hex_color(value).is_none())
+    .map(|(name, _)| name)
+}
+
+/// Parsed tray title colors indexed by usage level (see `formatters::usage_level`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrayPalette {
+    pub ok: Rgba,
+    pub warn: Rgba,
+    pub critical: Rgba,
+    pub unknown: Rgba,
+}
+
+impl TrayPalette {
+    /// Invalid entries fall back to the default palette individually.
+    pub fn from_colors(colors: &TrayColors) -> Self {
+        let defaults = TrayColors::default();
+        let pick = |value: &str, fallback: &str| {
+            parse_hex_color(value)
+                .or_else(|| parse_hex_color(fallback))
+                .unwrap_or(Rgba {
+                    red: 0.5,
+                    green: 0.5,
+                    blue: 0.5,
+                    alpha: 1.0,
+                })
+        };
+        Self {
+            ok: pick(&colors.ok, &defaults.ok),
+            warn: pick(&colors.warn, &defaults.warn),
+            critical: pick(&colors.critical, &defaults.critical),
+            unknown: pick(&colors.unknown, &defaults.unknown),
+        }
+    }
+
+    pub fn for_level(&self, level: i8) -> Rgba {
+        match level {
+            0 => self.ok,
+            1 => self.warn,
+            2 => self.critical,
+            _ => self.unknown,
+        }
+    }
+}
+
+impl Default for TrayPalette {
+    fn default() -> Self {
+        Self::from_colors(&TrayColors::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex_color_maps_to_rgba_components() {
+        assert_eq!(
+            parse_hex_color("#FF9800"),
+            Some(Rgba {
+                red: 1.0,
+                green: 152.0 / 255.0,
+                blue: 0.0,
+                alpha: 1.0,
+            })
+        );
+        assert_eq!(
+            parse_hex_color("00ff0080"),
+            Some(Rgba {
+                red: 0.0,
+                green: 1.0,
+                blue: 0.0,
+                alpha: 128.0 / 255.0,
+            })
+        );
+        assert_eq!(parse_hex_color("#FFF"), None);
+        assert_eq!(parse_hex_color("#GG0000"), None);
+        assert_eq!(parse_hex_color("#FF98001"), None);
+    }
+
+    #[test]
+    fn palette_falls_back_per_invalid_entry() {
+        let colors = TrayColors {
+            ok: "#0000FF".to_string(),
+            warn: "nope".to_string(),
+            ..TrayColors::default()
+        };
+        assert_eq!(first_invalid_color(&colors), Some("warn"));
+
+        let palette = TrayPalette::from_colors(&colors);
+        assert_eq!(palette.for_level(0), parse_hex_color("#0000FF").unwrap());
+        assert_eq!(palette.for_level(1), TrayPalette::default().warn);
+        assert_eq!(palette.for_level(-1), TrayPalette::default().unknown);
+    }
+}
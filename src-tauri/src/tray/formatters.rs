@@ -1,7 +1,13 @@
-use crate::provider_view::{view_claude, view_codex};
-use crate::types::UsageSnapshotBundle;
+use super::TrayOptions;
+use crate::provider_view::{view_claude, view_codex, ProviderOkView};
+use crate::staleness::{ProviderStaleness, StaleSince};
+use crate::types::{
+    ClaudeModelUsage, ClaudeUsageSnapshot, ColorblindMode, LastUpdatedDisplay, ProviderOrder,
+    TrayLevelMetric, TrayMetric, UsageSnapshotBundle, UsageStatus, WeeklyAggregate,
+};
 use chrono::format::Locale;
 use chrono::{DateTime, FixedOffset, Local};
+use std::fmt::Write as _;
 
 pub(crate) fn format_percent(value: Option<f64>) -> String {
     value
@@ -9,65 +15,540 @@ pub(crate) fn format_percent(value: Option<f64>) -> String {
         .unwrap_or_else(|| "--%".to_string())
 }
 
-/// Generate the tray title text based on usage snapshot.
-/// Returns percentage for Ok state, "--%" for error states.
+/// " (peak 78%)" when today's peak is above the current value; empty otherwise.
+pub(crate) fn format_peak_suffix(current: f64, peak: Option<f64>) -> String {
+    match peak {
+        Some(peak) if peak.round() > current.round() => {
+            format!(" (peak {})", format_percent(Some(peak)))
+        }
+        _ => String::new(),
+    }
+}
+
+/// Model rows to render in the Claude submenu.
+/// When `hide_zero` is set, rows at 0% (including the preferred Sonnet/Opus buckets) are dropped.
+pub(crate) fn visible_models(
+    models: &[ClaudeModelUsage],
+    hide_zero: bool,
+) -> Vec<&ClaudeModelUsage> {
+    models
+        .iter()
+        .filter(|m| !hide_zero || m.percent.round() > 0.0)
+        .collect()
+}
+
+/// The `max` highest-usage rows (all of them when `max` is 0) and how many were left out. The
+/// sort is stable, so equal percentages keep the parser's Sonnet/Opus-first order.
+pub(crate) fn top_models(
+    mut models: Vec<&ClaudeModelUsage>,
+    max: usize,
+) -> (Vec<&ClaudeModelUsage>, usize) {
+    models.sort_by(|a, b| b.percent.total_cmp(&a.percent));
+    let more = if max == 0 {
+        0
+    } else {
+        models.len().saturating_sub(max)
+    };
+    models.truncate(models.len() - more);
+    (models, more)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProviderSection {
+    Claude,
+    Codex,
+}
+
+/// Menu section order for the configured provider order.
+pub(crate) fn ordered_sections(order: ProviderOrder) -> [ProviderSection; 2] {
+    match order {
+        ProviderOrder::ClaudeFirst => [ProviderSection::Claude, ProviderSection::Codex],
+        ProviderOrder::CodexFirst => [ProviderSection::Codex, ProviderSection::Claude],
+    }
+}
+
+/// Combined weekly percentage across per-org Claude snapshots. Only Ok snapshots with a known
+/// weekly value count; `None` when there are none.
+pub(crate) fn aggregate_claude_weekly<'a>(
+    snapshots: impl IntoIterator<Item = &'a ClaudeUsageSnapshot>,
+    aggregate: WeeklyAggregate,
+) -> Option<f64> {
+    let values: Vec<f64> = snapshots
+        .into_iter()
+        .filter_map(|snapshot| match snapshot {
+            ClaudeUsageSnapshot::Ok { weekly_percent, .. } => *weekly_percent,
+            _ => None,
+        })
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+    Some(match aggregate {
+        WeeklyAggregate::Max => values.iter().copied().fold(f64::MIN, f64::max),
+        WeeklyAggregate::Avg => values.iter().sum::<f64>() / values.len() as f64,
+    })
+}
+
+/// Claude figures for the title and level: the selected organization's, or when other
+/// organizations are tracked, the highest session and the `weekly_aggregate` of weekly usage
+/// across all Ok ones (resets come from the first of them).
+fn claude_title_view<'a>(
+    snapshot: &'a UsageSnapshotBundle,
+    options: &TrayOptions,
+) -> Option<ProviderOkView<'a>> {
+    let all = || {
+        snapshot
+            .claude
+            .iter()
+            .chain(snapshot.claude_orgs.iter().map(|o| &o.snapshot))
+    };
+    let base = all().find_map(view_claude)?;
+    if snapshot.claude_orgs.is_empty() {
+        return Some(base);
+    }
+    let session_percent = all()
+        .filter_map(view_claude)
+        .map(|v| v.session_percent)
+        .fold(f64::MIN, f64::max);
+    Some(ProviderOkView {
+        session_percent,
+        weekly_percent: aggregate_claude_weekly(all(), options.weekly_aggregate),
+        ..base
+    })
+}
+
+/// Compact time until `iso` relative to `now`: `1h20m`, `45m`, or `<1m`.
+/// `None` when `iso` is unparseable or already past.
+fn format_reset_countdown_at(iso: &str, now: DateTime<Local>) -> Option<String> {
+    let resets_at = DateTime::parse_from_rfc3339(iso).ok()?;
+    let remaining = resets_at.signed_duration_since(now);
+    if remaining <= chrono::Duration::zero() {
+        return None;
+    }
+    Some(format_minutes_compact(remaining.num_minutes()))
+}
+
+/// `1h20m`, `45m`, or `<1m`.
+fn format_minutes_compact(minutes: i64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, 0) => "<1m".to_string(),
+        (0, m) => format!("{m}m"),
+        (h, m) => format!("{h}h{m}m"),
+    }
+}
+
+/// Label for the snooze toggle: what clicking it does, plus the time left while snoozed.
+pub(crate) fn snooze_menu_label(remaining: Option<std::time::Duration>) -> String {
+    match remaining {
+        // Round up so the label never reads `<1m` while a minute is still left.
+        Some(remaining) => format!(
+            "Resume notifications (snoozed, {} left)",
+            format_minutes_compact(remaining.as_secs().div_ceil(60) as i64)
+        ),
+        None => "Snooze notifications".to_string(),
+    }
+}
+
+/// Label for the polling toggle: what clicking it does.
+pub(crate) fn pause_menu_label(paused: bool) -> &'static str {
+    if paused {
+        "Resume polling (paused)"
+    } else {
+        "Pause polling"
+    }
+}
+
+/// Whether the title shows percentages from an old successful update (kept while offline or
+/// restored from the cache) for any tracked provider, per `LastSuccess::staleness`.
+pub(crate) fn title_is_stale(
+    track_claude: bool,
+    track_codex: bool,
+    staleness: &ProviderStaleness,
+) -> bool {
+    (track_claude && staleness.claude.is_some()) || (track_codex && staleness.codex.is_some())
+}
+
+pub(crate) fn with_stale_marker(title: String, stale: bool, options: &TrayOptions) -> String {
+    match (stale, options.ascii_title) {
+        (false, _) => title,
+        (true, true) => format!("{title} (stale)"),
+        (true, false) => format!("{title} ⚠"),
+    }
+}
+
+/// Append a paused marker to the tray title while timed refreshes are paused from the tray.
+pub(crate) fn with_paused_marker(title: String, paused: bool, options: &TrayOptions) -> String {
+    match (paused, options.ascii_title) {
+        (false, _) => title,
+        (true, true) => format!("{title} (paused)"),
+        (true, false) => format!("{title} ⏸"),
+    }
+}
+
+pub(crate) fn format_reset_countdown(iso: &str) -> Option<String> {
+    format_reset_countdown_at(iso, Local::now())
+}
+
+/// One provider's title value for `options.metric`: `25%`, `80%`, or `25%/80%`, with `--%` per
+/// unknown field, plus the session countdown when enabled.
+fn title_value(view: Option<ProviderOkView<'_>>, options: &TrayOptions) -> String {
+    let session = format_percent(view.as_ref().map(|v| v.session_percent));
+    let weekly = format_percent(view.as_ref().and_then(|v| v.weekly_percent));
+    let value = match options.metric {
+        TrayMetric::Session => session,
+        TrayMetric::Weekly => weekly,
+        TrayMetric::Both => format!("{session}/{weekly}"),
+    };
+    let countdown = view
+        .filter(|_| options.show_reset_countdown)
+        .and_then(|v| v.session_resets_at)
+        .and_then(format_reset_countdown);
+    match countdown {
+        Some(countdown) => format!("{value} ({countdown})"),
+        None => value,
+    }
+}
+
+/// Replace `{token}`s in `template` with `value(token)`. Unknown tokens and unbalanced braces are
+/// kept literally.
+fn render_title_template(template: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let token = &after[..end];
+        if token.contains('{') {
+            // `{{token}`: the first brace is literal; retry from the inner one.
+            out.push('{');
+            rest = after;
+            continue;
+        }
+        match value(token) {
+            Some(v) => out.push_str(&v),
+            None => {
+                out.push('{');
+                out.push_str(token);
+                out.push('}');
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Value for a title template token such as `claude_session`, `codex_weekly_remaining`, or
+/// `symbol` (the level symbol, empty when unknown or blank).
+fn title_template_value(
+    token: &str,
+    options: &TrayOptions,
+    snapshot: Option<&UsageSnapshotBundle>,
+    symbol: &str,
+) -> Option<String> {
+    let claude = || {
+        snapshot
+            .and_then(|s| s.claude.as_ref())
+            .and_then(view_claude)
+    };
+    let codex = || snapshot.and_then(|s| s.codex.as_ref()).and_then(view_codex);
+    let remaining = |used: Option<f64>| format_percent(used.map(|u| (100.0 - u).max(0.0)));
+    Some(match token {
+        "claude_label" => options.claude_label().to_string(),
+        "codex_label" => options.codex_label().to_string(),
+        "claude_session" => format_percent(claude().map(|v| v.session_percent)),
+        "claude_weekly" => format_percent(claude().and_then(|v| v.weekly_percent)),
+        "codex_session" => format_percent(codex().map(|v| v.session_percent)),
+        "codex_weekly" => format_percent(codex().and_then(|v| v.weekly_percent)),
+        "claude_session_remaining" => remaining(claude().map(|v| v.session_percent)),
+        "claude_weekly_remaining" => remaining(claude().and_then(|v| v.weekly_percent)),
+        "codex_session_remaining" => remaining(codex().map(|v| v.session_percent)),
+        "codex_weekly_remaining" => remaining(codex().and_then(|v| v.weekly_percent)),
+        "symbol" => symbol.to_string(),
+        _ => return None,
+    })
+}
+
+/// Separator between providers in the dual-provider title.
+fn provider_separator(options: &TrayOptions) -> &'static str {
+    if options.ascii_title {
+        " | "
+    } else {
+        " · "
+    }
+}
+
+/// Generate the tray title text based on usage snapshot and `options.metric`, or from
+/// `options.title_template` when one is set.
+/// Returns percentage for Ok state, "--%" for error states, with a trailing `*` while offline
+/// (values carried over from before, or a tracked provider that couldn't be reached).
 pub(crate) fn format_tray_title(
     track_claude: bool,
     track_codex: bool,
+    options: &TrayOptions,
+    snapshot: Option<&UsageSnapshotBundle>,
+) -> String {
+    let title = base_tray_title(track_claude, track_codex, options, snapshot);
+    if snapshot.is_some_and(|s| is_offline(track_claude, track_codex, s)) {
+        format!("{title}*")
+    } else {
+        title
+    }
+}
+
+fn is_offline(track_claude: bool, track_codex: bool, snapshot: &UsageSnapshotBundle) -> bool {
+    let claude_offline = track_claude
+        && snapshot
+            .claude
+            .as_ref()
+            .is_some_and(|c| c.status() == UsageStatus::Offline);
+    let codex_offline = track_codex
+        && snapshot
+            .codex
+            .as_ref()
+            .is_some_and(|c| c.status() == UsageStatus::Offline);
+    snapshot.offline || claude_offline || codex_offline
+}
+
+fn base_tray_title(
+    track_claude: bool,
+    track_codex: bool,
+    options: &TrayOptions,
     snapshot: Option<&UsageSnapshotBundle>,
 ) -> String {
+    if let Some(template) = options
+        .title_template
+        .as_deref()
+        .filter(|t| !t.trim().is_empty())
+    {
+        let level = usage_level(track_claude, track_codex, options, snapshot);
+        let symbol = level_symbol(level, options).unwrap_or_default();
+        return render_title_template(template, |token| {
+            title_template_value(token, options, snapshot, symbol)
+        });
+    }
+
+    let claude = || {
+        title_value(
+            snapshot.and_then(|s| claude_title_view(s, options)),
+            options,
+        )
+    };
+    let codex = || {
+        title_value(
+            snapshot.and_then(|s| s.codex.as_ref()).and_then(view_codex),
+            options,
+        )
+    };
+
+    let (claude_label, codex_label) = (options.claude_label(), options.codex_label());
     if track_claude && track_codex {
-        let claude = snapshot
-            .and_then(|s| s.claude.as_ref())
-            .and_then(view_claude)
-            .map(|v| v.session_percent)
-            .map(|v| format!("{}%", v.round() as i64))
-            .unwrap_or_else(|| "--%".to_string());
-        let codex = snapshot
-            .and_then(|s| s.codex.as_ref())
-            .and_then(view_codex)
-            .map(|v| v.session_percent)
-            .map(|v| format!("{}%", v.round() as i64))
-            .unwrap_or_else(|| "--%".to_string());
-        return format!("CL {claude} · CX {codex}");
+        let (claude, codex) = (claude(), codex());
+        let sep = provider_separator(options);
+        return match options.provider_order {
+            ProviderOrder::ClaudeFirst => {
+                format!("{claude_label} {claude}{sep}{codex_label} {codex}")
+            }
+            ProviderOrder::CodexFirst => {
+                format!("{codex_label} {codex}{sep}{claude_label} {claude}")
+            }
+        };
     }
 
     if track_claude {
-        let percent = snapshot
-            .and_then(|s| s.claude.as_ref())
-            .and_then(view_claude)
-            .map(|v| v.session_percent);
-        let value = percent
-            .map(|v| format!("{}%", v.round() as i64))
-            .unwrap_or_else(|| "--%".to_string());
-        return format!("CL {value}");
+        return format!("{claude_label} {}", claude());
     }
     if track_codex {
-        let percent = snapshot
-            .and_then(|s| s.codex.as_ref())
-            .and_then(view_codex)
-            .map(|v| v.session_percent);
-        let value = percent
-            .map(|v| format!("{}%", v.round() as i64))
-            .unwrap_or_else(|| "--%".to_string());
-        return format!("CX {value}");
+        return format!("{codex_label} {}", codex());
     }
 
     "--%".to_string()
 }
 
-/// Determine usage level from session percentage.
-/// Returns: 0 = low (green), 1 = medium (orange), 2 = high (red), -1 = unknown (gray)
-pub(crate) fn usage_level(
+/// Symbol for a `usage_level` result; `None` for unknown or when the symbol is blank.
+pub(crate) fn level_symbol(level: i8, options: &TrayOptions) -> Option<&str> {
+    let symbol = match level {
+        0 => &options.level_symbols.ok,
+        1 => &options.level_symbols.warn,
+        2 => &options.level_symbols.critical,
+        _ => return None,
+    };
+    Some(symbol.trim()).filter(|s| !s.is_empty())
+}
+
+/// Append the level symbol to `title` in colorblind mode so severity doesn't rely on color alone.
+pub(crate) fn with_level_symbol(title: String, level: i8, options: &TrayOptions) -> String {
+    if options.colorblind_mode == ColorblindMode::Off {
+        return title;
+    }
+    match level_symbol(level, options) {
+        Some(symbol) => format!("{title} {symbol}"),
+        None => title,
+    }
+}
+
+/// Plain-text usage summary (title, then session/weekly per tracked provider) for exports.
+#[cfg(feature = "menu-export")]
+pub(crate) fn summary_lines(
     track_claude: bool,
     track_codex: bool,
+    options: &TrayOptions,
     snapshot: Option<&UsageSnapshotBundle>,
-) -> i8 {
+) -> Vec<String> {
+    let mut lines = vec![format_tray_title(
+        track_claude,
+        track_codex,
+        options,
+        snapshot,
+    )];
+
+    for section in ordered_sections(options.provider_order) {
+        let (tracked, label, view) = match section {
+            ProviderSection::Claude => (
+                track_claude,
+                "Claude",
+                snapshot
+                    .and_then(|s| s.claude.as_ref())
+                    .and_then(view_claude),
+            ),
+            ProviderSection::Codex => (
+                track_codex,
+                "Codex",
+                snapshot.and_then(|s| s.codex.as_ref()).and_then(view_codex),
+            ),
+        };
+        if !tracked {
+            continue;
+        }
+
+        lines.push(String::new());
+        lines.push(label.to_string());
+        let resets = |at: Option<&str>| {
+            at.and_then(format_reset_at_short)
+                .filter(|t| !t.is_empty())
+                .map(|t| format!(" (resets {t})"))
+                .unwrap_or_default()
+        };
+        match view {
+            Some(v) => {
+                lines.push(format!(
+                    "Session: {}{}",
+                    format_percent(Some(v.session_percent)),
+                    resets(v.session_resets_at)
+                ));
+                lines.push(format!(
+                    "Weekly: {}{}",
+                    format_percent(v.weekly_percent),
+                    resets(v.weekly_resets_at)
+                ));
+            }
+            None => {
+                lines.push("Session: --%".to_string());
+                lines.push("Weekly: --%".to_string());
+            }
+        }
+    }
+
+    lines
+}
+
+/// Plain-text usage for the "Copy usage summary" menu item: session/weekly with full reset times
+/// and the last update per tracked provider.
+pub(crate) fn clipboard_summary(
+    track_claude: bool,
+    track_codex: bool,
+    options: &TrayOptions,
+    snapshot: Option<&UsageSnapshotBundle>,
+) -> String {
+    const NO_DATA: &str = "Claudometer: no usage data yet";
+    let Some(snapshot) = snapshot else {
+        return NO_DATA.to_string();
+    };
+
+    let mut sections = Vec::new();
+    for section in ordered_sections(options.provider_order) {
+        let (tracked, label, view, last_updated_at) = match section {
+            ProviderSection::Claude => (
+                track_claude,
+                "Claude",
+                snapshot.claude.as_ref().and_then(view_claude),
+                snapshot.claude.as_ref().map(|s| s.last_updated_at()),
+            ),
+            ProviderSection::Codex => (
+                track_codex,
+                "Codex",
+                snapshot.codex.as_ref().and_then(view_codex),
+                snapshot.codex.as_ref().map(|s| s.last_updated_at()),
+            ),
+        };
+        if !tracked {
+            continue;
+        }
+
+        let resets = |at: Option<&str>| {
+            at.map(|t| format!(" (resets {})", format_datetime_full(t)))
+                .unwrap_or_default()
+        };
+        let mut lines = vec![label.to_string()];
+        match view {
+            Some(v) => {
+                lines.push(format!(
+                    "Session: {}{}",
+                    format_percent(Some(v.session_percent)),
+                    resets(v.session_resets_at)
+                ));
+                lines.push(format!(
+                    "Weekly: {}{}",
+                    format_percent(v.weekly_percent),
+                    resets(v.weekly_resets_at)
+                ));
+            }
+            None => lines.push("No data".to_string()),
+        }
+        if let Some(at) = last_updated_at {
+            lines.push(format!("Last updated: {}", format_datetime_full(at)));
+        }
+        sections.push(lines.join("\n"));
+    }
+
+    if sections.is_empty() {
+        return NO_DATA.to_string();
+    }
+    sections.join("\n\n")
+}
+
+/// The percent the tray level is based on: `options.level_metric` (by default the metric(s)
+/// shown in the title), highest across tracked providers. `None` when no tracked provider has
+/// usable data.
+pub(crate) fn tray_percent(
+    track_claude: bool,
+    track_codex: bool,
+    options: &TrayOptions,
+    snapshot: Option<&UsageSnapshotBundle>,
+) -> Option<f64> {
+    // `Both` stands for the higher of the two windows here.
+    let metric = match options.level_metric {
+        TrayLevelMetric::Title => options.metric,
+        TrayLevelMetric::Session => TrayMetric::Session,
+        TrayLevelMetric::Weekly => TrayMetric::Weekly,
+        TrayLevelMetric::Max => TrayMetric::Both,
+    };
+    let level_percent = |view: ProviderOkView<'_>| match metric {
+        TrayMetric::Session => Some(view.session_percent),
+        TrayMetric::Weekly => view.weekly_percent,
+        TrayMetric::Both => Some(
+            view.weekly_percent
+                .map_or(view.session_percent, |w| w.max(view.session_percent)),
+        ),
+    };
     let claude = if track_claude {
         snapshot
-            .and_then(|s| s.claude.as_ref())
-            .and_then(view_claude)
-            .map(|v| v.session_percent)
+            .and_then(|s| claude_title_view(s, options))
+            .and_then(level_percent)
     } else {
         None
     };
@@ -75,25 +556,53 @@ pub(crate) fn usage_level(
         snapshot
             .and_then(|s| s.codex.as_ref())
             .and_then(view_codex)
-            .map(|v| v.session_percent)
+            .and_then(level_percent)
     } else {
         None
     };
 
-    let session_percent = match (claude, codex) {
+    match (claude, codex) {
         (Some(a), Some(b)) => Some(a.max(b)),
         (Some(a), None) => Some(a),
         (None, Some(b)) => Some(b),
         (None, None) => None,
-    };
+    }
+}
+
+/// Determine usage level from the metric(s) picked by `options.level_metric` (see
+/// `tray_percent`) and `options.thresholds`. In `Both`/`Max` mode the higher of session and
+/// weekly wins.
+/// Returns: 0 = low (green), 1 = medium (orange), 2 = high (red), -1 = unknown (gray)
+/// With `rate_limited_red`, a rate-limited tracked provider forces red. An offline provider has no
+/// percentage, so it reads as unknown like other failures.
+pub(crate) fn usage_level(
+    track_claude: bool,
+    track_codex: bool,
+    options: &TrayOptions,
+    snapshot: Option<&UsageSnapshotBundle>,
+) -> i8 {
+    if options.rate_limited_red {
+        let claude_limited = track_claude
+            && snapshot
+                .and_then(|s| s.claude.as_ref())
+                .is_some_and(|c| c.status() == UsageStatus::RateLimited);
+        let codex_limited = track_codex
+            && snapshot
+                .and_then(|s| s.codex.as_ref())
+                .is_some_and(|c| c.status() == UsageStatus::RateLimited);
+        if claude_limited || codex_limited {
+            return 2;
+        }
+    }
 
-    let Some(session_percent) = session_percent else {
+    let Some(percent) = tray_percent(track_claude, track_codex, options, snapshot) else {
         return -1;
     };
 
-    if session_percent < 50.0 {
+    let thresholds = options.thresholds;
+    if percent < thresholds.warn_percent {
         0 // green
-    } else if session_percent <= 70.0 {
+    } else if percent <= thresholds.critical_percent {
         1 // orange
     } else {
         2 // red
@@ -295,53 +804,173 @@ pub(crate) fn format_time_short(iso: &str) -> Option<String> {
     // - Locale detection uses `LC_TIME` → `LC_ALL` → `LANG`.
     // - Formatting uses `chrono` `unstable-locales` (pure-rust-locales) for locale patterns.
     let dt: DateTime<FixedOffset> = DateTime::parse_from_rfc3339(iso).ok()?;
-    let locale = system_locale();
-    let formatted = dt
-        .with_timezone(&Local)
-        .format_localized("%X", locale)
-        .to_string();
+    let formatted =
+        format_localized_or_fallback(&dt.with_timezone(&Local), "%X", "%H:%M:%S", system_locale());
     Some(strip_seconds_from_time_string(&formatted))
 }
 
-pub(crate) fn format_reset_at_short(iso: &str) -> Option<String> {
-    // Locale + TZ rules:
-    // - Time zone conversion uses `chrono::Local` (the OS-configured local time zone, incl. DST).
-    // - Locale detection uses `LC_TIME` → `LC_ALL` → `LANG`.
-    // - Formatting uses `chrono` `unstable-locales` (pure-rust-locales) for locale patterns.
-    let dt: DateTime<FixedOffset> = DateTime::parse_from_rfc3339(iso).ok()?;
-    let locale = system_locale();
-    let local = dt.with_timezone(&Local);
+/// Keep localized output only if it has something readable (some locales/patterns can yield
+/// empty or separator-only strings).
+fn usable_localized(s: String) -> Option<String> {
+    s.chars().any(char::is_alphanumeric).then_some(s)
+}
+
+fn try_format_localized(local: &DateTime<Local>, fmt: &str, locale: Locale) -> Option<String> {
+    // `to_string()` would panic if the formatter reports an error; write into a buffer instead.
+    let mut out = String::new();
+    write!(out, "{}", local.format_localized(fmt, locale)).ok()?;
+    usable_localized(out)
+}
+
+/// Localized `fmt`, falling back to `en_US` and then to `iso_fmt`.
+fn format_localized_or_fallback(
+    local: &DateTime<Local>,
+    fmt: &str,
+    iso_fmt: &str,
+    locale: Locale,
+) -> String {
+    try_format_localized(local, fmt, locale)
+        .or_else(|| try_format_localized(local, fmt, Locale::en_US))
+        .unwrap_or_else(|| local.format(iso_fmt).to_string())
+}
 
-    let date_full = local.format_localized("%x", locale).to_string();
-    let date = strip_year_from_date_string(&date_full);
+fn format_reset_at_short_in(local: &DateTime<Local>, locale: Locale) -> String {
+    let date_full = format_localized_or_fallback(local, "%x", "%Y-%m-%d", locale);
+    let date = usable_localized(strip_year_from_date_string(&date_full))
+        .or_else(|| {
+            try_format_localized(local, "%x", Locale::en_US)
+                .and_then(|d| usable_localized(strip_year_from_date_string(&d)))
+        })
+        .unwrap_or_else(|| local.format("%m-%d").to_string());
 
-    let time_full = local.format_localized("%X", locale).to_string();
+    let time_full = format_localized_or_fallback(local, "%X", "%H:%M:%S", locale);
     let time = strip_seconds_from_time_string(&time_full);
 
     if date.trim().is_empty() {
-        Some(time)
+        time
     } else if time.trim().is_empty() {
-        Some(date)
+        date
     } else {
-        Some(format!("{date} {time}"))
+        format!("{date} {time}")
     }
 }
 
+pub(crate) fn format_reset_at_short(iso: &str) -> Option<String> {
+    // Locale + TZ rules:
+    // - Time zone conversion uses `chrono::Local` (the OS-configured local time zone, incl. DST).
+    // - Locale detection uses `LC_TIME` → `LC_ALL` → `LANG`.
+    // - Formatting uses `chrono` `unstable-locales` (pure-rust-locales) for locale patterns.
+    // - Empty/separator-only localized output falls back to `en_US`, then ISO.
+    let dt: DateTime<FixedOffset> = DateTime::parse_from_rfc3339(iso).ok()?;
+    Some(format_reset_at_short_in(
+        &dt.with_timezone(&Local),
+        system_locale(),
+    ))
+}
+
+fn format_datetime_full_in(local: &DateTime<Local>, locale: Locale) -> String {
+    let date = format_localized_or_fallback(local, "%x", "%Y-%m-%d", locale);
+    let time = format_localized_or_fallback(local, "%X", "%H:%M:%S", locale);
+    format!("{date}, {time}")
+}
+
 pub(crate) fn format_datetime_full(iso: &str) -> String {
     let Ok(dt) = DateTime::parse_from_rfc3339(iso) else {
         return iso.to_string();
     };
-    let local = dt.with_timezone(&Local);
-    let locale = system_locale();
-    let date = local.format_localized("%x", locale).to_string();
-    let time = local.format_localized("%X", locale).to_string();
-    format!("{date}, {time}")
+    format_datetime_full_in(&dt.with_timezone(&Local), system_locale())
+}
+
+pub(crate) const SESSION_WINDOW_MS: i64 = 5 * 60 * 60 * 1000;
+pub(crate) const WEEKLY_WINDOW_MS: i64 = 7 * 24 * 60 * 60 * 1000;
+
+/// Time progress vs quota progress through the current usage window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct WindowPacing {
+    pub time_percent: f64,
+    pub quota_percent: f64,
+}
+
+impl WindowPacing {
+    /// Quota is being used up faster than the window elapses.
+    pub(crate) fn burning_faster(&self) -> bool {
+        self.quota_percent > self.time_percent
+    }
+}
+
+/// Pacing for a window of `window_ms` that resets at `resets_at`. `None` when the reset time is
+/// unknown, unparsable, or already passed.
+pub(crate) fn window_pacing(
+    quota_percent: f64,
+    resets_at: Option<&str>,
+    window_ms: i64,
+    now_ms: i64,
+) -> Option<WindowPacing> {
+    let resets_at_ms = DateTime::parse_from_rfc3339(resets_at?)
+        .ok()?
+        .timestamp_millis();
+    if resets_at_ms <= now_ms || window_ms <= 0 {
+        return None;
+    }
+    let elapsed_ms = now_ms - (resets_at_ms - window_ms);
+    Some(WindowPacing {
+        time_percent: (elapsed_ms as f64 / window_ms as f64 * 100.0).clamp(0.0, 100.0),
+        quota_percent,
+    })
+}
+
+/// "Pace: 40% of time, 55% of quota (burning faster than the clock)".
+pub(crate) fn format_pacing_line(pacing: WindowPacing) -> String {
+    let suffix = if pacing.burning_faster() {
+        " (burning faster than the clock)"
+    } else {
+        ""
+    };
+    format!(
+        "Pace: {} of time, {} of quota{suffix}",
+        format_percent(Some(pacing.time_percent)),
+        format_percent(Some(pacing.quota_percent))
+    )
+}
+
+/// "~40m to limit at this rate", or "~2h 5m …" past an hour.
+pub(crate) fn format_time_to_limit(ms_to_limit: i64) -> String {
+    let minutes = (ms_to_limit / 60_000).max(1);
+    if minutes >= 60 {
+        format!("~{}h {}m to limit at this rate", minutes / 60, minutes % 60)
+    } else {
+        format!("~{minutes}m to limit at this rate")
+    }
+}
+
+/// "Last updated: <fetch time>", or "Data as of: <provider time>" when the provider reported one
+/// and the user prefers data age.
+pub(crate) fn format_last_updated(
+    last_updated_at: &str,
+    provider_timestamp: Option<&str>,
+    display: LastUpdatedDisplay,
+) -> String {
+    match (display, provider_timestamp) {
+        (LastUpdatedDisplay::DataAge, Some(at)) => {
+            format!("Data as of: {}", format_datetime_full(at))
+        }
+        _ => format!("Last updated: {}", format_datetime_full(last_updated_at)),
+    }
+}
+
+/// "Stale: no update since <time>" for a provider whose refreshes stopped succeeding.
+pub(crate) fn format_stale_notice(stale: StaleSince) -> String {
+    let since = DateTime::from_timestamp_millis(stale.last_success_ms)
+        .map(|dt| format_datetime_full_in(&dt.with_timezone(&Local), system_locale()))
+        .unwrap_or_else(|| "--".to_string());
+    format!("Stale: no update since {since}")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{ClaudeUsageSnapshot, CodexUsageSnapshot};
+    use crate::tray::LevelThresholds;
+    use crate::types::{ClaudeUsageSnapshot, CodexUsageSnapshot, LevelSymbols};
 
     fn sample_rfc3339_utc() -> &'static str {
         "2026-01-06T22:59:31Z"
@@ -399,6 +1028,33 @@ mod tests {
         assert_eq!(strip_year_from_date_string("01/28/26"), "01/28");
     }
 
+    #[test]
+    fn format_reset_countdown_renders_compact_durations() {
+        let now = DateTime::parse_from_rfc3339("2026-01-07T03:40:00Z")
+            .unwrap()
+            .with_timezone(&Local);
+        let countdown = |iso| format_reset_countdown_at(iso, now);
+        assert_eq!(countdown("2026-01-07T05:00:00Z").as_deref(), Some("1h20m"));
+        assert_eq!(countdown("2026-01-07T04:25:00Z").as_deref(), Some("45m"));
+        assert_eq!(countdown("2026-01-07T03:40:30Z").as_deref(), Some("<1m"));
+        assert_eq!(countdown("2026-01-07T03:40:00Z"), None);
+        assert_eq!(countdown("2026-01-07T03:00:00Z"), None);
+        assert_eq!(countdown("not a timestamp"), None);
+    }
+
+    #[test]
+    fn snooze_label_shows_time_left() {
+        assert_eq!(snooze_menu_label(None), "Snooze notifications");
+        assert_eq!(
+            snooze_menu_label(Some(std::time::Duration::from_secs(42 * 60 - 30))),
+            "Resume notifications (snoozed, 42m left)"
+        );
+        assert_eq!(
+            snooze_menu_label(Some(std::time::Duration::from_secs(3600))),
+            "Resume notifications (snoozed, 1h0m left)"
+        );
+    }
+
     #[test]
     fn format_reset_at_short_does_not_crash() {
         assert!(format_reset_at_short(sample_rfc3339_utc()).is_some());
@@ -410,12 +1066,16 @@ mod tests {
                 organization_id: "org-123".to_string(),
                 session_percent,
                 session_resets_at: Some("2026-01-07T05:00:00Z".to_string()),
-                weekly_percent: 30.0,
+                weekly_percent: Some(30.0),
                 weekly_resets_at: Some("2026-01-13T00:00:00Z".to_string()),
                 models: vec![],
+                session_models: vec![],
                 last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+                provider_timestamp: None,
             }),
             codex: None,
+            offline: false,
+            claude_orgs: vec![],
         }
     }
 
@@ -425,52 +1085,173 @@ mod tests {
             codex: Some(CodexUsageSnapshot::Ok {
                 session_percent,
                 session_resets_at: Some("2026-01-07T05:00:00Z".to_string()),
-                weekly_percent: 30.0,
+                weekly_percent: Some(30.0),
                 weekly_resets_at: Some("2026-01-13T00:00:00Z".to_string()),
                 last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+                provider_timestamp: None,
             }),
+            offline: false,
+            claude_orgs: vec![],
         }
     }
 
     #[test]
-    fn format_tray_title_shows_percentage() {
-        let snapshot = make_claude_ok_bundle(25.0);
-        let title = format_tray_title(true, false, Some(&snapshot));
-        assert_eq!(title, "CL 25%");
+    fn only_tracked_stale_providers_mark_the_title() {
+        let staleness = ProviderStaleness {
+            claude: None,
+            codex: Some(StaleSince { last_success_ms: 0 }),
+        };
+        assert!(title_is_stale(true, true, &staleness));
+        assert!(!title_is_stale(true, false, &staleness));
+        assert!(!title_is_stale(true, true, &ProviderStaleness::default()));
     }
 
     #[test]
-    fn format_tray_title_rounds_49_point_9_to_50() {
-        let snapshot = make_claude_ok_bundle(49.9);
-        let title = format_tray_title(true, false, Some(&snapshot));
-        assert_eq!(title, "CL 50%");
+    fn stale_title_gets_a_marker() {
+        let ascii = TrayOptions {
+            ascii_title: true,
+            ..TrayOptions::default()
+        };
+        let title = || "CL 25%".to_string();
+        assert_eq!(with_stale_marker(title(), false, &ascii), "CL 25%");
+        assert_eq!(with_stale_marker(title(), true, &ascii), "CL 25% (stale)");
+        assert_eq!(
+            with_stale_marker(title(), true, &TrayOptions::default()),
+            "CL 25% ⚠"
+        );
     }
 
     #[test]
-    fn format_tray_title_shows_100_percent() {
-        let snapshot = make_claude_ok_bundle(100.0);
-        let title = format_tray_title(true, false, Some(&snapshot));
-        assert_eq!(title, "CL 100%");
+    fn paused_polling_is_marked_in_title_and_menu() {
+        let ascii = TrayOptions {
+            ascii_title: true,
+            ..TrayOptions::default()
+        };
+        let title = || "CL 25%".to_string();
+        assert_eq!(with_paused_marker(title(), false, &ascii), "CL 25%");
+        assert_eq!(with_paused_marker(title(), true, &ascii), "CL 25% (paused)");
+        assert_eq!(
+            with_paused_marker(title(), true, &TrayOptions::default()),
+            "CL 25% ⏸"
+        );
+        assert_eq!(pause_menu_label(false), "Pause polling");
+        assert_eq!(pause_menu_label(true), "Resume polling (paused)");
     }
 
     #[test]
-    fn format_tray_title_shows_placeholder_for_none() {
-        let title = format_tray_title(true, false, None);
-        assert_eq!(title, "CL --%");
+    fn format_tray_title_combines_tracked_organizations() {
+        let mut snapshot = make_claude_ok_bundle(25.0);
+        let mut other = make_claude_ok_bundle(60.0).claude.unwrap();
+        if let ClaudeUsageSnapshot::Ok { weekly_percent, .. } = &mut other {
+            *weekly_percent = Some(10.0);
+        }
+        let org = |id: &str, snapshot| crate::types::ClaudeOrgSnapshot {
+            organization: crate::types::ClaudeOrganization {
+                id: id.to_string(),
+                name: None,
+            },
+            snapshot,
+        };
+        snapshot.claude_orgs = vec![
+            org("org-2", other),
+            org(
+                "org-3",
+                ClaudeUsageSnapshot::Error {
+                    organization_id: None,
+                    last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+                    error_message: None,
+                },
+            ),
+        ];
+        let with = |metric, weekly_aggregate| TrayOptions {
+            metric,
+            weekly_aggregate,
+            ..TrayOptions::default()
+        };
+
+        let title =
+            |options: &TrayOptions| format_tray_title(true, false, options, Some(&snapshot));
+        assert_eq!(
+            title(&with(TrayMetric::Session, WeeklyAggregate::Max)),
+            "CL 60%"
+        );
+        assert_eq!(
+            title(&with(TrayMetric::Weekly, WeeklyAggregate::Max)),
+            "CL 30%"
+        );
+        assert_eq!(
+            title(&with(TrayMetric::Weekly, WeeklyAggregate::Avg)),
+            "CL 20%"
+        );
     }
 
     #[test]
-    fn format_tray_title_shows_placeholder_for_unauthorized() {
+    fn format_tray_title_marks_offline_values() {
+        let mut snapshot = make_claude_ok_bundle(25.0);
+        snapshot.offline = true;
+        let title = format_tray_title(true, false, &TrayOptions::default(), Some(&snapshot));
+        assert_eq!(title, "CL 25%*");
+
+        // Unreachable with nothing to carry over: no value, but still marked offline.
         let snapshot = UsageSnapshotBundle {
-            claude: Some(ClaudeUsageSnapshot::Unauthorized {
+            claude: Some(ClaudeUsageSnapshot::Offline {
                 organization_id: None,
-                error_message: Some("Invalid session".to_string()),
                 last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+                error_message: None,
             }),
             codex: None,
+            offline: false,
+            claude_orgs: vec![],
         };
-        let title = format_tray_title(true, false, Some(&snapshot));
-        assert_eq!(title, "CL --%");
+        let options = TrayOptions::default();
+        assert_eq!(
+            format_tray_title(true, false, &options, Some(&snapshot)),
+            "CL --%*"
+        );
+        assert_eq!(usage_level(true, false, &options, Some(&snapshot)), -1);
+    }
+
+    #[test]
+    fn format_tray_title_shows_percentage() {
+        let snapshot = make_claude_ok_bundle(25.0);
+        let title = format_tray_title(true, false, &TrayOptions::default(), Some(&snapshot));
+        assert_eq!(title, "CL 25%");
+    }
+
+    #[test]
+    fn format_tray_title_rounds_49_point_9_to_50() {
+        let snapshot = make_claude_ok_bundle(49.9);
+        let title = format_tray_title(true, false, &TrayOptions::default(), Some(&snapshot));
+        assert_eq!(title, "CL 50%");
+    }
+
+    #[test]
+    fn format_tray_title_shows_100_percent() {
+        let snapshot = make_claude_ok_bundle(100.0);
+        let title = format_tray_title(true, false, &TrayOptions::default(), Some(&snapshot));
+        assert_eq!(title, "CL 100%");
+    }
+
+    #[test]
+    fn format_tray_title_shows_placeholder_for_none() {
+        let title = format_tray_title(true, false, &TrayOptions::default(), None);
+        assert_eq!(title, "CL --%");
+    }
+
+    #[test]
+    fn format_tray_title_shows_placeholder_for_unauthorized() {
+        let snapshot = UsageSnapshotBundle {
+            claude: Some(ClaudeUsageSnapshot::Unauthorized {
+                organization_id: None,
+                error_message: Some("Invalid session".to_string()),
+                last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+            }),
+            codex: None,
+            offline: false,
+            claude_orgs: vec![],
+        };
+        let title = format_tray_title(true, false, &TrayOptions::default(), Some(&snapshot));
+        assert_eq!(title, "CL --%");
     }
 
     #[test]
@@ -482,8 +1263,10 @@ mod tests {
                 last_updated_at: "2026-01-06T22:59:31Z".to_string(),
             }),
             codex: None,
+            offline: false,
+            claude_orgs: vec![],
         };
-        let title = format_tray_title(true, false, Some(&snapshot));
+        let title = format_tray_title(true, false, &TrayOptions::default(), Some(&snapshot));
         assert_eq!(title, "CL --%");
     }
 
@@ -494,10 +1277,13 @@ mod tests {
                 organization_id: None,
                 error_message: Some("Too many requests".to_string()),
                 last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+                retry_after_seconds: None,
             }),
             codex: None,
+            offline: false,
+            claude_orgs: vec![],
         };
-        let title = format_tray_title(true, false, Some(&snapshot));
+        let title = format_tray_title(true, false, &TrayOptions::default(), Some(&snapshot));
         assert_eq!(title, "CL --%");
     }
 
@@ -510,34 +1296,51 @@ mod tests {
                 last_updated_at: "2026-01-06T22:59:31Z".to_string(),
             }),
             codex: None,
+            offline: false,
+            claude_orgs: vec![],
         };
-        let title = format_tray_title(true, false, Some(&snapshot));
+        let title = format_tray_title(true, false, &TrayOptions::default(), Some(&snapshot));
         assert_eq!(title, "CL --%");
     }
 
     #[test]
     fn format_tray_title_rounds_percentage_correctly() {
         let snapshot = make_claude_ok_bundle(75.7);
-        let title = format_tray_title(true, false, Some(&snapshot));
+        let title = format_tray_title(true, false, &TrayOptions::default(), Some(&snapshot));
         assert!(title.contains("76%"), "75.7 should round to 76");
 
         let snapshot = make_claude_ok_bundle(75.4);
-        let title = format_tray_title(true, false, Some(&snapshot));
+        let title = format_tray_title(true, false, &TrayOptions::default(), Some(&snapshot));
         assert!(title.contains("75%"), "75.4 should round to 75");
     }
 
     #[test]
     fn usage_level_returns_green_below_50() {
         assert_eq!(
-            usage_level(true, false, Some(&make_claude_ok_bundle(0.0))),
+            usage_level(
+                true,
+                false,
+                &TrayOptions::default(),
+                Some(&make_claude_ok_bundle(0.0))
+            ),
             0
         );
         assert_eq!(
-            usage_level(true, false, Some(&make_claude_ok_bundle(25.0))),
+            usage_level(
+                true,
+                false,
+                &TrayOptions::default(),
+                Some(&make_claude_ok_bundle(25.0))
+            ),
             0
         );
         assert_eq!(
-            usage_level(true, false, Some(&make_claude_ok_bundle(49.9))),
+            usage_level(
+                true,
+                false,
+                &TrayOptions::default(),
+                Some(&make_claude_ok_bundle(49.9))
+            ),
             0
         );
     }
@@ -545,15 +1348,30 @@ mod tests {
     #[test]
     fn usage_level_returns_orange_between_50_and_70() {
         assert_eq!(
-            usage_level(true, false, Some(&make_claude_ok_bundle(50.0))),
+            usage_level(
+                true,
+                false,
+                &TrayOptions::default(),
+                Some(&make_claude_ok_bundle(50.0))
+            ),
             1
         );
         assert_eq!(
-            usage_level(true, false, Some(&make_claude_ok_bundle(65.0))),
+            usage_level(
+                true,
+                false,
+                &TrayOptions::default(),
+                Some(&make_claude_ok_bundle(65.0))
+            ),
             1
         );
         assert_eq!(
-            usage_level(true, false, Some(&make_claude_ok_bundle(70.0))),
+            usage_level(
+                true,
+                false,
+                &TrayOptions::default(),
+                Some(&make_claude_ok_bundle(70.0))
+            ),
             1
         );
     }
@@ -561,22 +1379,54 @@ mod tests {
     #[test]
     fn usage_level_returns_red_above_70() {
         assert_eq!(
-            usage_level(true, false, Some(&make_claude_ok_bundle(70.1))),
+            usage_level(
+                true,
+                false,
+                &TrayOptions::default(),
+                Some(&make_claude_ok_bundle(70.1))
+            ),
             2
         );
         assert_eq!(
-            usage_level(true, false, Some(&make_claude_ok_bundle(90.0))),
+            usage_level(
+                true,
+                false,
+                &TrayOptions::default(),
+                Some(&make_claude_ok_bundle(90.0))
+            ),
             2
         );
         assert_eq!(
-            usage_level(true, false, Some(&make_claude_ok_bundle(100.0))),
+            usage_level(
+                true,
+                false,
+                &TrayOptions::default(),
+                Some(&make_claude_ok_bundle(100.0))
+            ),
             2
         );
     }
 
+    #[test]
+    fn usage_level_uses_configured_thresholds() {
+        let options = TrayOptions {
+            thresholds: LevelThresholds {
+                warn_percent: 70.0,
+                critical_percent: 90.0,
+            },
+            ..TrayOptions::default()
+        };
+        for (percent, expected) in [(60.0, 0), (70.0, 1), (90.0, 1), (91.0, 2)] {
+            assert_eq!(
+                usage_level(true, false, &options, Some(&make_claude_ok_bundle(percent))),
+                expected
+            );
+        }
+    }
+
     #[test]
     fn usage_level_returns_unknown_for_error_states() {
-        assert_eq!(usage_level(true, false, None), -1);
+        assert_eq!(usage_level(true, false, &TrayOptions::default(), None), -1);
         let error = UsageSnapshotBundle {
             claude: Some(ClaudeUsageSnapshot::Error {
                 organization_id: None,
@@ -584,8 +1434,68 @@ mod tests {
                 last_updated_at: "2026-01-06T22:59:31Z".to_string(),
             }),
             codex: None,
+            offline: false,
+            claude_orgs: vec![],
         };
-        assert_eq!(usage_level(true, false, Some(&error)), -1);
+        assert_eq!(
+            usage_level(true, false, &TrayOptions::default(), Some(&error)),
+            -1
+        );
+    }
+
+    fn model(name: &str, percent: f64) -> ClaudeModelUsage {
+        ClaudeModelUsage {
+            name: name.to_string(),
+            percent,
+            resets_at: None,
+        }
+    }
+
+    #[test]
+    fn visible_models_hides_all_zero_rows_when_enabled() {
+        let models = vec![model("Sonnet", 0.0), model("Opus", 0.0)];
+        assert!(visible_models(&models, true).is_empty());
+        assert_eq!(visible_models(&models, false).len(), 2);
+    }
+
+    #[test]
+    fn visible_models_keeps_non_zero_rows_in_order() {
+        let models = vec![model("Sonnet", 0.0), model("Opus", 12.0), model("Foo", 3.0)];
+        let names: Vec<&str> = visible_models(&models, true)
+            .into_iter()
+            .map(|m| m.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Opus", "Foo"]);
+    }
+
+    #[test]
+    fn top_models_sorts_by_usage_and_caps_the_list() {
+        let models = vec![
+            model("Sonnet", 10.0),
+            model("Opus", 10.0),
+            model("Foo", 40.0),
+            model("Bar", 5.0),
+        ];
+        let names = |(rows, more): (Vec<&ClaudeModelUsage>, usize)| {
+            (
+                rows.into_iter()
+                    .map(|m| m.name.as_str())
+                    .collect::<Vec<_>>(),
+                more,
+            )
+        };
+        assert_eq!(
+            names(top_models(models.iter().collect(), 0)),
+            (vec!["Foo", "Sonnet", "Opus", "Bar"], 0)
+        );
+        assert_eq!(
+            names(top_models(models.iter().collect(), 2)),
+            (vec!["Foo", "Sonnet"], 2)
+        );
+        assert_eq!(
+            names(top_models(models.iter().collect(), 5)),
+            (vec!["Foo", "Sonnet", "Opus", "Bar"], 0)
+        );
     }
 
     #[test]
@@ -593,17 +1503,605 @@ mod tests {
         let snapshot = UsageSnapshotBundle {
             claude: make_claude_ok_bundle(25.0).claude,
             codex: make_codex_ok_bundle(10.0).codex,
+            offline: false,
+            claude_orgs: vec![],
         };
-        let title = format_tray_title(true, true, Some(&snapshot));
+        let title = format_tray_title(true, true, &TrayOptions::default(), Some(&snapshot));
         assert_eq!(title, "CL 25% · CX 10%");
     }
 
+    #[test]
+    fn format_tray_title_renders_each_metric_mode() {
+        let snapshot = UsageSnapshotBundle {
+            claude: make_claude_ok_bundle(25.0).claude,
+            codex: make_codex_ok_bundle(10.0).codex,
+            offline: false,
+            claude_orgs: vec![],
+        };
+        let with_metric = |metric| TrayOptions {
+            metric,
+            ..TrayOptions::default()
+        };
+        for (metric, dual, claude_only) in [
+            (TrayMetric::Session, "CL 25% · CX 10%", "CL 25%"),
+            (TrayMetric::Weekly, "CL 30% · CX 30%", "CL 30%"),
+            (TrayMetric::Both, "CL 25%/30% · CX 10%/30%", "CL 25%/30%"),
+        ] {
+            let options = with_metric(metric);
+            assert_eq!(
+                format_tray_title(true, true, &options, Some(&snapshot)),
+                dual
+            );
+            assert_eq!(
+                format_tray_title(true, false, &options, Some(&snapshot)),
+                claude_only
+            );
+        }
+    }
+
+    #[test]
+    fn format_tray_title_both_mode_uses_placeholders_per_field() {
+        let options = TrayOptions {
+            metric: TrayMetric::Both,
+            ..TrayOptions::default()
+        };
+        let error = UsageSnapshotBundle {
+            claude: Some(ClaudeUsageSnapshot::Error {
+                organization_id: None,
+                error_message: None,
+                last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+            }),
+            codex: make_codex_ok_bundle(10.0).codex,
+            offline: false,
+            claude_orgs: vec![],
+        };
+        assert_eq!(
+            format_tray_title(true, true, &options, Some(&error)),
+            "CL --%/--% · CX 10%/30%"
+        );
+
+        let mut unknown_weekly = make_claude_ok_bundle(25.0);
+        if let Some(ClaudeUsageSnapshot::Ok { weekly_percent, .. }) = &mut unknown_weekly.claude {
+            *weekly_percent = None;
+        }
+        assert_eq!(
+            format_tray_title(true, false, &options, Some(&unknown_weekly)),
+            "CL 25%/--%"
+        );
+    }
+
+    #[test]
+    fn usage_level_considers_weekly_when_metric_includes_it() {
+        let mut snapshot = make_claude_ok_bundle(10.0);
+        if let Some(ClaudeUsageSnapshot::Ok { weekly_percent, .. }) = &mut snapshot.claude {
+            *weekly_percent = Some(85.0);
+        }
+        let level = |metric| {
+            let options = TrayOptions {
+                metric,
+                ..TrayOptions::default()
+            };
+            usage_level(true, false, &options, Some(&snapshot))
+        };
+        assert_eq!(level(TrayMetric::Session), 0);
+        assert_eq!(level(TrayMetric::Weekly), 2);
+        assert_eq!(level(TrayMetric::Both), 2);
+    }
+
+    #[test]
+    fn usage_level_follows_level_metric_independently_of_the_title() {
+        // Session green, weekly red.
+        let mut snapshot = make_claude_ok_bundle(10.0);
+        if let Some(ClaudeUsageSnapshot::Ok { weekly_percent, .. }) = &mut snapshot.claude {
+            *weekly_percent = Some(85.0);
+        }
+        let level = |level_metric| {
+            let options = TrayOptions {
+                level_metric,
+                ..TrayOptions::default()
+            };
+            usage_level(true, false, &options, Some(&snapshot))
+        };
+        assert_eq!(level(TrayLevelMetric::Title), 0);
+        assert_eq!(level(TrayLevelMetric::Session), 0);
+        assert_eq!(level(TrayLevelMetric::Weekly), 2);
+        assert_eq!(level(TrayLevelMetric::Max), 2);
+
+        // The title metric no longer matters once a level metric is picked.
+        let options = TrayOptions {
+            metric: TrayMetric::Weekly,
+            level_metric: TrayLevelMetric::Session,
+            ..TrayOptions::default()
+        };
+        assert_eq!(usage_level(true, false, &options, Some(&snapshot)), 0);
+    }
+
+    #[test]
+    fn usage_level_by_weekly_still_takes_the_max_across_providers() {
+        // Claude: session 90% (red), weekly 30%; Codex: session 10%, weekly 60% (orange).
+        let mut codex = make_codex_ok_bundle(10.0).codex;
+        if let Some(CodexUsageSnapshot::Ok { weekly_percent, .. }) = &mut codex {
+            *weekly_percent = Some(60.0);
+        }
+        let snapshot = UsageSnapshotBundle {
+            claude: make_claude_ok_bundle(90.0).claude,
+            codex,
+            offline: false,
+            claude_orgs: vec![],
+        };
+        let level = |level_metric| {
+            let options = TrayOptions {
+                level_metric,
+                ..TrayOptions::default()
+            };
+            usage_level(true, true, &options, Some(&snapshot))
+        };
+        assert_eq!(level(TrayLevelMetric::Weekly), 1);
+        assert_eq!(level(TrayLevelMetric::Session), 2);
+        assert_eq!(level(TrayLevelMetric::Max), 2);
+        // Codex is left out when it isn't tracked.
+        let options = TrayOptions {
+            level_metric: TrayLevelMetric::Weekly,
+            ..TrayOptions::default()
+        };
+        assert_eq!(usage_level(true, false, &options, Some(&snapshot)), 0);
+    }
+
+    #[test]
+    fn format_tray_title_uses_configured_labels() {
+        let snapshot = UsageSnapshotBundle {
+            claude: make_claude_ok_bundle(25.0).claude,
+            codex: make_codex_ok_bundle(10.0).codex,
+            offline: false,
+            claude_orgs: vec![],
+        };
+        for (claude_label, codex_label, both, claude_only) in [
+            (None, None, "CL 25% · CX 10%", "CL 25%"),
+            (
+                Some("Claude"),
+                Some("Codex"),
+                "Claude 25% · Codex 10%",
+                "Claude 25%",
+            ),
+            (Some("🤖"), None, "🤖 25% · CX 10%", "🤖 25%"),
+        ] {
+            let options = TrayOptions {
+                claude_label: claude_label.map(str::to_string),
+                codex_label: codex_label.map(str::to_string),
+                ..TrayOptions::default()
+            };
+            assert_eq!(
+                format_tray_title(true, true, &options, Some(&snapshot)),
+                both
+            );
+            assert_eq!(
+                format_tray_title(true, false, &options, Some(&snapshot)),
+                claude_only
+            );
+
+            let templated = TrayOptions {
+                title_template: Some("{codex_label}={codex_session}".to_string()),
+                ..options
+            };
+            assert_eq!(
+                format_tray_title(false, true, &templated, Some(&snapshot)),
+                format!("{}=10%", codex_label.unwrap_or("CX"))
+            );
+        }
+    }
+
+    #[test]
+    fn ascii_title_uses_pipe_separator_between_providers() {
+        let snapshot = UsageSnapshotBundle {
+            claude: make_claude_ok_bundle(25.0).claude,
+            codex: make_codex_ok_bundle(10.0).codex,
+            offline: false,
+            claude_orgs: vec![],
+        };
+        let ascii = TrayOptions {
+            ascii_title: true,
+            ..TrayOptions::default()
+        };
+        assert_eq!(
+            format_tray_title(true, true, &ascii, Some(&snapshot)),
+            "CL 25% | CX 10%"
+        );
+        assert_eq!(
+            format_tray_title(true, true, &TrayOptions::default(), Some(&snapshot)),
+            "CL 25% · CX 10%"
+        );
+        assert_eq!(
+            format_tray_title(true, false, &ascii, Some(&snapshot)),
+            "CL 25%"
+        );
+        assert_eq!(
+            format_tray_title(false, true, &ascii, Some(&snapshot)),
+            "CX 10%"
+        );
+    }
+
+    #[test]
+    fn title_template_substitutes_tokens() {
+        let snapshot = UsageSnapshotBundle {
+            claude: make_claude_ok_bundle(25.0).claude,
+            codex: make_codex_ok_bundle(10.0).codex,
+            offline: false,
+            claude_orgs: vec![],
+        };
+        let with_template = |template: &str| TrayOptions {
+            title_template: Some(template.to_string()),
+            ..TrayOptions::default()
+        };
+        assert_eq!(
+            format_tray_title(
+                true,
+                true,
+                &with_template("{claude_label}{claude_session} {codex_label}{codex_weekly}"),
+                Some(&snapshot)
+            ),
+            "CL25% CX30%"
+        );
+        assert_eq!(
+            format_tray_title(
+                true,
+                false,
+                &with_template("Claude {claude_session}"),
+                Some(&snapshot)
+            ),
+            "Claude 25%"
+        );
+    }
+
+    #[test]
+    fn title_template_uses_placeholders_for_missing_data() {
+        let options = TrayOptions {
+            title_template: Some("{claude_session}/{claude_weekly} {codex_session}".to_string()),
+            ..TrayOptions::default()
+        };
+        let snapshot = make_claude_ok_bundle(25.0);
+        assert_eq!(
+            format_tray_title(true, true, &options, Some(&snapshot)),
+            "25%/30% --%"
+        );
+        assert_eq!(format_tray_title(true, true, &options, None), "--%/--% --%");
+    }
+
+    #[test]
+    fn title_template_supports_remaining_and_symbol_tokens() {
+        let options = TrayOptions {
+            title_template: Some(
+                "{symbol}C{claude_session_remaining} left, wk {claude_weekly_remaining} · X{codex_session}"
+                    .to_string(),
+            ),
+            ..TrayOptions::default()
+        };
+        let snapshot = UsageSnapshotBundle {
+            claude: make_claude_ok_bundle(75.0).claude,
+            codex: Some(CodexUsageSnapshot::Error {
+                last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+                error_message: None,
+            }),
+            offline: false,
+            claude_orgs: vec![],
+        };
+        assert_eq!(
+            format_tray_title(true, true, &options, Some(&snapshot)),
+            "‼C25% left, wk 70% · X--%"
+        );
+
+        let errors = UsageSnapshotBundle {
+            claude: Some(ClaudeUsageSnapshot::Error {
+                organization_id: None,
+                error_message: None,
+                last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+            }),
+            codex: None,
+            offline: false,
+            claude_orgs: vec![],
+        };
+        assert_eq!(
+            format_tray_title(true, false, &options, Some(&errors)),
+            "C--% left, wk --% · X--%"
+        );
+    }
+
+    #[test]
+    fn title_template_keeps_unknown_tokens_and_malformed_braces() {
+        let options = TrayOptions::default();
+        let value = |token: &str| {
+            title_template_value(token, &options, Some(&make_claude_ok_bundle(25.0)), "")
+        };
+        assert_eq!(
+            render_title_template("{nope} {claude_session}", value),
+            "{nope} 25%"
+        );
+        assert_eq!(render_title_template("{{claude_session}", value), "{25%");
+        assert_eq!(
+            render_title_template("CL {claude_session", value),
+            "CL {claude_session"
+        );
+        assert_eq!(render_title_template("}{}", value), "}{}");
+
+        // Blank templates fall back to the built-in layout.
+        let blank = TrayOptions {
+            title_template: Some("   ".to_string()),
+            ..TrayOptions::default()
+        };
+        assert_eq!(
+            format_tray_title(true, false, &blank, Some(&make_claude_ok_bundle(25.0))),
+            "CL 25%"
+        );
+    }
+
     #[test]
     fn usage_level_in_dual_mode_uses_max_severity() {
         let snapshot = UsageSnapshotBundle {
             claude: make_claude_ok_bundle(10.0).claude,
             codex: make_codex_ok_bundle(95.0).codex,
+            offline: false,
+            claude_orgs: vec![],
+        };
+        assert_eq!(
+            usage_level(true, true, &TrayOptions::default(), Some(&snapshot)),
+            2
+        );
+    }
+
+    #[test]
+    fn format_peak_suffix_only_shows_when_above_current() {
+        assert_eq!(format_peak_suffix(42.0, Some(78.0)), " (peak 78%)");
+        assert_eq!(format_peak_suffix(78.2, Some(78.0)), "");
+        assert_eq!(format_peak_suffix(42.0, None), "");
+    }
+
+    #[test]
+    fn codex_first_order_applies_to_title_and_menu_sections() {
+        let snapshot = UsageSnapshotBundle {
+            claude: make_claude_ok_bundle(25.0).claude,
+            codex: make_codex_ok_bundle(10.0).codex,
+            offline: false,
+            claude_orgs: vec![],
+        };
+        let options = TrayOptions {
+            provider_order: ProviderOrder::CodexFirst,
+            ..TrayOptions::default()
+        };
+        assert_eq!(
+            format_tray_title(true, true, &options, Some(&snapshot)),
+            "CX 10% · CL 25%"
+        );
+        assert_eq!(
+            ordered_sections(ProviderOrder::CodexFirst),
+            [ProviderSection::Codex, ProviderSection::Claude]
+        );
+        assert_eq!(
+            ordered_sections(ProviderOrder::ClaudeFirst),
+            [ProviderSection::Claude, ProviderSection::Codex]
+        );
+    }
+
+    #[test]
+    fn usage_level_colors_rate_limited_per_setting() {
+        let limited = UsageSnapshotBundle {
+            claude: Some(ClaudeUsageSnapshot::RateLimited {
+                organization_id: None,
+                error_message: None,
+                last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+                retry_after_seconds: None,
+            }),
+            codex: make_codex_ok_bundle(10.0).codex,
+            offline: false,
+            claude_orgs: vec![],
+        };
+        let red = TrayOptions {
+            rate_limited_red: true,
+            ..TrayOptions::default()
+        };
+        assert_eq!(usage_level(true, true, &red, Some(&limited)), 2);
+        assert_eq!(
+            usage_level(true, false, &TrayOptions::default(), Some(&limited)),
+            -1
+        );
+        // Untracked providers never force red.
+        assert_eq!(usage_level(false, true, &red, Some(&limited)), 0);
+    }
+
+    #[test]
+    fn cjk_locale_formats_are_non_empty() {
+        let local = DateTime::parse_from_rfc3339(sample_rfc3339_utc())
+            .unwrap()
+            .with_timezone(&Local);
+        for tag in ["ja_JP", "zh_CN", "ko_KR"] {
+            let locale: Locale = normalize_locale_tag(tag).parse().unwrap();
+            let short = format_reset_at_short_in(&local, locale);
+            assert!(short.chars().any(|c| c.is_ascii_digit()), "{tag}: {short}");
+            let full = format_datetime_full_in(&local, locale);
+            assert!(full.chars().any(|c| c.is_ascii_digit()), "{tag}: {full}");
+        }
+    }
+
+    #[test]
+    fn separator_only_localized_output_is_rejected() {
+        assert_eq!(usable_localized(" / - ".to_string()), None);
+        assert_eq!(usable_localized(String::new()), None);
+        assert_eq!(
+            usable_localized("1月7日".to_string()).as_deref(),
+            Some("1月7日")
+        );
+    }
+
+    #[test]
+    fn colorblind_mode_appends_symbol_per_level() {
+        let options = TrayOptions {
+            colorblind_mode: ColorblindMode::Symbols,
+            ..TrayOptions::default()
+        };
+        for (percent, expected) in [(25.0, "CL 25% ✓"), (60.0, "CL 60% !"), (90.0, "CL 90% ‼")]
+        {
+            let snapshot = make_claude_ok_bundle(percent);
+            let level = usage_level(true, false, &options, Some(&snapshot));
+            let title = format_tray_title(true, false, &options, Some(&snapshot));
+            assert_eq!(with_level_symbol(title, level, &options), expected);
+        }
+
+        // Unknown level and mode off keep the plain title.
+        assert_eq!(
+            with_level_symbol("CL --%".to_string(), -1, &options),
+            "CL --%"
+        );
+        assert_eq!(
+            with_level_symbol("CL 90%".to_string(), 2, &TrayOptions::default()),
+            "CL 90%"
+        );
+    }
+
+    #[test]
+    fn colorblind_symbols_are_configurable() {
+        let options = TrayOptions {
+            colorblind_mode: ColorblindMode::SymbolsOnly,
+            level_symbols: LevelSymbols {
+                ok: "o".to_string(),
+                warn: "  ".to_string(),
+                critical: "X".to_string(),
+            },
+            ..TrayOptions::default()
+        };
+        assert_eq!(
+            with_level_symbol("CL 90%".to_string(), 2, &options),
+            "CL 90% X"
+        );
+        assert_eq!(
+            with_level_symbol("CL 60%".to_string(), 1, &options),
+            "CL 60%"
+        );
+    }
+
+    fn claude_org_ok(organization_id: &str, weekly_percent: Option<f64>) -> ClaudeUsageSnapshot {
+        ClaudeUsageSnapshot::Ok {
+            organization_id: organization_id.to_string(),
+            session_percent: 10.0,
+            session_resets_at: None,
+            weekly_percent,
+            weekly_resets_at: None,
+            models: vec![],
+            session_models: vec![],
+            last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+            provider_timestamp: None,
+        }
+    }
+
+    #[test]
+    fn aggregate_claude_weekly_max_and_avg_across_orgs() {
+        let orgs = [
+            claude_org_ok("org-a", Some(30.0)),
+            claude_org_ok("org-b", Some(70.0)),
+        ];
+        assert_eq!(
+            aggregate_claude_weekly(&orgs, WeeklyAggregate::Max),
+            Some(70.0)
+        );
+        assert_eq!(
+            aggregate_claude_weekly(&orgs, WeeklyAggregate::Avg),
+            Some(50.0)
+        );
+    }
+
+    #[test]
+    fn aggregate_claude_weekly_skips_unknown_and_errors() {
+        let orgs = [
+            claude_org_ok("org-a", None),
+            ClaudeUsageSnapshot::Error {
+                organization_id: Some("org-b".to_string()),
+                error_message: None,
+                last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+            },
+            claude_org_ok("org-c", Some(40.0)),
+        ];
+        assert_eq!(
+            aggregate_claude_weekly(&orgs, WeeklyAggregate::Avg),
+            Some(40.0)
+        );
+        assert_eq!(
+            aggregate_claude_weekly(&orgs[..2], WeeklyAggregate::Max),
+            None
+        );
+    }
+
+    #[test]
+    fn window_pacing_compares_time_and_quota_progress() {
+        let resets_at = "2026-01-07T05:00:00Z";
+        let reset_ms = DateTime::parse_from_rfc3339(resets_at)
+            .unwrap()
+            .timestamp_millis();
+        // Two hours into a five-hour session window: 40% of the time has elapsed.
+        let now = reset_ms - 3 * 60 * 60 * 1000;
+
+        let ahead = window_pacing(55.0, Some(resets_at), SESSION_WINDOW_MS, now).unwrap();
+        assert_eq!(ahead.time_percent, 40.0);
+        assert!(ahead.burning_faster());
+        assert_eq!(
+            format_pacing_line(ahead),
+            "Pace: 40% of time, 55% of quota (burning faster than the clock)"
+        );
+
+        let behind = window_pacing(30.0, Some(resets_at), SESSION_WINDOW_MS, now).unwrap();
+        assert!(!behind.burning_faster());
+        assert_eq!(
+            format_pacing_line(behind),
+            "Pace: 40% of time, 30% of quota"
+        );
+
+        assert_eq!(
+            window_pacing(30.0, Some(resets_at), SESSION_WINDOW_MS, reset_ms),
+            None
+        );
+        assert_eq!(window_pacing(30.0, None, SESSION_WINDOW_MS, now), None);
+    }
+
+    #[test]
+    fn time_to_limit_reads_in_minutes_then_hours() {
+        assert_eq!(
+            format_time_to_limit(40 * 60_000),
+            "~40m to limit at this rate"
+        );
+        assert_eq!(format_time_to_limit(10_000), "~1m to limit at this rate");
+        assert_eq!(
+            format_time_to_limit(125 * 60_000),
+            "~2h 5m to limit at this rate"
+        );
+    }
+
+    #[test]
+    fn clipboard_summary_lists_tracked_providers_with_full_times() {
+        let snapshot = UsageSnapshotBundle {
+            claude: make_claude_ok_bundle(25.0).claude,
+            codex: make_codex_ok_bundle(40.0).codex,
+            offline: false,
+            claude_orgs: vec![],
         };
-        assert_eq!(usage_level(true, true, Some(&snapshot)), 2);
+        let options = TrayOptions::default();
+        let full = format_datetime_full;
+        assert_eq!(
+            clipboard_summary(true, false, &options, Some(&snapshot)),
+            format!(
+                "Claude\nSession: 25% (resets {})\nWeekly: 30% (resets {})\nLast updated: {}",
+                full("2026-01-07T05:00:00Z"),
+                full("2026-01-13T00:00:00Z"),
+                full("2026-01-06T22:59:31Z"),
+            )
+        );
+
+        let both = clipboard_summary(true, true, &options, Some(&snapshot));
+        assert!(both.starts_with("Claude\n"));
+        assert!(both.contains("\n\nCodex\nSession: 40%"));
+
+        assert_eq!(
+            clipboard_summary(true, true, &options, None),
+            "Claudometer: no usage data yet"
+        );
+        assert_eq!(
+            clipboard_summary(false, false, &options, Some(&snapshot)),
+            "Claudometer: no usage data yet"
+        );
     }
 }
@@ -1,27 +1,55 @@
-use super::formatters::{format_datetime_full, format_percent, format_reset_at_short};
-use crate::types::{ClaudeUsageSnapshot, CodexUsageSnapshot, UsageSnapshotBundle, UsageStatus};
-use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use super::formatters::{
+    format_datetime_full, format_last_updated, format_pacing_line, format_peak_suffix,
+    format_percent, format_reset_at_short, format_stale_notice, format_time_to_limit,
+    ordered_sections, pause_menu_label, snooze_menu_label, top_models, visible_models,
+    window_pacing, ProviderSection, SESSION_WINDOW_MS, WEEKLY_WINDOW_MS,
+};
+use crate::provider_view::{CLAUDE_PROVIDER_KEY, CODEX_PROVIDER_KEY};
+use crate::settings::debug_mode_enabled;
+use crate::staleness::now_unix_ms;
+use crate::types::{
+    ClaudeOrgSnapshot, ClaudeUsageSnapshot, CodexUsageSnapshot, UsageSnapshotBundle, UsageStatus,
+};
+use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
 use tauri::{AppHandle, Runtime};
 
 use super::{
-    ITEM_CHECK_UPDATES, ITEM_DEBUG_BUMP_RESETS_AT, ITEM_DEBUG_CLEAR_SIMULATION,
-    ITEM_DEBUG_SET_BELOW_LIMIT, ITEM_DEBUG_SET_NEAR_LIMIT, ITEM_OPEN_SETTINGS, ITEM_QUIT,
-    ITEM_REFRESH_NOW,
+    select_organization_item_id, TrayExtras, TrayOptions, ITEM_CHECK_UPDATES, ITEM_COPY_USAGE,
+    ITEM_DEBUG_BUMP_RESETS_AT, ITEM_DEBUG_CLEAR_SIMULATION, ITEM_DEBUG_SET_BELOW_LIMIT,
+    ITEM_DEBUG_SET_NEAR_LIMIT, ITEM_OPEN_CLAUDE_USAGE_PAGE, ITEM_OPEN_CODEX_USAGE_PAGE,
+    ITEM_OPEN_SETTINGS, ITEM_QUIT, ITEM_REFRESH_NOW, ITEM_SAVE_USAGE_REPORT,
+    ITEM_SNOOZE_NOTIFICATIONS, ITEM_TOGGLE_PAUSED,
 };
 
-fn debug_menu_enabled() -> bool {
-    matches!(
-        std::env::var("CLAUDOMETER_DEBUG").as_deref(),
-        Ok("1") | Ok("true") | Ok("yes") | Ok("on")
-    )
-}
-
 pub(super) fn build_menu<R: Runtime>(
     app: &AppHandle<R>,
     track_claude: bool,
     track_codex: bool,
+    options: &TrayOptions,
+    extras: &TrayExtras,
     snapshot: Option<&UsageSnapshotBundle>,
 ) -> tauri::Result<Menu<R>> {
+    let peaks = &extras.peaks;
+    let now_ms = now_unix_ms();
+    // Optional "Pace: …" line under a session/weekly item.
+    let pacing_line = |percent: Option<f64>, resets_at: Option<&str>, window_ms: i64| {
+        if !options.show_window_pacing {
+            return None;
+        }
+        window_pacing(percent?, resets_at, window_ms, now_ms).map(format_pacing_line)
+    };
+    // "~40m to limit at this rate" under the pacing line, when the window is climbing.
+    let limit_line = |provider: &str, window: &str| {
+        if !options.show_window_pacing {
+            return None;
+        }
+        extras
+            .burn_rates
+            .iter()
+            .find(|rate| rate.provider == provider && rate.window == window)?
+            .ms_to_limit
+            .map(format_time_to_limit)
+    };
     fn status_label_claude(status: UsageStatus) -> &'static str {
         match status {
             UsageStatus::Ok => "ok",
@@ -29,6 +57,7 @@ pub(super) fn build_menu<R: Runtime>(
             UsageStatus::RateLimited => "rate limited",
             UsageStatus::Error => "error",
             UsageStatus::MissingKey => "needs session key",
+            UsageStatus::Offline => "offline",
         }
     }
 
@@ -39,6 +68,7 @@ pub(super) fn build_menu<R: Runtime>(
             UsageStatus::RateLimited => "rate limited",
             UsageStatus::Error => "error",
             UsageStatus::MissingKey => "missing credentials",
+            UsageStatus::Offline => "offline",
         }
     }
 
@@ -69,6 +99,48 @@ pub(super) fn build_menu<R: Runtime>(
     let header = MenuItem::with_id(app, "header", header_text, false, None::<&str>)?;
 
     let refresh_now = MenuItem::with_id(app, ITEM_REFRESH_NOW, "Refresh now", true, None::<&str>)?;
+    let copy_usage = MenuItem::with_id(
+        app,
+        ITEM_COPY_USAGE,
+        "Copy usage summary",
+        true,
+        None::<&str>,
+    )?;
+    let save_usage_report = MenuItem::with_id(
+        app,
+        ITEM_SAVE_USAGE_REPORT,
+        "Save usage report…",
+        true,
+        None::<&str>,
+    )?;
+    let snooze_notifications = MenuItem::with_id(
+        app,
+        ITEM_SNOOZE_NOTIFICATIONS,
+        snooze_menu_label(extras.notifications_snoozed_for),
+        true,
+        None::<&str>,
+    )?;
+    let toggle_paused = MenuItem::with_id(
+        app,
+        ITEM_TOGGLE_PAUSED,
+        pause_menu_label(extras.polling_paused),
+        true,
+        None::<&str>,
+    )?;
+    let open_claude_usage_page = MenuItem::with_id(
+        app,
+        ITEM_OPEN_CLAUDE_USAGE_PAGE,
+        "Open Claude Usage Page",
+        true,
+        None::<&str>,
+    )?;
+    let open_codex_usage_page = MenuItem::with_id(
+        app,
+        ITEM_OPEN_CODEX_USAGE_PAGE,
+        "Open Codex Usage Page",
+        true,
+        None::<&str>,
+    )?;
     let open_settings = MenuItem::with_id(
         app,
         ITEM_OPEN_SETTINGS,
@@ -96,6 +168,11 @@ pub(super) fn build_menu<R: Runtime>(
 
     let mut refs: Vec<&dyn tauri::menu::IsMenuItem<R>> = vec![&header, &sep];
 
+    // Weekly rows (with their pace and ETA lines) and per-model rows can be turned off to keep the
+    // menu short.
+    let show_weekly = !options.hide_menu_weekly;
+    let show_models = !options.hide_menu_models;
+
     let build_claude_items =
         |snap: Option<&ClaudeUsageSnapshot>| -> tauri::Result<Vec<MenuItem<R>>> {
             let status = snap.map(|s| s.status());
@@ -111,6 +188,15 @@ pub(super) fn build_menu<R: Runtime>(
                 false,
                 None::<&str>,
             )?];
+            if let Some(stale) = extras.staleness.claude {
+                items.push(MenuItem::with_id(
+                    app,
+                    "claude_stale",
+                    format_stale_notice(stale),
+                    false,
+                    None::<&str>,
+                )?);
+            }
 
             match snap {
                 Some(ClaudeUsageSnapshot::Ok {
@@ -119,7 +205,9 @@ pub(super) fn build_menu<R: Runtime>(
                     weekly_percent,
                     weekly_resets_at,
                     models,
+                    session_models,
                     last_updated_at,
+                    provider_timestamp,
                     ..
                 }) => {
                     let session_time = session_resets_at
@@ -139,33 +227,132 @@ pub(super) fn build_menu<R: Runtime>(
                         app,
                         "claude_session",
                         format!(
-                            "Session: {}{session_time}",
-                            format_percent(Some(*session_percent))
+                            "Session: {}{session_time}{}",
+                            format_percent(Some(*session_percent)),
+                            format_peak_suffix(*session_percent, peaks.claude.session_percent)
                         ),
                         false,
                         None::<&str>,
                     )?);
-                    items.push(MenuItem::with_id(
-                        app,
-                        "claude_weekly",
-                        format!(
-                            "Weekly: {}{weekly_time}",
-                            format_percent(Some(*weekly_percent))
-                        ),
-                        false,
-                        None::<&str>,
-                    )?);
-
-                    if models.is_empty() {
+                    if let Some(pace) = pacing_line(
+                        Some(*session_percent),
+                        session_resets_at.as_deref(),
+                        SESSION_WINDOW_MS,
+                    ) {
                         items.push(MenuItem::with_id(
                             app,
-                            "claude_model_none",
-                            "Models (weekly): (none)",
+                            "claude_session_pace",
+                            pace,
                             false,
                             None::<&str>,
                         )?);
-                    } else {
-                        for (idx, m) in models.iter().enumerate() {
+                    }
+                    if let Some(eta) = limit_line(CLAUDE_PROVIDER_KEY, "session") {
+                        items.push(MenuItem::with_id(
+                            app,
+                            "claude_session_eta",
+                            eta,
+                            false,
+                            None::<&str>,
+                        )?);
+                    }
+                    if show_weekly {
+                        items.push(MenuItem::with_id(
+                            app,
+                            "claude_weekly",
+                            format!(
+                                "Weekly: {}{weekly_time}{}",
+                                format_percent(*weekly_percent),
+                                weekly_percent
+                                    .map(|w| format_peak_suffix(w, peaks.claude.weekly_percent))
+                                    .unwrap_or_default()
+                            ),
+                            false,
+                            None::<&str>,
+                        )?);
+                        if let Some(pace) = pacing_line(
+                            *weekly_percent,
+                            weekly_resets_at.as_deref(),
+                            WEEKLY_WINDOW_MS,
+                        ) {
+                            items.push(MenuItem::with_id(
+                                app,
+                                "claude_weekly_pace",
+                                pace,
+                                false,
+                                None::<&str>,
+                            )?);
+                        }
+                        if let Some(eta) = limit_line(CLAUDE_PROVIDER_KEY, "weekly") {
+                            items.push(MenuItem::with_id(
+                                app,
+                                "claude_weekly_eta",
+                                eta,
+                                false,
+                                None::<&str>,
+                            )?);
+                        }
+                    }
+
+                    if show_models {
+                        let (shown, more) = top_models(
+                            visible_models(models, options.hide_zero_models),
+                            options.max_menu_models,
+                        );
+                        if models.is_empty() {
+                            items.push(MenuItem::with_id(
+                                app,
+                                "claude_model_none",
+                                "Models (weekly): (none)",
+                                false,
+                                None::<&str>,
+                            )?);
+                        } else if shown.is_empty() {
+                            items.push(MenuItem::with_id(
+                                app,
+                                "claude_model_none",
+                                "Models (weekly): (none active)",
+                                false,
+                                None::<&str>,
+                            )?);
+                        } else {
+                            for (idx, m) in shown.into_iter().enumerate() {
+                                let model_time = m
+                                    .resets_at
+                                    .as_deref()
+                                    .and_then(format_reset_at_short)
+                                    .filter(|t| !t.is_empty())
+                                    .map(|t| format!(" (resets {t})"))
+                                    .unwrap_or_default();
+                                items.push(MenuItem::with_id(
+                                    app,
+                                    format!("claude_model_{idx}"),
+                                    format!(
+                                        "{} (weekly): {}{model_time}",
+                                        m.name,
+                                        format_percent(Some(m.percent))
+                                    ),
+                                    false,
+                                    None::<&str>,
+                                )?);
+                            }
+                            if more > 0 {
+                                items.push(MenuItem::with_id(
+                                    app,
+                                    "claude_model_more",
+                                    format!("+{more} more"),
+                                    false,
+                                    None::<&str>,
+                                )?);
+                            }
+                        }
+
+                        // Session buckets are rare, so the group only appears when the API sends one.
+                        let (shown, more) = top_models(
+                            visible_models(session_models, options.hide_zero_models),
+                            options.max_menu_models,
+                        );
+                        for (idx, m) in shown.into_iter().enumerate() {
                             let model_time = m
                                 .resets_at
                                 .as_deref()
@@ -175,9 +362,9 @@ pub(super) fn build_menu<R: Runtime>(
                                 .unwrap_or_default();
                             items.push(MenuItem::with_id(
                                 app,
-                                format!("claude_model_{idx}"),
+                                format!("claude_session_model_{idx}"),
                                 format!(
-                                    "{} (weekly): {}{model_time}",
+                                    "{} (session): {}{model_time}",
                                     m.name,
                                     format_percent(Some(m.percent))
                                 ),
@@ -185,12 +372,35 @@ pub(super) fn build_menu<R: Runtime>(
                                 None::<&str>,
                             )?);
                         }
+                        if more > 0 {
+                            items.push(MenuItem::with_id(
+                                app,
+                                "claude_session_model_more",
+                                format!("+{more} more"),
+                                false,
+                                None::<&str>,
+                            )?);
+                        }
+                    }
+
+                    if let Some(notice) = &extras.claude_org_notice {
+                        items.push(MenuItem::with_id(
+                            app,
+                            "claude_org_notice",
+                            notice,
+                            false,
+                            None::<&str>,
+                        )?);
                     }
 
                     items.push(MenuItem::with_id(
                         app,
                         "claude_last_updated",
-                        format!("Last updated: {}", format_datetime_full(last_updated_at)),
+                        format_last_updated(
+                            last_updated_at,
+                            provider_timestamp.as_deref(),
+                            options.last_updated_display,
+                        ),
                         false,
                         None::<&str>,
                     )?);
@@ -200,7 +410,8 @@ pub(super) fn build_menu<R: Runtime>(
                         ClaudeUsageSnapshot::Unauthorized { error_message, .. }
                         | ClaudeUsageSnapshot::RateLimited { error_message, .. }
                         | ClaudeUsageSnapshot::Error { error_message, .. }
-                        | ClaudeUsageSnapshot::MissingKey { error_message, .. } => {
+                        | ClaudeUsageSnapshot::MissingKey { error_message, .. }
+                        | ClaudeUsageSnapshot::Offline { error_message, .. } => {
                             error_message.clone().unwrap_or_default()
                         }
                         _ => String::new(),
@@ -213,20 +424,24 @@ pub(super) fn build_menu<R: Runtime>(
                         false,
                         None::<&str>,
                     )?);
-                    items.push(MenuItem::with_id(
-                        app,
-                        "claude_weekly",
-                        "Weekly: --%",
-                        false,
-                        None::<&str>,
-                    )?);
-                    items.push(MenuItem::with_id(
-                        app,
-                        "claude_model_placeholder",
-                        "Models (weekly): --%",
-                        false,
-                        None::<&str>,
-                    )?);
+                    if show_weekly {
+                        items.push(MenuItem::with_id(
+                            app,
+                            "claude_weekly",
+                            "Weekly: --%",
+                            false,
+                            None::<&str>,
+                        )?);
+                    }
+                    if show_models {
+                        items.push(MenuItem::with_id(
+                            app,
+                            "claude_model_placeholder",
+                            "Models (weekly): --%",
+                            false,
+                            None::<&str>,
+                        )?);
+                    }
                     if !error_message.trim().is_empty() {
                         items.push(MenuItem::with_id(
                             app,
@@ -255,20 +470,24 @@ pub(super) fn build_menu<R: Runtime>(
                         false,
                         None::<&str>,
                     )?);
-                    items.push(MenuItem::with_id(
-                        app,
-                        "claude_weekly",
-                        "Weekly: --%",
-                        false,
-                        None::<&str>,
-                    )?);
-                    items.push(MenuItem::with_id(
-                        app,
-                        "claude_model_placeholder",
-                        "Models (weekly): --%",
-                        false,
-                        None::<&str>,
-                    )?);
+                    if show_weekly {
+                        items.push(MenuItem::with_id(
+                            app,
+                            "claude_weekly",
+                            "Weekly: --%",
+                            false,
+                            None::<&str>,
+                        )?);
+                    }
+                    if show_models {
+                        items.push(MenuItem::with_id(
+                            app,
+                            "claude_model_placeholder",
+                            "Models (weekly): --%",
+                            false,
+                            None::<&str>,
+                        )?);
+                    }
                     items.push(MenuItem::with_id(
                         app,
                         "claude_last_updated",
@@ -282,6 +501,76 @@ pub(super) fn build_menu<R: Runtime>(
             Ok(items)
         };
 
+    // A compact section (header, session, weekly) per other tracked organization.
+    let build_claude_org_items = |orgs: &[ClaudeOrgSnapshot]| -> tauri::Result<Vec<MenuItem<R>>> {
+        let mut items: Vec<MenuItem<R>> = Vec::new();
+        for (idx, org) in orgs.iter().enumerate() {
+            let name = org
+                .organization
+                .name
+                .as_deref()
+                .filter(|n| !n.trim().is_empty())
+                .unwrap_or(&org.organization.id);
+            let label = match org.snapshot.status() {
+                UsageStatus::Ok => format!("Claude · {name}"),
+                st => format!("Claude · {name} ({})", status_label_claude(st)),
+            };
+            items.push(MenuItem::with_id(
+                app,
+                format!("claude_org_{idx}_header"),
+                label,
+                false,
+                None::<&str>,
+            )?);
+            let (session, weekly) = match &org.snapshot {
+                ClaudeUsageSnapshot::Ok {
+                    session_percent,
+                    session_resets_at,
+                    weekly_percent,
+                    weekly_resets_at,
+                    ..
+                } => {
+                    let reset_suffix = |resets_at: &Option<String>| {
+                        resets_at
+                            .as_deref()
+                            .and_then(format_reset_at_short)
+                            .filter(|t| !t.is_empty())
+                            .map(|t| format!(" (resets {t})"))
+                            .unwrap_or_default()
+                    };
+                    (
+                        format!(
+                            "Session: {}{}",
+                            format_percent(Some(*session_percent)),
+                            reset_suffix(session_resets_at)
+                        ),
+                        format!(
+                            "Weekly: {}{}",
+                            format_percent(*weekly_percent),
+                            reset_suffix(weekly_resets_at)
+                        ),
+                    )
+                }
+                _ => ("Session: --%".to_string(), "Weekly: --%".to_string()),
+            };
+            items.push(MenuItem::with_id(
+                app,
+                format!("claude_org_{idx}_session"),
+                session,
+                false,
+                None::<&str>,
+            )?);
+            items.push(MenuItem::with_id(
+                app,
+                format!("claude_org_{idx}_weekly"),
+                weekly,
+                false,
+                None::<&str>,
+            )?);
+        }
+        Ok(items)
+    };
+
     let build_codex_items = |snap: Option<&CodexUsageSnapshot>| -> tauri::Result<Vec<MenuItem<R>>> {
         let status = snap.map(|s| s.status());
         let label = match status {
@@ -296,6 +585,15 @@ pub(super) fn build_menu<R: Runtime>(
             false,
             None::<&str>,
         )?];
+        if let Some(stale) = extras.staleness.codex {
+            items.push(MenuItem::with_id(
+                app,
+                "codex_stale",
+                format_stale_notice(stale),
+                false,
+                None::<&str>,
+            )?);
+        }
 
         match snap {
             Some(CodexUsageSnapshot::Ok {
@@ -304,6 +602,7 @@ pub(super) fn build_menu<R: Runtime>(
                 weekly_percent,
                 weekly_resets_at,
                 last_updated_at,
+                provider_timestamp,
             }) => {
                 let session_time = session_resets_at
                     .as_deref()
@@ -322,26 +621,82 @@ pub(super) fn build_menu<R: Runtime>(
                     app,
                     "codex_session",
                     format!(
-                        "Session: {}{session_time}",
-                        format_percent(Some(*session_percent))
-                    ),
-                    false,
-                    None::<&str>,
-                )?);
-                items.push(MenuItem::with_id(
-                    app,
-                    "codex_weekly",
-                    format!(
-                        "Weekly: {}{weekly_time}",
-                        format_percent(Some(*weekly_percent))
+                        "Session: {}{session_time}{}",
+                        format_percent(Some(*session_percent)),
+                        format_peak_suffix(*session_percent, peaks.codex.session_percent)
                     ),
                     false,
                     None::<&str>,
                 )?);
+                if let Some(pace) = pacing_line(
+                    Some(*session_percent),
+                    session_resets_at.as_deref(),
+                    SESSION_WINDOW_MS,
+                ) {
+                    items.push(MenuItem::with_id(
+                        app,
+                        "codex_session_pace",
+                        pace,
+                        false,
+                        None::<&str>,
+                    )?);
+                }
+                if let Some(eta) = limit_line(CODEX_PROVIDER_KEY, "session") {
+                    items.push(MenuItem::with_id(
+                        app,
+                        "codex_session_eta",
+                        eta,
+                        false,
+                        None::<&str>,
+                    )?);
+                }
+                if show_weekly {
+                    items.push(MenuItem::with_id(
+                        app,
+                        "codex_weekly",
+                        match weekly_percent {
+                            Some(weekly) => format!(
+                                "Weekly: {}{weekly_time}{}",
+                                format_percent(Some(*weekly)),
+                                format_peak_suffix(*weekly, peaks.codex.weekly_percent)
+                            ),
+                            None => "Weekly: n/a".to_string(),
+                        },
+                        false,
+                        None::<&str>,
+                    )?);
+                    if let Some(pace) = pacing_line(
+                        *weekly_percent,
+                        weekly_resets_at.as_deref(),
+                        WEEKLY_WINDOW_MS,
+                    ) {
+                        items.push(MenuItem::with_id(
+                            app,
+                            "codex_weekly_pace",
+                            pace,
+                            false,
+                            None::<&str>,
+                        )?);
+                    }
+                    if let Some(eta) = limit_line(CODEX_PROVIDER_KEY, "weekly") {
+                        items.push(MenuItem::with_id(
+                            app,
+                            "codex_weekly_eta",
+                            eta,
+                            false,
+                            None::<&str>,
+                        )?);
+                    }
+                }
+
                 items.push(MenuItem::with_id(
                     app,
                     "codex_last_updated",
-                    format!("Last updated: {}", format_datetime_full(last_updated_at)),
+                    format_last_updated(
+                        last_updated_at,
+                        provider_timestamp.as_deref(),
+                        options.last_updated_display,
+                    ),
                     false,
                     None::<&str>,
                 )?);
@@ -351,7 +706,8 @@ pub(super) fn build_menu<R: Runtime>(
                     CodexUsageSnapshot::Unauthorized { error_message, .. }
                     | CodexUsageSnapshot::RateLimited { error_message, .. }
                     | CodexUsageSnapshot::Error { error_message, .. }
-                    | CodexUsageSnapshot::MissingKey { error_message, .. } => {
+                    | CodexUsageSnapshot::MissingKey { error_message, .. }
+                    | CodexUsageSnapshot::Offline { error_message, .. } => {
                         error_message.clone().unwrap_or_default()
                     }
                     _ => String::new(),
@@ -363,13 +719,15 @@ pub(super) fn build_menu<R: Runtime>(
                     false,
                     None::<&str>,
                 )?);
-                items.push(MenuItem::with_id(
-                    app,
-                    "codex_weekly",
-                    "Weekly: --%",
-                    false,
-                    None::<&str>,
-                )?);
+                if show_weekly {
+                    items.push(MenuItem::with_id(
+                        app,
+                        "codex_weekly",
+                        "Weekly: --%",
+                        false,
+                        None::<&str>,
+                    )?);
+                }
                 items.push(MenuItem::with_id(
                     app,
                     "codex_error",
@@ -399,13 +757,15 @@ pub(super) fn build_menu<R: Runtime>(
                     false,
                     None::<&str>,
                 )?);
-                items.push(MenuItem::with_id(
-                    app,
-                    "codex_weekly",
-                    "Weekly: --%",
-                    false,
-                    None::<&str>,
-                )?);
+                if show_weekly {
+                    items.push(MenuItem::with_id(
+                        app,
+                        "codex_weekly",
+                        "Weekly: --%",
+                        false,
+                        None::<&str>,
+                    )?);
+                }
                 items.push(MenuItem::with_id(
                     app,
                     "codex_last_updated",
@@ -426,15 +786,51 @@ pub(super) fn build_menu<R: Runtime>(
         None
     };
 
-    if let Some(claude_items) = &claude_items {
-        for item in claude_items {
-            refs.push(item);
-        }
-    }
+    let claude_org_items = match snapshot {
+        Some(snapshot) if track_claude => build_claude_org_items(&snapshot.claude_orgs)?,
+        _ => vec![],
+    };
 
-    if let Some(sep_between_sections) = &sep_between_sections {
-        refs.push(sep_between_sections);
-    }
+    // Switcher between the organizations known for the session key; the checked one is tracked.
+    let organization_items = if track_claude && extras.organizations.len() > 1 {
+        extras
+            .organizations
+            .iter()
+            .map(|org| {
+                let name = org
+                    .name
+                    .as_deref()
+                    .filter(|n| !n.trim().is_empty())
+                    .unwrap_or(&org.id);
+                let selected = extras.selected_organization_id.as_deref() == Some(org.id.as_str());
+                CheckMenuItem::with_id(
+                    app,
+                    select_organization_item_id(&org.id),
+                    name,
+                    true,
+                    selected,
+                    None::<&str>,
+                )
+            })
+            .collect::<tauri::Result<Vec<_>>>()?
+    } else {
+        vec![]
+    };
+    let organization_menu = if organization_items.is_empty() {
+        None
+    } else {
+        let items: Vec<&dyn IsMenuItem<R>> = organization_items
+            .iter()
+            .map(|item| item as &dyn IsMenuItem<R>)
+            .collect();
+        Some(Submenu::with_id_and_items(
+            app,
+            "claude_organization",
+            "Organization",
+            true,
+            &items,
+        )?)
+    };
 
     let codex_items = if track_codex {
         let codex = snapshot.and_then(|s| s.codex.as_ref());
@@ -443,13 +839,45 @@ pub(super) fn build_menu<R: Runtime>(
         None
     };
 
-    if let Some(codex_items) = &codex_items {
-        for item in codex_items {
-            refs.push(item);
+    for (idx, section) in ordered_sections(options.provider_order)
+        .into_iter()
+        .enumerate()
+    {
+        if idx > 0 {
+            if let Some(sep_between_sections) = &sep_between_sections {
+                refs.push(sep_between_sections);
+            }
+        }
+        let items = match section {
+            ProviderSection::Claude => &claude_items,
+            ProviderSection::Codex => &codex_items,
+        };
+        if let Some(items) = items {
+            for item in items {
+                refs.push(item);
+            }
+        }
+        if section == ProviderSection::Claude {
+            for item in &claude_org_items {
+                refs.push(item);
+            }
+            if let Some(organization_menu) = &organization_menu {
+                refs.push(organization_menu);
+            }
         }
     }
     refs.push(&sep_before_actions);
     refs.push(&refresh_now);
+    refs.push(&copy_usage);
+    refs.push(&save_usage_report);
+    refs.push(&snooze_notifications);
+    refs.push(&toggle_paused);
+    if track_claude {
+        refs.push(&open_claude_usage_page);
+    }
+    if track_codex {
+        refs.push(&open_codex_usage_page);
+    }
     refs.push(&open_settings);
     refs.push(&check_updates);
 
@@ -483,7 +911,7 @@ pub(super) fn build_menu<R: Runtime>(
         None::<&str>,
     )?;
 
-    if debug_menu_enabled() {
+    if debug_mode_enabled() {
         refs.push(&sep_debug);
         refs.push(&debug_set_below);
         refs.push(&debug_set_near);
@@ -1,9 +1,22 @@
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+mod badge;
+mod colors;
 mod formatters;
 mod menu_builder;
 
-use crate::types::UsageSnapshotBundle;
-use tauri::tray::{TrayIcon, TrayIconBuilder};
-use tauri::{image::Image, AppHandle, Runtime};
+use crate::daily_peaks::DailyPeaks;
+use crate::staleness::ProviderStaleness;
+use crate::state::AppState;
+use crate::types::{
+    BurnRate, ClaudeOrganization, ColorblindMode, LastUpdatedDisplay, LevelSymbols, ProviderOrder,
+    TrayClickAction, TrayLevelMetric, TrayMetric, UsageSnapshotBundle, WeeklyAggregate,
+};
+#[cfg(target_os = "macos")]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::{image::Image, AppHandle, Manager as _, Runtime};
 
 #[cfg(target_os = "macos")]
 use objc2::MainThreadMarker;
@@ -12,33 +25,224 @@ use objc2_app_kit::{NSColor, NSForegroundColorAttributeName};
 #[cfg(target_os = "macos")]
 use objc2_foundation::{NSAttributedString, NSDictionary, NSString};
 
+pub use colors::{first_invalid_color, TrayPalette};
+pub(crate) use formatters::clipboard_summary;
+#[cfg(feature = "menu-export")]
+pub(crate) use formatters::summary_lines;
+
 pub const TRAY_ID: &str = "main";
 
 pub const ITEM_REFRESH_NOW: &str = "refresh_now";
+pub const ITEM_COPY_USAGE: &str = "copy_usage";
+pub const ITEM_SAVE_USAGE_REPORT: &str = "save_usage_report";
+pub const ITEM_SNOOZE_NOTIFICATIONS: &str = "snooze_notifications";
+pub const ITEM_TOGGLE_PAUSED: &str = "toggle_paused";
 pub const ITEM_OPEN_SETTINGS: &str = "open_settings";
+pub const ITEM_OPEN_CLAUDE_USAGE_PAGE: &str = "open_claude_usage_page";
+pub const ITEM_OPEN_CODEX_USAGE_PAGE: &str = "open_codex_usage_page";
 pub const ITEM_CHECK_UPDATES: &str = "check_updates";
 pub const ITEM_QUIT: &str = "quit";
 
+/// Prefix of the per-organization items in the tray's "Organization" submenu; the org id follows.
+const ITEM_SELECT_ORGANIZATION_PREFIX: &str = "select_organization:";
+
+pub fn select_organization_item_id(org_id: &str) -> String {
+    format!("{ITEM_SELECT_ORGANIZATION_PREFIX}{org_id}")
+}
+
+/// The organization id encoded in a menu item id by `select_organization_item_id`, if any.
+pub fn organization_id_from_item(item_id: &str) -> Option<&str> {
+    item_id
+        .strip_prefix(ITEM_SELECT_ORGANIZATION_PREFIX)
+        .filter(|org_id| !org_id.trim().is_empty())
+}
+
 pub const ITEM_DEBUG_SET_BELOW_LIMIT: &str = "debug_set_below_limit";
 pub const ITEM_DEBUG_SET_NEAR_LIMIT: &str = "debug_set_near_limit";
 pub const ITEM_DEBUG_BUMP_RESETS_AT: &str = "debug_bump_resets_at";
 pub const ITEM_DEBUG_CLEAR_SIMULATION: &str = "debug_clear_simulation";
 
+/// Provider usage dashboards opened from the tray unless overridden in settings.
+pub const DEFAULT_CLAUDE_USAGE_PAGE_URL: &str = "https://claude.ai/settings/usage";
+pub const DEFAULT_CODEX_USAGE_PAGE_URL: &str = "https://chatgpt.com/codex/settings/usage";
+
+/// `configured` when it is an http(s) URL, otherwise `default`; a blank or mistyped setting
+/// can't make the menu item open anything other than a web page.
+pub fn usage_page_url(configured: Option<&str>, default: &str) -> String {
+    configured
+        .map(str::trim)
+        .and_then(|url| reqwest::Url::parse(url).ok())
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+        .map_or_else(|| default.to_string(), String::from)
+}
+
+/// Session-usage cutoffs for the tray levels: below `warn_percent` is ok, up to and including
+/// `critical_percent` is warn, above it is critical.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelThresholds {
+    pub warn_percent: f64,
+    pub critical_percent: f64,
+}
+
+impl Default for LevelThresholds {
+    fn default() -> Self {
+        Self {
+            warn_percent: 50.0,
+            critical_percent: 70.0,
+        }
+    }
+}
+
+/// Display preferences that shape the tray title and menu (read from settings on each update).
+#[derive(Debug, Clone, Default)]
+pub struct TrayOptions {
+    pub hide_zero_models: bool,
+    /// Leave the weekly rows out of the menu.
+    pub hide_menu_weekly: bool,
+    /// Leave the per-model rows out of the menu.
+    pub hide_menu_models: bool,
+    /// Most model rows per group, busiest first, before a "+N more" row; 0 shows all of them.
+    pub max_menu_models: usize,
+    pub provider_order: ProviderOrder,
+    pub metric: TrayMetric,
+    /// Which window the tray color follows; by default the one(s) in the title.
+    pub level_metric: TrayLevelMetric,
+    pub click_action: TrayClickAction,
+    /// Append time until the session window resets to the title, e.g. `CL 72% (1h20m)`.
+    pub show_reset_countdown: bool,
+    /// Separate providers with ` | ` instead of ` · ` for fonts without the middle dot.
+    pub ascii_title: bool,
+    /// macOS: draw the percent as a colored badge icon instead of a text title.
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    pub icon_badge: bool,
+    /// Custom title such as `{claude_label} {claude_session}`; blank means the built-in layout.
+    pub title_template: Option<String>,
+    /// Title label for Claude; `None` for the default `CL`.
+    pub claude_label: Option<String>,
+    /// Title label for Codex; `None` for the default `CX`.
+    pub codex_label: Option<String>,
+    /// Show rate-limited providers as red instead of gray (unknown).
+    pub rate_limited_red: bool,
+    pub thresholds: LevelThresholds,
+    /// Show how far through each window's time we are next to its quota usage.
+    pub show_window_pacing: bool,
+    /// Our fetch time or the provider's own timestamp in the "last updated" line.
+    pub last_updated_display: LastUpdatedDisplay,
+    pub palette: TrayPalette,
+    pub colorblind_mode: ColorblindMode,
+    pub level_symbols: LevelSymbols,
+    /// Combination of weekly usage across tracked Claude organizations (used once more than one
+    /// org is tracked).
+    pub weekly_aggregate: WeeklyAggregate,
+}
+
+impl TrayOptions {
+    pub fn claude_label(&self) -> &str {
+        self.claude_label.as_deref().unwrap_or("CL")
+    }
+
+    pub fn codex_label(&self) -> &str {
+        self.codex_label.as_deref().unwrap_or("CX")
+    }
+}
+
+/// Transient state shown in the menu next to the snapshot (not user preferences).
+#[derive(Debug, Clone, Default)]
+pub struct TrayExtras {
+    pub peaks: DailyPeaks,
+    pub claude_org_notice: Option<String>,
+    pub staleness: ProviderStaleness,
+    /// Time left on the tray's notification snooze, if active.
+    pub notifications_snoozed_for: Option<Duration>,
+    /// Timed refreshes are paused from the tray.
+    pub polling_paused: bool,
+    /// Claude organizations offered in the tray's switcher (Web source only); fewer than two
+    /// hides it.
+    pub organizations: Vec<ClaudeOrganization>,
+    pub selected_organization_id: Option<String>,
+    /// Projected time to the limit per climbing window, shown next to the pacing lines.
+    pub burn_rates: Vec<BurnRate>,
+}
+
+/// How often to retry creating the tray after it failed (e.g. no StatusNotifier host on Linux).
+const TRAY_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A value built lazily with throttled retries; failures leave it empty instead of erroring.
+struct RetrySlot<T> {
+    value: Option<T>,
+    last_attempt: Option<Instant>,
+}
+
+impl<T: Clone> RetrySlot<T> {
+    fn empty() -> Self {
+        Self {
+            value: None,
+            last_attempt: None,
+        }
+    }
+
+    fn get_or_retry<E>(
+        &mut self,
+        now: Instant,
+        retry_interval: Duration,
+        build: impl FnOnce() -> Result<T, E>,
+    ) -> Result<Option<T>, E> {
+        if let Some(value) = &self.value {
+            return Ok(Some(value.clone()));
+        }
+        if self
+            .last_attempt
+            .is_some_and(|at| now.duration_since(at) < retry_interval)
+        {
+            return Ok(None);
+        }
+        self.last_attempt = Some(now);
+        let value = build()?;
+        self.value = Some(value.clone());
+        Ok(Some(value))
+    }
+}
+
+/// Tray handle that tolerates the tray being unavailable: the app keeps refreshing without it and
+/// creation is retried on later updates.
 pub struct TrayUi<R: Runtime> {
-    tray: TrayIcon<R>,
+    app: AppHandle<R>,
+    slot: Arc<Mutex<RetrySlot<TrayIcon<R>>>>,
+    /// Whether the badge icon is showing, so the app icon is only restored when it's turned off.
+    #[cfg(target_os = "macos")]
+    badge_shown: Arc<AtomicBool>,
 }
 
 impl<R: Runtime> Clone for TrayUi<R> {
     fn clone(&self) -> Self {
         Self {
-            tray: self.tray.clone(),
+            app: self.app.clone(),
+            slot: self.slot.clone(),
+            #[cfg(target_os = "macos")]
+            badge_shown: self.badge_shown.clone(),
         }
     }
 }
 
+fn app_icon() -> tauri::Result<Image<'static>> {
+    Image::from_bytes(include_bytes!("../../icons/icon.png"))
+}
+
+/// Tray icon showing the rounded `percent` on a circle colored for `level`. Unknown/error states
+/// (`level` -1) are a plain gray dot.
+#[cfg(target_os = "macos")]
+fn render_badge_icon(percent: Option<f64>, level: i8, palette: &TrayPalette) -> Image<'static> {
+    let pixels = if level < 0 {
+        badge::badge_pixels(None, badge::NEUTRAL_GRAY)
+    } else {
+        badge::badge_pixels(percent, palette.for_level(level))
+    };
+    Image::new_owned(pixels, badge::BADGE_SIZE, badge::BADGE_SIZE)
+}
+
 /// Set colored attributed title on macOS tray button.
 #[cfg(target_os = "macos")]
-fn set_colored_tray_title<R: Runtime>(tray: &TrayIcon<R>, title: &str, level: i8) {
+fn set_colored_tray_title<R: Runtime>(tray: &TrayIcon<R>, title: &str, rgba: colors::Rgba) {
     use objc2::rc::Retained;
     use objc2::runtime::{AnyObject, ProtocolObject};
     use objc2_foundation::NSCopying;
@@ -57,13 +261,8 @@ fn set_colored_tray_title<R: Runtime>(tray: &TrayIcon<R>, title: &str, level: i8
             return;
         };
 
-        // Create color based on level
-        let color: Retained<NSColor> = match level {
-            0 => NSColor::colorWithSRGBRed_green_blue_alpha(0.298, 0.686, 0.314, 1.0), // green #4CAF50
-            1 => NSColor::colorWithSRGBRed_green_blue_alpha(1.0, 0.596, 0.0, 1.0), // orange #FF9800
-            2 => NSColor::colorWithSRGBRed_green_blue_alpha(0.957, 0.263, 0.212, 1.0), // red #F44336
-            _ => NSColor::colorWithSRGBRed_green_blue_alpha(0.5, 0.5, 0.5, 1.0),       // gray
-        };
+        let color: Retained<NSColor> =
+            NSColor::colorWithSRGBRed_green_blue_alpha(rgba.red, rgba.green, rgba.blue, rgba.alpha);
 
         // Create attributed string with foreground color
         let ns_string = NSString::from_str(&title_owned);
@@ -99,46 +298,243 @@ fn set_colored_tray_title<R: Runtime>(tray: &TrayIcon<R>, title: &str, level: i8
     });
 }
 
-impl<R: Runtime> TrayUi<R> {
-    pub fn new(app: &AppHandle<R>) -> tauri::Result<Self> {
-        let menu = menu_builder::build_menu(app, true, true, None)?;
+/// What a left click on the tray icon does for the configured action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClickDispatch {
+    /// The OS opens the menu itself; nothing to do.
+    ShowMenu,
+    Refresh,
+    OpenSettings,
+}
 
-        let icon = Image::from_bytes(include_bytes!("../../icons/icon.png"))?;
+fn click_dispatch(action: TrayClickAction) -> ClickDispatch {
+    match action {
+        TrayClickAction::Menu => ClickDispatch::ShowMenu,
+        TrayClickAction::Refresh => ClickDispatch::Refresh,
+        // No separate dashboard window yet: the settings window shows the usage cards.
+        TrayClickAction::Settings | TrayClickAction::Dashboard => ClickDispatch::OpenSettings,
+    }
+}
 
-        let tray = TrayIconBuilder::with_id(TRAY_ID)
-            .icon(icon)
-            .menu(&menu)
-            .tooltip("Claudometer")
-            .title("CL --% · CX --%")
-            .build(app)?;
+fn handle_left_click<R: Runtime>(app: &AppHandle<R>) {
+    let Some(state) = app.try_state::<AppState<R>>() else {
+        return;
+    };
+    match click_dispatch(state.tray_click_action()) {
+        ClickDispatch::ShowMenu => {}
+        ClickDispatch::Refresh => {
+            let refresh = state.refresh.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = refresh.refresh_now().await;
+            });
+        }
+        ClickDispatch::OpenSettings => {
+            crate::windows::open_settings_window_or_report(app);
+        }
+    }
+}
 
-        Ok(Self { tray })
+fn build_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<TrayIcon<R>> {
+    let menu = menu_builder::build_menu(
+        app,
+        true,
+        true,
+        &TrayOptions::default(),
+        &TrayExtras::default(),
+        None,
+    )?;
+
+    TrayIconBuilder::with_id(TRAY_ID)
+        .icon(app_icon()?)
+        .menu(&menu)
+        .tooltip("Claudometer")
+        .title("CL --% · CX --%")
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                handle_left_click(tray.app_handle());
+            }
+        })
+        .build(app)
+}
+
+impl<R: Runtime> TrayUi<R> {
+    /// Never fails: if the tray can't be created now, the app runs without it until a retry works.
+    pub fn new(app: &AppHandle<R>) -> Self {
+        let ui = Self {
+            app: app.clone(),
+            slot: Arc::new(Mutex::new(RetrySlot::empty())),
+            #[cfg(target_os = "macos")]
+            badge_shown: Arc::new(AtomicBool::new(false)),
+        };
+        let _ = ui.tray();
+        ui
+    }
+
+    fn tray(&self) -> Option<TrayIcon<R>> {
+        let mut slot = self.slot.lock().unwrap_or_else(|e| e.into_inner());
+        match slot.get_or_retry(Instant::now(), TRAY_RETRY_INTERVAL, || {
+            build_tray(&self.app)
+        }) {
+            Ok(tray) => tray,
+            // Retried after `TRAY_RETRY_INTERVAL`.
+            Err(_) => None,
+        }
     }
 
     pub fn update_snapshot(
         &self,
         track_claude: bool,
         track_codex: bool,
+        options: &TrayOptions,
+        extras: &TrayExtras,
         snapshot: Option<&UsageSnapshotBundle>,
     ) {
-        let app = self.tray.app_handle();
-        let menu = menu_builder::build_menu(app, track_claude, track_codex, snapshot);
+        let Some(tray) = self.tray() else {
+            return;
+        };
+        let menu = menu_builder::build_menu(
+            &self.app,
+            track_claude,
+            track_codex,
+            options,
+            extras,
+            snapshot,
+        );
         if let Ok(menu) = menu {
-            let _ = self.tray.set_menu(Some(menu));
+            let _ = tray.set_menu(Some(menu));
         }
+        let _ = tray.set_show_menu_on_left_click(options.click_action == TrayClickAction::Menu);
 
-        let title = formatters::format_tray_title(track_claude, track_codex, snapshot);
-        let level = formatters::usage_level(track_claude, track_codex, snapshot);
+        let level = formatters::usage_level(track_claude, track_codex, options, snapshot);
+        let stale = formatters::title_is_stale(track_claude, track_codex, &extras.staleness);
+        let title = formatters::with_paused_marker(
+            formatters::with_stale_marker(
+                formatters::with_level_symbol(
+                    formatters::format_tray_title(track_claude, track_codex, options, snapshot),
+                    level,
+                    options,
+                ),
+                stale,
+                options,
+            ),
+            extras.polling_paused,
+            options,
+        );
 
         #[cfg(target_os = "macos")]
         {
-            set_colored_tray_title(&self.tray, &title, level);
+            let badge_was_shown = self.badge_shown.swap(options.icon_badge, Ordering::Relaxed);
+            if options.icon_badge {
+                let percent =
+                    formatters::tray_percent(track_claude, track_codex, options, snapshot);
+                let _ = tray.set_icon(Some(render_badge_icon(percent, level, &options.palette)));
+                let _ = tray.set_title(None::<&str>);
+                return;
+            }
+            if badge_was_shown {
+                if let Ok(icon) = app_icon() {
+                    let _ = tray.set_icon(Some(icon));
+                }
+            }
+            if options.colorblind_mode == ColorblindMode::SymbolsOnly {
+                let _ = tray.set_title(Some(title));
+            } else {
+                set_colored_tray_title(&tray, &title, options.palette.for_level(level));
+            }
         }
 
         #[cfg(not(target_os = "macos"))]
         {
             let _ = level;
-            let _ = self.tray.set_title(Some(title));
+            let _ = tray.set_title(Some(title));
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn organization_item_ids_round_trip() {
+        let id = select_organization_item_id("org-1:a");
+        assert_eq!(organization_id_from_item(&id), Some("org-1:a"));
+        assert_eq!(
+            organization_id_from_item(ITEM_SELECT_ORGANIZATION_PREFIX),
+            None
+        );
+        assert_eq!(organization_id_from_item("select_organization: "), None);
+        assert_eq!(organization_id_from_item(ITEM_REFRESH_NOW), None);
+        assert_eq!(organization_id_from_item("org-1"), None);
+    }
+
+    #[test]
+    fn usage_page_url_only_accepts_web_urls() {
+        let default = DEFAULT_CLAUDE_USAGE_PAGE_URL;
+        assert_eq!(usage_page_url(None, default), default);
+        assert_eq!(usage_page_url(Some(" "), default), default);
+        assert_eq!(usage_page_url(Some("file:///etc/passwd"), default), default);
+        assert_eq!(
+            usage_page_url(Some(" https://console.example.com/usage "), default),
+            "https://console.example.com/usage"
+        );
+    }
+
+    #[test]
+    fn click_actions_dispatch_to_menu_refresh_or_settings() {
+        assert_eq!(
+            click_dispatch(TrayClickAction::Menu),
+            ClickDispatch::ShowMenu
+        );
+        assert_eq!(
+            click_dispatch(TrayClickAction::Refresh),
+            ClickDispatch::Refresh
+        );
+        assert_eq!(
+            click_dispatch(TrayClickAction::Settings),
+            ClickDispatch::OpenSettings
+        );
+        assert_eq!(
+            click_dispatch(TrayClickAction::Dashboard),
+            ClickDispatch::OpenSettings
+        );
+        assert_eq!(
+            TrayClickAction::from_setting_value("bogus").unwrap_or_default(),
+            TrayClickAction::Menu
+        );
+    }
+
+    #[test]
+    fn failed_build_leaves_slot_empty_and_retries_after_interval() {
+        let interval = Duration::from_secs(60);
+        let start = Instant::now();
+        let mut slot: RetrySlot<u32> = RetrySlot::empty();
+
+        assert_eq!(
+            slot.get_or_retry(start, interval, || Err::<u32, _>("no tray host")),
+            Err("no tray host")
+        );
+        // Within the interval no rebuild is attempted and setup carries on without a tray.
+        assert_eq!(
+            slot.get_or_retry(
+                start + Duration::from_secs(5),
+                interval,
+                || -> Result<u32, &'static str> { panic!("should not retry yet") }
+            ),
+            Ok(None)
+        );
+        assert_eq!(
+            slot.get_or_retry(start + interval, interval, || Ok::<_, &str>(7)),
+            Ok(Some(7))
+        );
+        assert_eq!(
+            slot.get_or_retry(start + interval, interval, || Err::<u32, _>("unused")),
+            Ok(Some(7))
+        );
+    }
+}
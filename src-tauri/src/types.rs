@@ -9,6 +9,9 @@ pub enum UsageStatus {
     RateLimited,
     Error,
     MissingKey,
+    // The server couldn't be reached (no network, DNS failure, timeout), as opposed to `Error`
+    // for failures reported by the server or in its response.
+    Offline,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
@@ -22,7 +25,317 @@ pub enum UsageSource {
 #[serde(rename_all = "snake_case")]
 pub enum CodexUsageSource {
     Oauth,
+    /// chatgpt.com session cookie pasted into settings.
+    Web,
     Cli,
+    /// OAuth, then the web cookie, then the CLI; the first one with usable credentials wins.
+    Auto,
+}
+
+impl CodexUsageSource {
+    /// Value persisted under `KEY_CODEX_USAGE_SOURCE`.
+    pub fn as_setting_value(self) -> &'static str {
+        match self {
+            Self::Oauth => "oauth",
+            Self::Web => "web",
+            Self::Cli => "cli",
+            Self::Auto => "auto",
+        }
+    }
+
+    pub fn from_setting_value(value: &str) -> Option<Self> {
+        match value {
+            "oauth" => Some(Self::Oauth),
+            "web" => Some(Self::Web),
+            "cli" => Some(Self::Cli),
+            "auto" => Some(Self::Auto),
+            _ => None,
+        }
+    }
+}
+
+/// What to do when the selected Claude organization no longer exists upstream.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingOrgPolicy {
+    /// Switch to the first available organization (and say so).
+    AutoSwitch,
+    /// Report an error until an organization is chosen in Settings.
+    Wait,
+}
+
+impl MissingOrgPolicy {
+    /// Value persisted under `KEY_MISSING_ORG_POLICY`.
+    pub fn as_setting_value(self) -> &'static str {
+        match self {
+            Self::AutoSwitch => "auto_switch",
+            Self::Wait => "wait",
+        }
+    }
+
+    pub fn from_setting_value(value: &str) -> Option<Self> {
+        match value {
+            "auto_switch" => Some(Self::AutoSwitch),
+            "wait" => Some(Self::Wait),
+            _ => None,
+        }
+    }
+}
+
+/// Which provider is listed first in the tray title and menu.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderOrder {
+    #[default]
+    ClaudeFirst,
+    CodexFirst,
+}
+
+impl ProviderOrder {
+    /// Value persisted under `KEY_PROVIDER_ORDER`.
+    pub fn as_setting_value(self) -> &'static str {
+        match self {
+            Self::ClaudeFirst => "claude_first",
+            Self::CodexFirst => "codex_first",
+        }
+    }
+
+    pub fn from_setting_value(value: &str) -> Option<Self> {
+        match value {
+            "claude_first" => Some(Self::ClaudeFirst),
+            "codex_first" => Some(Self::CodexFirst),
+            _ => None,
+        }
+    }
+}
+
+/// Tray title colors per usage level, as `#RRGGBB` (or `#RRGGBBAA`) hex strings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TrayColors {
+    pub ok: String,
+    pub warn: String,
+    pub critical: String,
+    pub unknown: String,
+}
+
+impl Default for TrayColors {
+    fn default() -> Self {
+        Self {
+            ok: "#4CAF50".to_string(),
+            warn: "#FF9800".to_string(),
+            critical: "#F44336".to_string(),
+            unknown: "#808080".to_string(),
+        }
+    }
+}
+
+/// How weekly usage of several tracked Claude organizations is combined into one figure.
+/// (Summing percentages is meaningless, so only max/avg are offered.)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum WeeklyAggregate {
+    #[default]
+    Max,
+    Avg,
+}
+
+impl WeeklyAggregate {
+    /// Value persisted under `KEY_CLAUDE_WEEKLY_AGGREGATE`.
+    pub fn as_setting_value(self) -> &'static str {
+        match self {
+            Self::Max => "max",
+            Self::Avg => "avg",
+        }
+    }
+
+    pub fn from_setting_value(value: &str) -> Option<Self> {
+        match value {
+            "max" => Some(Self::Max),
+            "avg" => Some(Self::Avg),
+            _ => None,
+        }
+    }
+}
+
+/// What a left click on the tray icon does.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayClickAction {
+    #[default]
+    Menu,
+    Refresh,
+    Settings,
+    Dashboard,
+}
+
+impl TrayClickAction {
+    /// Value persisted under `KEY_TRAY_CLICK_ACTION`.
+    pub fn as_setting_value(self) -> &'static str {
+        match self {
+            Self::Menu => "menu",
+            Self::Refresh => "refresh",
+            Self::Settings => "settings",
+            Self::Dashboard => "dashboard",
+        }
+    }
+
+    pub fn from_setting_value(value: &str) -> Option<Self> {
+        match value {
+            "menu" => Some(Self::Menu),
+            "refresh" => Some(Self::Refresh),
+            "settings" => Some(Self::Settings),
+            "dashboard" => Some(Self::Dashboard),
+            _ => None,
+        }
+    }
+}
+
+/// Which usage window(s) the tray title shows (and the tray level is computed from).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayMetric {
+    #[default]
+    Session,
+    Weekly,
+    /// `session/weekly`, e.g. `CL 25%/80%`.
+    Both,
+}
+
+impl TrayMetric {
+    /// Value persisted under `KEY_TRAY_METRIC`.
+    pub fn as_setting_value(self) -> &'static str {
+        match self {
+            Self::Session => "session",
+            Self::Weekly => "weekly",
+            Self::Both => "both",
+        }
+    }
+
+    pub fn from_setting_value(value: &str) -> Option<Self> {
+        match value {
+            "session" => Some(Self::Session),
+            "weekly" => Some(Self::Weekly),
+            "both" => Some(Self::Both),
+            _ => None,
+        }
+    }
+}
+
+/// Which usage window decides the tray color (and the percent on the macOS badge).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayLevelMetric {
+    /// Whatever the title shows (`TrayMetric`).
+    #[default]
+    Title,
+    Session,
+    Weekly,
+    /// The higher of session and weekly.
+    Max,
+}
+
+impl TrayLevelMetric {
+    /// Value persisted under `KEY_TRAY_LEVEL_METRIC`.
+    pub fn as_setting_value(self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Session => "session",
+            Self::Weekly => "weekly",
+            Self::Max => "max",
+        }
+    }
+
+    pub fn from_setting_value(value: &str) -> Option<Self> {
+        match value {
+            "title" => Some(Self::Title),
+            "session" => Some(Self::Session),
+            "weekly" => Some(Self::Weekly),
+            "max" => Some(Self::Max),
+            _ => None,
+        }
+    }
+}
+
+/// Which time the menu's "last updated" line shows.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum LastUpdatedDisplay {
+    /// When we fetched the data.
+    #[default]
+    CheckedAt,
+    /// When the provider computed it, falling back to the fetch time if unreported.
+    DataAge,
+}
+
+impl LastUpdatedDisplay {
+    /// Value persisted under `KEY_LAST_UPDATED_DISPLAY`.
+    pub fn as_setting_value(self) -> &'static str {
+        match self {
+            Self::CheckedAt => "checked_at",
+            Self::DataAge => "data_age",
+        }
+    }
+
+    pub fn from_setting_value(value: &str) -> Option<Self> {
+        match value {
+            "checked_at" => Some(Self::CheckedAt),
+            "data_age" => Some(Self::DataAge),
+            _ => None,
+        }
+    }
+}
+
+/// How usage severity is conveyed in the tray title besides color.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorblindMode {
+    /// Color only.
+    #[default]
+    Off,
+    /// Color plus a level symbol.
+    Symbols,
+    /// Level symbol with a plain (uncolored) title.
+    SymbolsOnly,
+}
+
+impl ColorblindMode {
+    /// Value persisted under `KEY_COLORBLIND_MODE`.
+    pub fn as_setting_value(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Symbols => "symbols",
+            Self::SymbolsOnly => "symbols_only",
+        }
+    }
+
+    pub fn from_setting_value(value: &str) -> Option<Self> {
+        match value {
+            "off" => Some(Self::Off),
+            "symbols" => Some(Self::Symbols),
+            "symbols_only" => Some(Self::SymbolsOnly),
+            _ => None,
+        }
+    }
+}
+
+/// Symbols appended to the tray title per usage level in colorblind mode.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LevelSymbols {
+    pub ok: String,
+    pub warn: String,
+    pub critical: String,
+}
+
+impl Default for LevelSymbols {
+    fn default() -> Self {
+        Self {
+            ok: "✓".to_string(),
+            warn: "!".to_string(),
+            critical: "‼".to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -30,6 +343,49 @@ pub enum CodexUsageSource {
 pub struct UsageSnapshotBundle {
     pub claude: Option<ClaudeUsageSnapshot>,
     pub codex: Option<CodexUsageSnapshot>,
+    /// The latest fetch couldn't reach the network, so the provider slots show the last known
+    /// values instead of an error.
+    #[serde(default)]
+    pub offline: bool,
+    /// Usage of the other organizations when `trackAllOrgs` is on; `claude` stays the selected
+    /// organization. Empty otherwise.
+    #[serde(default)]
+    pub claude_orgs: Vec<ClaudeOrgSnapshot>,
+}
+
+/// One additional tracked Claude organization and its latest usage.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeOrgSnapshot {
+    pub organization: ClaudeOrganization,
+    pub snapshot: ClaudeUsageSnapshot,
+}
+
+/// The cached snapshot for the settings window, read without triggering a fetch.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSnapshotResponse {
+    pub snapshot: Option<UsageSnapshotBundle>,
+    pub claude_last_updated_at: Option<String>,
+    pub codex_last_updated_at: Option<String>,
+}
+
+impl UsageSnapshotResponse {
+    pub fn from_snapshot(snapshot: Option<UsageSnapshotBundle>) -> Self {
+        let claude_last_updated_at = snapshot
+            .as_ref()
+            .and_then(|s| s.claude.as_ref())
+            .map(|c| c.last_updated_at().to_string());
+        let codex_last_updated_at = snapshot
+            .as_ref()
+            .and_then(|s| s.codex.as_ref())
+            .map(|c| c.last_updated_at().to_string());
+        Self {
+            snapshot,
+            claude_last_updated_at,
+            codex_last_updated_at,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -50,14 +406,21 @@ pub enum ClaudeUsageSnapshot {
         session_percent: f64,
         #[serde(rename = "sessionResetsAt")]
         session_resets_at: Option<String>,
+        /// `None` when the weekly bucket has no utilization (unknown, not 0%).
         #[serde(rename = "weeklyPercent")]
-        weekly_percent: f64,
+        weekly_percent: Option<f64>,
         #[serde(rename = "weeklyResetsAt")]
         weekly_resets_at: Option<String>,
         #[serde(rename = "models")]
         models: Vec<ClaudeModelUsage>,
+        /// Per-model 5-hour buckets (e.g. `five_hour_opus`); usually empty.
+        #[serde(rename = "sessionModels", default)]
+        session_models: Vec<ClaudeModelUsage>,
         #[serde(rename = "lastUpdatedAt")]
         last_updated_at: String,
+        /// When the provider generated the response (its `Date` header), vs our fetch time.
+        #[serde(rename = "providerTimestamp", default)]
+        provider_timestamp: Option<String>,
     },
     Unauthorized {
         #[serde(rename = "organizationId")]
@@ -74,6 +437,10 @@ pub enum ClaudeUsageSnapshot {
         last_updated_at: String,
         #[serde(rename = "errorMessage")]
         error_message: Option<String>,
+        /// From the response's `Retry-After` header, when it sent one.
+        #[serde(rename = "retryAfterSeconds", default)]
+        #[ts(type = "number | null")]
+        retry_after_seconds: Option<u64>,
     },
     Error {
         #[serde(rename = "organizationId")]
@@ -91,6 +458,14 @@ pub enum ClaudeUsageSnapshot {
         #[serde(rename = "errorMessage")]
         error_message: Option<String>,
     },
+    Offline {
+        #[serde(rename = "organizationId")]
+        organization_id: Option<String>,
+        #[serde(rename = "lastUpdatedAt")]
+        last_updated_at: String,
+        #[serde(rename = "errorMessage")]
+        error_message: Option<String>,
+    },
 }
 
 impl ClaudeUsageSnapshot {
@@ -101,6 +476,7 @@ impl ClaudeUsageSnapshot {
             Self::RateLimited { .. } => UsageStatus::RateLimited,
             Self::Error { .. } => UsageStatus::Error,
             Self::MissingKey { .. } => UsageStatus::MissingKey,
+            Self::Offline { .. } => UsageStatus::Offline,
         }
     }
 
@@ -121,6 +497,32 @@ impl ClaudeUsageSnapshot {
             Self::MissingKey {
                 last_updated_at, ..
             } => last_updated_at,
+            Self::Offline {
+                last_updated_at, ..
+            } => last_updated_at,
+        }
+    }
+
+    /// Server-requested wait before retrying, when rate limited with a `Retry-After` header.
+    pub fn retry_after_seconds(&self) -> Option<u64> {
+        match self {
+            Self::RateLimited {
+                retry_after_seconds,
+                ..
+            } => *retry_after_seconds,
+            _ => None,
+        }
+    }
+
+    /// The failure detail, if any (always `None` for `Ok`).
+    pub fn error_message(&self) -> Option<&str> {
+        match self {
+            Self::Ok { .. } => None,
+            Self::Unauthorized { error_message, .. }
+            | Self::RateLimited { error_message, .. }
+            | Self::Error { error_message, .. }
+            | Self::MissingKey { error_message, .. }
+            | Self::Offline { error_message, .. } => error_message.as_deref(),
         }
     }
 }
@@ -133,12 +535,15 @@ pub enum CodexUsageSnapshot {
         session_percent: f64,
         #[serde(rename = "sessionResetsAt")]
         session_resets_at: Option<String>,
+        /// `None` when the account only reports a session window (e.g. some free-tier accounts).
         #[serde(rename = "weeklyPercent")]
-        weekly_percent: f64,
+        weekly_percent: Option<f64>,
         #[serde(rename = "weeklyResetsAt")]
         weekly_resets_at: Option<String>,
         #[serde(rename = "lastUpdatedAt")]
         last_updated_at: String,
+        #[serde(rename = "providerTimestamp", default)]
+        provider_timestamp: Option<String>,
     },
     Unauthorized {
         #[serde(rename = "lastUpdatedAt")]
@@ -151,6 +556,10 @@ pub enum CodexUsageSnapshot {
         last_updated_at: String,
         #[serde(rename = "errorMessage")]
         error_message: Option<String>,
+        /// From the response's `Retry-After` header, when it sent one.
+        #[serde(rename = "retryAfterSeconds", default)]
+        #[ts(type = "number | null")]
+        retry_after_seconds: Option<u64>,
     },
     Error {
         #[serde(rename = "lastUpdatedAt")]
@@ -164,6 +573,12 @@ pub enum CodexUsageSnapshot {
         #[serde(rename = "errorMessage")]
         error_message: Option<String>,
     },
+    Offline {
+        #[serde(rename = "lastUpdatedAt")]
+        last_updated_at: String,
+        #[serde(rename = "errorMessage")]
+        error_message: Option<String>,
+    },
 }
 
 impl CodexUsageSnapshot {
@@ -174,6 +589,7 @@ impl CodexUsageSnapshot {
             Self::RateLimited { .. } => UsageStatus::RateLimited,
             Self::Error { .. } => UsageStatus::Error,
             Self::MissingKey { .. } => UsageStatus::MissingKey,
+            Self::Offline { .. } => UsageStatus::Offline,
         }
     }
 
@@ -194,6 +610,32 @@ impl CodexUsageSnapshot {
             Self::MissingKey {
                 last_updated_at, ..
             } => last_updated_at,
+            Self::Offline {
+                last_updated_at, ..
+            } => last_updated_at,
+        }
+    }
+
+    /// Server-requested wait before retrying, when rate limited with a `Retry-After` header.
+    pub fn retry_after_seconds(&self) -> Option<u64> {
+        match self {
+            Self::RateLimited {
+                retry_after_seconds,
+                ..
+            } => *retry_after_seconds,
+            _ => None,
+        }
+    }
+
+    /// The failure detail, if any (always `None` for `Ok`).
+    pub fn error_message(&self) -> Option<&str> {
+        match self {
+            Self::Ok { .. } => None,
+            Self::Unauthorized { error_message, .. }
+            | Self::RateLimited { error_message, .. }
+            | Self::Error { error_message, .. }
+            | Self::MissingKey { error_message, .. }
+            | Self::Offline { error_message, .. } => error_message.as_deref(),
         }
     }
 }
@@ -204,6 +646,73 @@ pub struct ClaudeOrganization {
     pub name: Option<String>,
 }
 
+/// Which settings fields apply to a Claude usage source.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeSourceCapabilities {
+    pub source: UsageSource,
+    pub needs_session_key: bool,
+    pub needs_organization: bool,
+    pub uses_local_credentials: bool,
+}
+
+/// Which settings fields apply to a Codex usage source.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexSourceCapabilities {
+    pub source: CodexUsageSource,
+    pub uses_local_credentials: bool,
+    pub needs_cli_binary: bool,
+    pub needs_cookie: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ProviderCapabilities {
+    pub claude: Vec<ClaudeSourceCapabilities>,
+    pub codex: Vec<CodexSourceCapabilities>,
+}
+
+/// One point of the rolling usage history (`provider` is `"claude"` or `"codex"`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageHistorySample {
+    #[ts(type = "number")]
+    pub timestamp_ms: i64,
+    pub provider: String,
+    pub session_percent: f64,
+    pub weekly_percent: Option<f64>,
+    /// Claude organization the sample was fetched for; `None` for Codex.
+    #[serde(default)]
+    pub organization_id: Option<String>,
+}
+
+/// How fast one provider window (`window` is `"session"` or `"weekly"`) is climbing over the
+/// recent usage history, and when it would hit 100% at that rate (`None` when it wouldn't).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct BurnRate {
+    pub provider: String,
+    pub window: String,
+    pub percent_per_hour: f64,
+    #[ts(type = "number | null")]
+    pub ms_to_limit: Option<i64>,
+}
+
+/// How the Codex Auto source got its latest snapshot: the source that produced it and each
+/// source tried, in order, with its outcome. Kept apart from the snapshot as a diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct AutoResolution {
+    pub source: CodexUsageSource,
+    pub attempts: Vec<(CodexUsageSource, UsageStatus)>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveSettingsResult {
+    /// Non-blocking problem with what was saved, shown next to the success state.
+    pub warning: Option<String>,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, TS)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum IpcErrorCode {
@@ -222,6 +731,15 @@ pub struct IpcError {
     pub message: String,
 }
 
+/// Payload of the `refresh:error` event: `provider` (`"claude"` or `"codex"`) just entered a
+/// failure state. `code`/`message` follow `IpcError`.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct RefreshErrorEvent {
+    pub provider: String,
+    pub code: IpcErrorCode,
+    pub message: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(untagged)]
 pub enum IpcResult<T> {
@@ -253,10 +771,13 @@ pub struct SettingsState {
     pub usage_source: UsageSource,
     pub remember_session_key: bool,
     pub codex_usage_source: CodexUsageSource,
+    pub remember_codex_cookie: bool,
     pub refresh_interval_seconds: u32,
     pub notify_on_usage_reset: bool,
     pub autostart_enabled: bool,
     pub check_updates_on_startup: bool,
+    pub tray_warn_percent: u32,
+    pub tray_critical_percent: u32,
     pub organizations: Vec<ClaudeOrganization>,
     pub selected_organization_id: Option<String>,
     pub latest_snapshot: Option<UsageSnapshotBundle>,
@@ -276,5 +797,98 @@ pub struct SaveSettingsPayload {
     pub notify_on_usage_reset: bool,
     pub autostart_enabled: bool,
     pub check_updates_on_startup: bool,
+    pub tray_warn_percent: u32,
+    pub tray_critical_percent: u32,
     pub selected_organization_id: Option<String>,
+    /// Left unchanged when omitted.
+    #[serde(default)]
+    #[ts(optional)]
+    pub refresh_jitter_ratio: Option<f64>,
+    /// Left unchanged when omitted.
+    #[serde(default)]
+    #[ts(optional)]
+    pub rate_limit_backoff_seconds: Option<u32>,
+    /// Left unchanged when omitted.
+    #[serde(default)]
+    #[ts(optional)]
+    pub http_timeout_seconds: Option<u32>,
+    /// Tray title label for Claude (at most 8 characters); blank restores "CL". Left unchanged
+    /// when omitted.
+    #[serde(default)]
+    #[ts(optional)]
+    pub claude_label: Option<String>,
+    /// Tray title label for Codex (at most 8 characters); blank restores "CX". Left unchanged
+    /// when omitted.
+    #[serde(default)]
+    #[ts(optional)]
+    pub codex_label: Option<String>,
+    /// chatgpt.com cookie for the Codex web source; blank keeps the stored one.
+    #[serde(default)]
+    #[ts(optional)]
+    pub codex_cookie: Option<String>,
+    /// Left unchanged when omitted.
+    #[serde(default)]
+    #[ts(optional)]
+    pub remember_codex_cookie: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codex_usage_source_setting_value_round_trips() {
+        for source in [
+            CodexUsageSource::Oauth,
+            CodexUsageSource::Web,
+            CodexUsageSource::Cli,
+            CodexUsageSource::Auto,
+        ] {
+            assert_eq!(
+                CodexUsageSource::from_setting_value(source.as_setting_value()),
+                Some(source)
+            );
+        }
+        assert_eq!(CodexUsageSource::from_setting_value("bogus"), None);
+    }
+
+    #[test]
+    fn auto_resolution_lists_attempts_as_pairs() {
+        let resolution = AutoResolution {
+            source: CodexUsageSource::Cli,
+            attempts: vec![
+                (CodexUsageSource::Oauth, UsageStatus::Unauthorized),
+                (CodexUsageSource::Cli, UsageStatus::Ok),
+            ],
+        };
+        assert_eq!(
+            serde_json::to_value(&resolution).unwrap(),
+            serde_json::json!({
+                "source": "cli",
+                "attempts": [["oauth", "unauthorized"], ["cli", "ok"]],
+            })
+        );
+    }
+
+    #[test]
+    fn snapshot_response_carries_per_provider_last_updated() {
+        let snapshot = UsageSnapshotBundle {
+            claude: None,
+            codex: Some(CodexUsageSnapshot::Error {
+                last_updated_at: "2026-01-06T22:59:31Z".to_string(),
+                error_message: None,
+            }),
+            offline: false,
+            claude_orgs: vec![],
+        };
+        let response = UsageSnapshotResponse::from_snapshot(Some(snapshot));
+        assert_eq!(response.claude_last_updated_at, None);
+        assert_eq!(
+            response.codex_last_updated_at.as_deref(),
+            Some("2026-01-06T22:59:31Z")
+        );
+
+        let empty = UsageSnapshotResponse::from_snapshot(None);
+        assert!(empty.snapshot.is_none() && empty.codex_last_updated_at.is_none());
+    }
 }
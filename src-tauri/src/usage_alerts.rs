@@ -1,4 +1,8 @@
-const NEAR_LIMIT_THRESHOLD_PERCENT: f64 = 90.0;
+use crate::burn_rate::{projected_ms_to_limit, UsageSample};
+use chrono::DateTime;
+
+/// Near-limit threshold when a provider has no configured value.
+pub const DEFAULT_NEAR_LIMIT_THRESHOLD_PERCENT: f64 = 90.0;
 const UNKNOWN_PERIOD_ID: &str = "unknown";
 
 #[derive(Debug, Clone, PartialEq)]
@@ -17,7 +21,8 @@ pub struct UsageResetDecision {
     pub weekly_reset_period_id: Option<String>,
 }
 
-fn normalize_period_id(resets_at: Option<&str>) -> String {
+/// Identifies a usage period by its `resets_at`; "unknown" when the provider didn't report one.
+pub fn normalize_period_id(resets_at: Option<&str>) -> String {
     let trimmed = resets_at.unwrap_or("").trim();
     if trimmed.is_empty() {
         UNKNOWN_PERIOD_ID.to_string()
@@ -27,7 +32,7 @@ fn normalize_period_id(resets_at: Option<&str>) -> String {
 }
 
 fn should_notify_near_limit(params: ShouldNotifyNearLimitParams<'_>) -> bool {
-    if params.current_percent < NEAR_LIMIT_THRESHOLD_PERCENT {
+    if params.current_percent < params.threshold {
         return false;
     }
     if params.last_notified_period_id == Some(params.current_period_id) {
@@ -35,11 +40,12 @@ fn should_notify_near_limit(params: ShouldNotifyNearLimitParams<'_>) -> bool {
     }
     match params.previous_percent {
         None => true,
-        Some(prev) => prev < NEAR_LIMIT_THRESHOLD_PERCENT,
+        Some(prev) => prev < params.threshold,
     }
 }
 
 struct ShouldNotifyNearLimitParams<'a> {
+    threshold: f64,
     current_percent: f64,
     previous_percent: Option<f64>,
     current_period_id: &'a str,
@@ -51,18 +57,25 @@ pub fn decide_near_limit_alerts(params: DecideNearLimitAlertsParams<'_>) -> Near
     let weekly_period_id = normalize_period_id(params.current_weekly_resets_at);
 
     let notify_session = should_notify_near_limit(ShouldNotifyNearLimitParams {
+        threshold: params.session_threshold,
         current_percent: params.current_session_percent,
         previous_percent: params.previous_session_percent,
         current_period_id: &session_period_id,
         last_notified_period_id: params.last_notified_session_period_id,
     });
 
-    let notify_weekly = should_notify_near_limit(ShouldNotifyNearLimitParams {
-        current_percent: params.current_weekly_percent,
-        previous_percent: params.previous_weekly_percent,
-        current_period_id: &weekly_period_id,
-        last_notified_period_id: params.last_notified_weekly_period_id,
-    });
+    // Unknown weekly usage never alerts.
+    let notify_weekly = params
+        .current_weekly_percent
+        .is_some_and(|current_percent| {
+            should_notify_near_limit(ShouldNotifyNearLimitParams {
+                threshold: params.weekly_threshold,
+                current_percent,
+                previous_percent: params.previous_weekly_percent,
+                current_period_id: &weekly_period_id,
+                last_notified_period_id: params.last_notified_weekly_period_id,
+            })
+        });
 
     NearLimitAlertDecision {
         notify_session,
@@ -74,13 +87,46 @@ pub fn decide_near_limit_alerts(params: DecideNearLimitAlertsParams<'_>) -> Near
 
 pub struct DecideNearLimitAlertsParams<'a> {
     pub current_session_percent: f64,
-    pub current_weekly_percent: f64,
+    pub current_weekly_percent: Option<f64>,
     pub current_session_resets_at: Option<&'a str>,
     pub current_weekly_resets_at: Option<&'a str>,
     pub previous_session_percent: Option<f64>,
     pub previous_weekly_percent: Option<f64>,
     pub last_notified_session_period_id: Option<&'a str>,
     pub last_notified_weekly_period_id: Option<&'a str>,
+    /// Percent at or above which each window counts as near the limit.
+    pub session_threshold: f64,
+    pub weekly_threshold: f64,
+}
+
+/// The highest alert tier already sent for a window: its period id and index into the tiers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NotifiedTier<'a> {
+    pub period_id: &'a str,
+    pub tier: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TieredAlertDecision {
+    pub tier: usize,
+    pub period_id: String,
+}
+
+/// The highest of the ascending `thresholds` reached by `current_percent`, if it is above the
+/// tier already notified in this period. Lower tiers skipped on the way up are not sent
+/// afterwards, and a new period id starts over from the first tier.
+pub fn decide_tiered_alerts(
+    thresholds: &[f64],
+    current_percent: f64,
+    current_resets_at: Option<&str>,
+    last_notified: Option<NotifiedTier<'_>>,
+) -> Option<TieredAlertDecision> {
+    let tier = thresholds.iter().rposition(|t| current_percent >= *t)?;
+    let period_id = normalize_period_id(current_resets_at);
+    if last_notified.is_some_and(|last| last.period_id == period_id && last.tier >= tier) {
+        return None;
+    }
+    Some(TieredAlertDecision { tier, period_id })
 }
 
 fn should_notify_reset(params: ShouldNotifyResetParams<'_>) -> bool {
@@ -138,37 +184,220 @@ pub struct DecideUsageResetsParams<'a> {
     pub last_notified_weekly_reset_period_id: Option<&'a str>,
 }
 
+/// Notify once per period when the projected time to the limit drops below `horizon_ms`.
+pub fn should_notify_burn_rate(
+    period_id: &str,
+    samples: &[UsageSample],
+    horizon_ms: i64,
+    last_notified_period_id: Option<&str>,
+) -> bool {
+    if period_id == UNKNOWN_PERIOD_ID || last_notified_period_id == Some(period_id) {
+        return false;
+    }
+    projected_ms_to_limit(samples).is_some_and(|ms| ms < horizon_ms)
+}
+
+/// Notify once per period when the projected time to 100% comes before the window resets, i.e.
+/// usage is on track to run out first. The period id is the window's `resets_at`.
+pub fn should_notify_exhaustion(
+    period_id: &str,
+    samples: &[UsageSample],
+    last_notified_period_id: Option<&str>,
+) -> bool {
+    if period_id == UNKNOWN_PERIOD_ID || last_notified_period_id == Some(period_id) {
+        return false;
+    }
+    let (Ok(resets_at), Some(last)) = (DateTime::parse_from_rfc3339(period_id), samples.last())
+    else {
+        return false;
+    };
+    projected_ms_to_limit(samples)
+        .is_some_and(|ms| ms > 0 && last.at_unix_ms + ms < resets_at.timestamp_millis())
+}
+
+/// "Heads up" for the first of several tiers, "Critical" for the last, "Warning" otherwise.
+pub fn tier_alert_message(
+    provider_label: &str,
+    window: &str,
+    tier: usize,
+    tier_count: usize,
+    current_percent: f64,
+) -> String {
+    let label = if tier_count > 1 && tier + 1 == tier_count {
+        "Critical"
+    } else if tier_count > 1 && tier == 0 {
+        "Heads up"
+    } else {
+        "Warning"
+    };
+    format!(
+        "{label}: {provider_label} {window} usage is at {}%.",
+        current_percent.round() as i64
+    )
+}
+
+/// "40m", or "2h 5m" past an hour.
+fn format_eta(projected_ms: i64) -> String {
+    let minutes = (projected_ms / 60_000).max(1);
+    if minutes >= 60 {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+pub fn burn_rate_message(provider_label: &str, window: &str, projected_ms: i64) -> String {
+    let eta = format_eta(projected_ms);
+    format!(
+        "{provider_label} {window} usage is climbing fast: about {eta} to the limit at this rate."
+    )
+}
+
+pub fn exhaustion_message(provider_label: &str, window: &str, projected_ms: i64) -> String {
+    let eta = format_eta(projected_ms);
+    format!(
+        "{provider_label} {window} usage is on track to run out in about {eta}, before it resets."
+    )
+}
+
+/// "72%→93%" when a previous reading exists.
+pub fn format_percent_delta(previous: Option<f64>, current: f64) -> Option<String> {
+    previous.map(|prev| format!("{}%→{}%", prev.round() as i64, current.round() as i64))
+}
+
+/// Near-limit notification body; `window` is "session" or "weekly".
+pub fn near_limit_message(
+    provider_label: &str,
+    window: &str,
+    threshold: f64,
+    current_percent: f64,
+    previous_percent: Option<f64>,
+    show_delta: bool,
+) -> String {
+    let base = format!(
+        "{provider_label} {window} usage is near the limit (>= {}%)",
+        threshold.round() as i64
+    );
+    match show_delta
+        .then(|| format_percent_delta(previous_percent, current_percent))
+        .flatten()
+    {
+        Some(delta) => format!("{base}: {delta}."),
+        None => format!("{base}."),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn format_percent_delta_requires_previous_value() {
+        assert_eq!(
+            format_percent_delta(Some(72.4), 93.0).as_deref(),
+            Some("72%→93%")
+        );
+        assert_eq!(format_percent_delta(None, 93.0), None);
+    }
+
+    #[test]
+    fn near_limit_message_includes_delta_only_when_enabled() {
+        assert_eq!(
+            near_limit_message("Claude", "weekly", 90.0, 93.0, Some(72.0), true),
+            "Claude weekly usage is near the limit (>= 90%): 72%→93%."
+        );
+        assert_eq!(
+            near_limit_message("Claude", "weekly", 90.0, 93.0, None, true),
+            "Claude weekly usage is near the limit (>= 90%)."
+        );
+        assert_eq!(
+            near_limit_message("Codex", "session", 90.0, 93.0, Some(72.0), false),
+            "Codex session usage is near the limit (>= 90%)."
+        );
+    }
+
+    #[test]
+    fn near_limit_uses_each_providers_threshold() {
+        let decide = |threshold: f64, previous: Option<f64>| {
+            decide_near_limit_alerts(DecideNearLimitAlertsParams {
+                current_session_percent: 82.0,
+                current_weekly_percent: Some(82.0),
+                current_session_resets_at: Some("2026-01-01T05:00:00.000Z"),
+                current_weekly_resets_at: Some("2026-01-08T00:00:00.000Z"),
+                previous_session_percent: previous,
+                previous_weekly_percent: previous,
+                last_notified_session_period_id: None,
+                last_notified_weekly_period_id: None,
+                session_threshold: threshold,
+                weekly_threshold: threshold,
+            })
+        };
+
+        let codex = decide(80.0, Some(75.0));
+        assert!(codex.notify_session && codex.notify_weekly);
+        let claude = decide(95.0, Some(75.0));
+        assert!(!claude.notify_session && !claude.notify_weekly);
+
+        // Already above the lower threshold last time: no new crossing.
+        let codex_again = decide(80.0, Some(81.0));
+        assert!(!codex_again.notify_session && !codex_again.notify_weekly);
+
+        assert_eq!(
+            near_limit_message("Codex", "session", 80.0, 82.0, None, false),
+            "Codex session usage is near the limit (>= 80%)."
+        );
+    }
+
     #[test]
     fn near_limit_does_not_notify_below_threshold() {
         let result = decide_near_limit_alerts(DecideNearLimitAlertsParams {
             current_session_percent: 89.9,
-            current_weekly_percent: 0.0,
+            current_weekly_percent: Some(0.0),
             current_session_resets_at: Some("2026-01-01T05:00:00.000Z"),
             current_weekly_resets_at: Some("2026-01-08T00:00:00.000Z"),
             previous_session_percent: None,
             previous_weekly_percent: None,
             last_notified_session_period_id: None,
             last_notified_weekly_period_id: None,
+            session_threshold: 90.0,
+            weekly_threshold: 90.0,
         });
         assert!(!result.notify_session);
         assert!(!result.notify_weekly);
     }
 
+    #[test]
+    fn near_limit_skips_unknown_weekly_usage() {
+        let result = decide_near_limit_alerts(DecideNearLimitAlertsParams {
+            current_session_percent: 95.0,
+            current_weekly_percent: None,
+            current_session_resets_at: Some("2026-01-01T05:00:00.000Z"),
+            current_weekly_resets_at: Some("2026-01-08T00:00:00.000Z"),
+            previous_session_percent: None,
+            previous_weekly_percent: None,
+            last_notified_session_period_id: None,
+            last_notified_weekly_period_id: None,
+            session_threshold: 90.0,
+            weekly_threshold: 90.0,
+        });
+        assert!(result.notify_session);
+        assert!(!result.notify_weekly);
+        assert_eq!(result.weekly_period_id, None);
+    }
+
     #[test]
     fn near_limit_notifies_once_on_first_observation() {
         let result = decide_near_limit_alerts(DecideNearLimitAlertsParams {
             current_session_percent: 90.0,
-            current_weekly_percent: 95.0,
+            current_weekly_percent: Some(95.0),
             current_session_resets_at: Some("2026-01-01T05:00:00.000Z"),
             current_weekly_resets_at: Some("2026-01-08T00:00:00.000Z"),
             previous_session_percent: None,
             previous_weekly_percent: None,
             last_notified_session_period_id: Some(""),
             last_notified_weekly_period_id: Some(""),
+            session_threshold: 90.0,
+            weekly_threshold: 90.0,
         });
         assert!(result.notify_session);
         assert_eq!(
@@ -186,13 +415,15 @@ mod tests {
     fn near_limit_does_not_notify_again_for_same_period_id() {
         let result = decide_near_limit_alerts(DecideNearLimitAlertsParams {
             current_session_percent: 99.0,
-            current_weekly_percent: 99.0,
+            current_weekly_percent: Some(99.0),
             current_session_resets_at: Some("2026-01-01T05:00:00.000Z"),
             current_weekly_resets_at: Some("2026-01-08T00:00:00.000Z"),
             previous_session_percent: Some(95.0),
             previous_weekly_percent: Some(95.0),
             last_notified_session_period_id: Some("2026-01-01T05:00:00.000Z"),
             last_notified_weekly_period_id: Some("2026-01-08T00:00:00.000Z"),
+            session_threshold: 90.0,
+            weekly_threshold: 90.0,
         });
         assert!(!result.notify_session);
         assert!(!result.notify_weekly);
@@ -202,13 +433,15 @@ mod tests {
     fn near_limit_allows_notification_again_when_period_id_changes() {
         let result = decide_near_limit_alerts(DecideNearLimitAlertsParams {
             current_session_percent: 92.0,
-            current_weekly_percent: 92.0,
+            current_weekly_percent: Some(92.0),
             current_session_resets_at: Some("2026-01-01T10:00:00.000Z"),
             current_weekly_resets_at: Some("2026-01-15T00:00:00.000Z"),
             previous_session_percent: Some(10.0),
             previous_weekly_percent: Some(10.0),
             last_notified_session_period_id: Some("2026-01-01T05:00:00.000Z"),
             last_notified_weekly_period_id: Some("2026-01-08T00:00:00.000Z"),
+            session_threshold: 90.0,
+            weekly_threshold: 90.0,
         });
         assert!(result.notify_session);
         assert_eq!(
@@ -226,13 +459,15 @@ mod tests {
     fn near_limit_uses_unknown_period_id_when_resets_at_missing() {
         let first = decide_near_limit_alerts(DecideNearLimitAlertsParams {
             current_session_percent: 90.0,
-            current_weekly_percent: 90.0,
+            current_weekly_percent: Some(90.0),
             current_session_resets_at: None,
             current_weekly_resets_at: None,
             previous_session_percent: None,
             previous_weekly_percent: None,
             last_notified_session_period_id: None,
             last_notified_weekly_period_id: None,
+            session_threshold: 90.0,
+            weekly_threshold: 90.0,
         });
         assert!(first.notify_session);
         assert_eq!(first.session_period_id.as_deref(), Some("unknown"));
@@ -241,13 +476,15 @@ mod tests {
 
         let second = decide_near_limit_alerts(DecideNearLimitAlertsParams {
             current_session_percent: 95.0,
-            current_weekly_percent: 95.0,
+            current_weekly_percent: Some(95.0),
             current_session_resets_at: None,
             current_weekly_resets_at: None,
             previous_session_percent: Some(90.0),
             previous_weekly_percent: Some(90.0),
             last_notified_session_period_id: Some("unknown"),
             last_notified_weekly_period_id: Some("unknown"),
+            session_threshold: 90.0,
+            weekly_threshold: 90.0,
         });
         assert!(!second.notify_session);
         assert!(!second.notify_weekly);
@@ -348,4 +585,152 @@ mod tests {
         );
         assert!(!result.notify_weekly_reset);
     }
+
+    const PERIOD: &str = "2026-01-08T00:00:00.000Z";
+
+    fn samples(percents: &[f64]) -> Vec<UsageSample> {
+        percents
+            .iter()
+            .enumerate()
+            .map(|(i, p)| UsageSample {
+                at_unix_ms: i as i64 * 60_000,
+                percent: *p,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn burn_rate_projects_steep_trend_within_horizon() {
+        // +10% per minute ending at 80% → 2 minutes to the limit.
+        let recent = samples(&[60.0, 70.0, 80.0]);
+        let projected = projected_ms_to_limit(&recent).unwrap();
+        assert_eq!(projected, 2 * 60_000);
+        assert!(should_notify_burn_rate(
+            PERIOD,
+            &recent,
+            2 * 60 * 60_000,
+            None
+        ));
+        assert!(!should_notify_burn_rate(
+            PERIOD,
+            &recent,
+            2 * 60 * 60_000,
+            Some(PERIOD)
+        ));
+        assert!(!should_notify_burn_rate(
+            UNKNOWN_PERIOD_ID,
+            &recent,
+            2 * 60 * 60_000,
+            None
+        ));
+    }
+
+    #[test]
+    fn burn_rate_ignores_flat_or_short_trends() {
+        let flat = samples(&[40.0, 40.0, 40.0, 40.0]);
+        assert_eq!(projected_ms_to_limit(&flat), None);
+        assert!(!should_notify_burn_rate(PERIOD, &flat, i64::MAX, None));
+
+        let short = samples(&[10.0, 90.0]);
+        assert_eq!(projected_ms_to_limit(&short), None);
+    }
+
+    /// One sample a minute, the last one `minutes_before_reset` before `resets_at`.
+    fn samples_before_reset(
+        resets_at: Option<&str>,
+        minutes_before_reset: i64,
+        percents: &[f64],
+    ) -> Vec<UsageSample> {
+        let reset_ms = resets_at.map_or(0, |r| {
+            DateTime::parse_from_rfc3339(r).unwrap().timestamp_millis()
+        });
+        let first_ms = reset_ms - (minutes_before_reset + percents.len() as i64 - 1) * 60_000;
+        percents
+            .iter()
+            .enumerate()
+            .map(|(i, p)| UsageSample {
+                at_unix_ms: first_ms + i as i64 * 60_000,
+                percent: *p,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exhaustion_is_predicted_when_the_limit_comes_before_the_reset() {
+        // +10% per minute ending at 80%: the limit is 2 minutes after the last sample.
+        let early = samples_before_reset(Some(PERIOD), 10, &[60.0, 70.0, 80.0]);
+        assert!(should_notify_exhaustion(PERIOD, &early, None));
+        assert!(!should_notify_exhaustion(PERIOD, &early, Some(PERIOD)));
+
+        let late = samples_before_reset(Some(PERIOD), 1, &[60.0, 70.0, 80.0]);
+        assert!(!should_notify_exhaustion(PERIOD, &late, None));
+    }
+
+    #[test]
+    fn exhaustion_needs_a_climbing_trend_and_a_known_reset() {
+        let flat = samples_before_reset(Some(PERIOD), 60, &[40.0, 40.0, 40.0]);
+        assert!(!should_notify_exhaustion(PERIOD, &flat, None));
+        let short = samples_before_reset(Some(PERIOD), 60, &[10.0, 90.0]);
+        assert!(!should_notify_exhaustion(PERIOD, &short, None));
+        let at_limit = samples_before_reset(Some(PERIOD), 60, &[98.0, 99.0, 100.0]);
+        assert!(!should_notify_exhaustion(PERIOD, &at_limit, None));
+
+        let unknown = samples_before_reset(None, 60, &[60.0, 70.0, 80.0]);
+        assert!(!should_notify_exhaustion(
+            &normalize_period_id(None),
+            &unknown,
+            None
+        ));
+    }
+
+    #[test]
+    fn tiered_alerts_escalate_once_per_period() {
+        let tiers = [75.0, 90.0, 95.0];
+        let period = Some("2026-01-01T05:00:00.000Z");
+        let notified = |tier| {
+            Some(NotifiedTier {
+                period_id: "2026-01-01T05:00:00.000Z",
+                tier,
+            })
+        };
+
+        assert_eq!(decide_tiered_alerts(&tiers, 74.9, period, None), None);
+        let first = decide_tiered_alerts(&tiers, 80.0, period, None).unwrap();
+        assert_eq!(first.tier, 0);
+        assert_eq!(first.period_id, "2026-01-01T05:00:00.000Z");
+        assert_eq!(
+            decide_tiered_alerts(&tiers, 85.0, period, notified(0)),
+            None
+        );
+        assert_eq!(
+            decide_tiered_alerts(&tiers, 91.0, period, notified(0)).map(|d| d.tier),
+            Some(1)
+        );
+
+        // Jumping straight to critical skips the lower tiers, which then never fire this period.
+        assert_eq!(
+            decide_tiered_alerts(&tiers, 96.0, period, None).map(|d| d.tier),
+            Some(2)
+        );
+        assert_eq!(
+            decide_tiered_alerts(&tiers, 91.0, period, notified(2)),
+            None
+        );
+
+        // A new period starts over.
+        assert_eq!(
+            decide_tiered_alerts(&tiers, 80.0, Some("2026-01-01T10:00:00.000Z"), notified(2))
+                .map(|d| d.tier),
+            Some(0)
+        );
+
+        assert_eq!(
+            tier_alert_message("Claude", "session", 0, 3, 76.0),
+            "Heads up: Claude session usage is at 76%."
+        );
+        assert_eq!(
+            tier_alert_message("Codex", "weekly", 2, 3, 95.4),
+            "Critical: Codex weekly usage is at 95%."
+        );
+    }
 }
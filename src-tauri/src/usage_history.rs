@@ -0,0 +1,193 @@
+use crate::provider_view::{CLAUDE_PROVIDER_KEY, CODEX_PROVIDER_KEY};
+use crate::types::{
+    ClaudeUsageSnapshot, CodexUsageSnapshot, UsageHistorySample, UsageSnapshotBundle,
+};
+use chrono::DateTime;
+use std::collections::HashMap;
+
+/// Upper bound on stored samples per provider (a day of one-minute refreshes).
+pub const MAX_HISTORY_SAMPLES: usize = 1440;
+
+fn timestamp_ms(iso: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(iso)
+        .ok()
+        .map(|dt| dt.timestamp_millis())
+}
+
+/// Samples for the Ok providers in `snapshot`; errors and missing keys are left out rather than
+/// recorded as 0%.
+fn samples_from_snapshot(snapshot: &UsageSnapshotBundle) -> Vec<UsageHistorySample> {
+    let mut out = Vec::new();
+    if let Some(ClaudeUsageSnapshot::Ok {
+        organization_id,
+        session_percent,
+        weekly_percent,
+        last_updated_at,
+        ..
+    }) = snapshot.claude.as_ref()
+    {
+        if let Some(timestamp_ms) = timestamp_ms(last_updated_at) {
+            out.push(UsageHistorySample {
+                timestamp_ms,
+                provider: CLAUDE_PROVIDER_KEY.to_string(),
+                session_percent: *session_percent,
+                weekly_percent: *weekly_percent,
+                organization_id: Some(organization_id.clone()),
+            });
+        }
+    }
+    if let Some(CodexUsageSnapshot::Ok {
+        session_percent,
+        weekly_percent,
+        last_updated_at,
+        ..
+    }) = snapshot.codex.as_ref()
+    {
+        if let Some(timestamp_ms) = timestamp_ms(last_updated_at) {
+            out.push(UsageHistorySample {
+                timestamp_ms,
+                provider: CODEX_PROVIDER_KEY.to_string(),
+                session_percent: *session_percent,
+                weekly_percent: *weekly_percent,
+                organization_id: None,
+            });
+        }
+    }
+    out
+}
+
+/// Drop samples older than `retention_ms` and, per provider, the oldest ones beyond
+/// `MAX_HISTORY_SAMPLES`. Returns whether anything was removed.
+pub fn prune(samples: &mut Vec<UsageHistorySample>, now_ms: i64, retention_ms: i64) -> bool {
+    let before = samples.len();
+    samples.retain(|s| now_ms - s.timestamp_ms <= retention_ms);
+
+    let mut kept_by_provider: HashMap<&str, usize> = HashMap::new();
+    let mut keep: Vec<bool> = samples
+        .iter()
+        .rev()
+        .map(|s| {
+            let kept = kept_by_provider.entry(s.provider.as_str()).or_default();
+            *kept += 1;
+            *kept <= MAX_HISTORY_SAMPLES
+        })
+        .collect();
+    keep.reverse();
+    let mut keep = keep.into_iter();
+    samples.retain(|_| keep.next().unwrap_or(true));
+
+    samples.len() != before
+}
+
+/// Append the Ok parts of `snapshot`, then prune. A provider's sample is only added when its
+/// fetch time is new, since a published bundle also carries the other provider's last result.
+/// Returns whether the history changed (so callers only persist when needed).
+pub fn record(
+    samples: &mut Vec<UsageHistorySample>,
+    snapshot: &UsageSnapshotBundle,
+    now_ms: i64,
+    retention_ms: i64,
+) -> bool {
+    let mut changed = false;
+    for sample in samples_from_snapshot(snapshot) {
+        let seen = samples
+            .iter()
+            .rev()
+            .find(|s| s.provider == sample.provider)
+            .is_some_and(|last| last.timestamp_ms >= sample.timestamp_ms);
+        if !seen {
+            samples.push(sample);
+            changed = true;
+        }
+    }
+    prune(samples, now_ms, retention_ms) || changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_MS: i64 = 60 * 60 * 1000;
+
+    fn bundle(claude_at: &str, codex: CodexUsageSnapshot) -> UsageSnapshotBundle {
+        UsageSnapshotBundle {
+            claude: Some(ClaudeUsageSnapshot::Ok {
+                organization_id: "org".to_string(),
+                session_percent: 40.0,
+                session_resets_at: None,
+                weekly_percent: None,
+                weekly_resets_at: None,
+                models: vec![],
+                session_models: vec![],
+                last_updated_at: claude_at.to_string(),
+                provider_timestamp: None,
+            }),
+            codex: Some(codex),
+            offline: false,
+            claude_orgs: vec![],
+        }
+    }
+
+    #[test]
+    fn record_skips_errors_and_already_seen_fetches() {
+        let codex_error = CodexUsageSnapshot::Error {
+            last_updated_at: "2026-01-01T00:00:00Z".to_string(),
+            error_message: None,
+        };
+        let now = timestamp_ms("2026-01-01T00:05:00Z").unwrap();
+        let mut samples = Vec::new();
+
+        assert!(record(
+            &mut samples,
+            &bundle("2026-01-01T00:00:00Z", codex_error.clone()),
+            now,
+            24 * HOUR_MS
+        ));
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].provider, "claude");
+        assert_eq!(samples[0].organization_id.as_deref(), Some("org"));
+        assert_eq!(samples[0].weekly_percent, None);
+
+        // Same Claude fetch republished alongside a Codex update: nothing new for Claude.
+        assert!(!record(
+            &mut samples,
+            &bundle("2026-01-01T00:00:00Z", codex_error),
+            now,
+            24 * HOUR_MS
+        ));
+        assert_eq!(samples.len(), 1);
+    }
+
+    #[test]
+    fn prune_applies_retention_and_cap() {
+        let now = 100 * HOUR_MS;
+        let sample = |timestamp_ms| UsageHistorySample {
+            timestamp_ms,
+            provider: "codex".to_string(),
+            session_percent: 1.0,
+            weekly_percent: Some(1.0),
+            organization_id: None,
+        };
+        let mut samples = vec![sample(now - 3 * HOUR_MS), sample(now - HOUR_MS)];
+        assert!(prune(&mut samples, now, 2 * HOUR_MS));
+        assert_eq!(samples, vec![sample(now - HOUR_MS)]);
+
+        let mut samples: Vec<_> = (0..MAX_HISTORY_SAMPLES as i64 + 5).map(sample).collect();
+        assert!(prune(&mut samples, now, now));
+        assert_eq!(samples.len(), MAX_HISTORY_SAMPLES);
+        assert_eq!(samples[0].timestamp_ms, 5);
+
+        // The cap is per provider: a full Codex history does not evict Claude's samples.
+        let claude = UsageHistorySample {
+            provider: "claude".to_string(),
+            ..sample(0)
+        };
+        let mut samples: Vec<_> = std::iter::once(claude.clone())
+            .chain((1..=MAX_HISTORY_SAMPLES as i64 + 1).map(sample))
+            .collect();
+        assert!(prune(&mut samples, now, now));
+        assert_eq!(samples.len(), MAX_HISTORY_SAMPLES + 1);
+        assert_eq!(samples[0], claude);
+        assert_eq!(samples[1].timestamp_ms, 2);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::notifications::notify_plain;
+use crate::redact::redact_secrets;
 use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
 
 pub const SETTINGS_WINDOW_LABEL: &str = "settings";
@@ -26,3 +28,65 @@ pub fn open_settings_window<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()>
     window.set_focus()?;
     Ok(())
 }
+
+/// After a failed settings open, bring any existing window forward; if there is none, tell
+/// the user via `notify` since a menu/tray click has nowhere else to show the error.
+fn handle_open_failure(
+    error: &str,
+    focus_existing: impl FnOnce() -> bool,
+    notify: impl FnOnce(&str),
+) {
+    if !focus_existing() {
+        notify(&format!("Couldn't open Settings: {error}"));
+    }
+}
+
+/// `open_settings_window` for tray/menu clicks, where errors would otherwise be dropped.
+pub fn open_settings_window_or_report<R: Runtime>(app: &AppHandle<R>) {
+    let Err(e) = open_settings_window(app) else {
+        return;
+    };
+    handle_open_failure(
+        &redact_secrets(&e.to_string()),
+        || {
+            app.webview_windows()
+                .values()
+                .next()
+                .is_some_and(|window| window.show().is_ok() && window.set_focus().is_ok())
+        },
+        |body| {
+            let app = app.clone();
+            let body = body.to_string();
+            tauri::async_runtime::spawn(async move {
+                notify_plain(&app, &body).await;
+            });
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn open_failure_focuses_existing_window_or_notifies() {
+        let notified = RefCell::new(None::<String>);
+        handle_open_failure(
+            "webview crashed",
+            || true,
+            |body| *notified.borrow_mut() = Some(body.to_string()),
+        );
+        assert_eq!(*notified.borrow(), None);
+
+        handle_open_failure(
+            "webview crashed",
+            || false,
+            |body| *notified.borrow_mut() = Some(body.to_string()),
+        );
+        assert_eq!(
+            notified.borrow().as_deref(),
+            Some("Couldn't open Settings: webview crashed")
+        );
+    }
+}
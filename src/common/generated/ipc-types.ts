@@ -2,11 +2,11 @@
 // This file is generated by `cargo run --manifest-path src-tauri/Cargo.toml --bin generate_ipc_types`.
 // Do not edit manually.
 
-export type UsageStatus = 'ok' | 'unauthorized' | 'rate_limited' | 'error' | 'missing_key';
+export type UsageStatus = 'ok' | 'unauthorized' | 'rate_limited' | 'error' | 'missing_key' | 'offline';
 
 export type UsageSource = 'web' | 'cli';
 
-export type CodexUsageSource = 'oauth' | 'cli';
+export type CodexUsageSource = 'oauth' | 'web' | 'cli' | 'auto';
 
 export type ClaudeModelUsage = { name: string; percent: number; resetsAt: string | null };
 
@@ -16,10 +16,12 @@ export type ClaudeUsageSnapshot =
       organizationId: string;
       sessionPercent: number;
       sessionResetsAt: string | null;
-      weeklyPercent: number;
+      weeklyPercent: number | null;
       weeklyResetsAt: string | null;
       models: Array<ClaudeModelUsage>;
+      sessionModels: Array<ClaudeModelUsage>;
       lastUpdatedAt: string;
+      providerTimestamp: string | null;
     }
   | {
       status: 'unauthorized';
@@ -32,6 +34,10 @@ export type ClaudeUsageSnapshot =
       organizationId: string | null;
       lastUpdatedAt: string;
       errorMessage: string | null;
+      /**
+       * From the response's `Retry-After` header, when it sent one.
+       */
+      retryAfterSeconds: number | null;
     }
   | {
       status: 'error';
@@ -44,6 +50,12 @@ export type ClaudeUsageSnapshot =
       organizationId: string | null;
       lastUpdatedAt: string;
       errorMessage: string | null;
+    }
+  | {
+      status: 'offline';
+      organizationId: string | null;
+      lastUpdatedAt: string;
+      errorMessage: string | null;
     };
 
 export type CodexUsageSnapshot =
@@ -51,18 +63,52 @@ export type CodexUsageSnapshot =
       status: 'ok';
       sessionPercent: number;
       sessionResetsAt: string | null;
-      weeklyPercent: number;
+      weeklyPercent: number | null;
       weeklyResetsAt: string | null;
       lastUpdatedAt: string;
+      providerTimestamp: string | null;
     }
   | { status: 'unauthorized'; lastUpdatedAt: string; errorMessage: string | null }
-  | { status: 'rate_limited'; lastUpdatedAt: string; errorMessage: string | null }
+  | {
+      status: 'rate_limited';
+      lastUpdatedAt: string;
+      errorMessage: string | null;
+      /**
+       * From the response's `Retry-After` header, when it sent one.
+       */
+      retryAfterSeconds: number | null;
+    }
   | { status: 'error'; lastUpdatedAt: string; errorMessage: string | null }
-  | { status: 'missing_key'; lastUpdatedAt: string; errorMessage: string | null };
+  | { status: 'missing_key'; lastUpdatedAt: string; errorMessage: string | null }
+  | { status: 'offline'; lastUpdatedAt: string; errorMessage: string | null };
 
 export type UsageSnapshotBundle = {
   claude: ClaudeUsageSnapshot | null;
   codex: CodexUsageSnapshot | null;
+  /**
+   * The latest fetch couldn't reach the network, so the provider slots show the last known
+   * values instead of an error.
+   */
+  offline: boolean;
+  /**
+   * Usage of the other organizations when `trackAllOrgs` is on; `claude` stays the selected
+   * organization. Empty otherwise.
+   */
+  claudeOrgs: Array<ClaudeOrgSnapshot>;
+};
+
+/**
+ * One additional tracked Claude organization and its latest usage.
+ */
+export type ClaudeOrgSnapshot = {
+  organization: ClaudeOrganization;
+  snapshot: ClaudeUsageSnapshot;
+};
+
+export type UsageSnapshotResponse = {
+  snapshot: UsageSnapshotBundle | null;
+  claudeLastUpdatedAt: string | null;
+  codexLastUpdatedAt: string | null;
 };
 
 export type ClaudeOrganization = { id: string; name: string | null };
@@ -73,10 +119,13 @@ export type SettingsState = {
   usageSource: UsageSource;
   rememberSessionKey: boolean;
   codexUsageSource: CodexUsageSource;
+  rememberCodexCookie: boolean;
   refreshIntervalSeconds: number;
   notifyOnUsageReset: boolean;
   autostartEnabled: boolean;
   checkUpdatesOnStartup: boolean;
+  trayWarnPercent: number;
+  trayCriticalPercent: number;
   organizations: Array<ClaudeOrganization>;
   selectedOrganizationId: string | null;
   latestSnapshot: UsageSnapshotBundle | null;
@@ -94,9 +143,100 @@ export type SaveSettingsPayload = {
   notifyOnUsageReset: boolean;
   autostartEnabled: boolean;
   checkUpdatesOnStartup: boolean;
+  trayWarnPercent: number;
+  trayCriticalPercent: number;
   selectedOrganizationId: string | null;
+  /**
+   * Left unchanged when omitted.
+   */
+  refreshJitterRatio?: number;
+  /**
+   * Left unchanged when omitted.
+   */
+  rateLimitBackoffSeconds?: number;
+  /**
+   * Left unchanged when omitted.
+   */
+  httpTimeoutSeconds?: number;
+  /**
+   * Tray title label for Claude (at most 8 characters); blank restores "CL". Left unchanged
+   * when omitted.
+   */
+  claudeLabel?: string;
+  /**
+   * Tray title label for Codex (at most 8 characters); blank restores "CX". Left unchanged
+   * when omitted.
+   */
+  codexLabel?: string;
+  /**
+   * chatgpt.com cookie for the Codex web source; blank keeps the stored one.
+   */
+  codexCookie?: string;
+  /**
+   * Left unchanged when omitted.
+   */
+  rememberCodexCookie?: boolean;
+};
+
+export type ClaudeSourceCapabilities = {
+  source: UsageSource;
+  needsSessionKey: boolean;
+  needsOrganization: boolean;
+  usesLocalCredentials: boolean;
+};
+
+export type CodexSourceCapabilities = {
+  source: CodexUsageSource;
+  usesLocalCredentials: boolean;
+  needsCliBinary: boolean;
+  needsCookie: boolean;
+};
+
+export type ProviderCapabilities = {
+  claude: Array<ClaudeSourceCapabilities>;
+  codex: Array<CodexSourceCapabilities>;
+};
+
+/**
+ * How the Codex Auto source got its latest snapshot: the source that produced it and each
+ * source tried, in order, with its outcome. Kept apart from the snapshot as a diagnostic.
+ */
+export type AutoResolution = {
+  source: CodexUsageSource;
+  attempts: Array<[CodexUsageSource, UsageStatus]>;
 };
 
+export type UsageHistorySample = {
+  timestampMs: number;
+  provider: string;
+  sessionPercent: number;
+  weeklyPercent: number | null;
+  /**
+   * Claude organization the sample was fetched for; `None` for Codex.
+   */
+  organizationId: string | null;
+};
+
+/**
+ * How fast one provider window (`window` is `"session"` or `"weekly"`) is climbing over the
+ * recent usage history, and when it would hit 100% at that rate (`None` when it wouldn't).
+ */
+export type BurnRate = {
+  provider: string;
+  window: string;
+  percentPerHour: number;
+  msToLimit: number | null;
+};
+
+export type SaveSettingsResult = {
+  /**
+   * Non-blocking problem with what was saved, shown next to the success state.
+   */
+  warning: string | null;
+};
+
+export type TrayColors = { ok: string; warn: string; critical: string; unknown: string };
+
 export type IpcErrorCode =
   | 'VALIDATION'
   | 'NETWORK'
@@ -108,4 +248,10 @@ export type IpcErrorCode =
 
 export type IpcError = { code: IpcErrorCode; message: string };
 
+/**
+ * Payload of the `refresh:error` event: `provider` (`"claude"` or `"codex"`) just entered a
+ * failure state. `code`/`message` follow `IpcError`.
+ */
+export type RefreshErrorEvent = { provider: string; code: IpcErrorCode; message: string };
+
 export type IpcResult<T> = { ok: boolean; value: T } | { ok: boolean; error: IpcError };
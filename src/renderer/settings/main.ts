@@ -9,8 +9,10 @@ import type {
   CodexUsageSource,
   IpcResult,
   SaveSettingsPayload,
+  SaveSettingsResult,
   SettingsState,
   UsageSnapshotBundle,
+  UsageSnapshotResponse,
   UsageSource,
 } from '../../common/generated/ipc-types.ts';
 
@@ -24,8 +26,14 @@ async function settingsGetState(): Promise<SettingsState> {
   return await invoke<SettingsState>('settings_get_state');
 }
 
-async function settingsSave(payload: SaveSettingsPayload): Promise<IpcResult<null>> {
-  return await invoke<IpcResult<null>>('settings_save', { payload });
+async function settingsGetSnapshot(): Promise<UsageSnapshotResponse> {
+  return await invoke<UsageSnapshotResponse>('settings_get_snapshot');
+}
+
+async function settingsSave(
+  payload: SaveSettingsPayload,
+): Promise<IpcResult<SaveSettingsResult>> {
+  return await invoke<IpcResult<SaveSettingsResult>>('settings_save', { payload });
 }
 
 async function settingsForgetKey(): Promise<IpcResult<null>> {
@@ -136,15 +144,22 @@ function updateUsageStats(ui: Ui, snapshot: UsageSnapshotBundle | null): void {
     const claude = snapshot.claude;
     if (claude.status === 'ok') {
       const sessionPct = Math.round(claude.sessionPercent);
-      const weeklyPct = Math.round(claude.weeklyPercent);
 
       ui.claudeSessionValueEl.textContent = `${sessionPct}%`;
       ui.claudeSessionBarEl.style.width = `${sessionPct}%`;
       ui.claudeSessionBarEl.className = getProgressBarClass(sessionPct);
 
-      ui.claudeWeeklyValueEl.textContent = `${weeklyPct}%`;
-      ui.claudeWeeklyBarEl.style.width = `${weeklyPct}%`;
-      ui.claudeWeeklyBarEl.className = getProgressBarClass(weeklyPct);
+      if (claude.weeklyPercent === null) {
+        // Weekly bucket reported without utilization: unknown, not 0%.
+        ui.claudeWeeklyValueEl.textContent = '--%';
+        ui.claudeWeeklyBarEl.style.width = '0%';
+        ui.claudeWeeklyBarEl.className = 'progress-bar-fill';
+      } else {
+        const weeklyPct = Math.round(claude.weeklyPercent);
+        ui.claudeWeeklyValueEl.textContent = `${weeklyPct}%`;
+        ui.claudeWeeklyBarEl.style.width = `${weeklyPct}%`;
+        ui.claudeWeeklyBarEl.className = getProgressBarClass(weeklyPct);
+      }
 
       ui.claudeLastUpdatedEl.textContent = `Last updated: ${claude.lastUpdatedAt}`;
     } else {
@@ -169,15 +184,22 @@ function updateUsageStats(ui: Ui, snapshot: UsageSnapshotBundle | null): void {
     const codex = snapshot.codex;
     if (codex.status === 'ok') {
       const sessionPct = Math.round(codex.sessionPercent);
-      const weeklyPct = Math.round(codex.weeklyPercent);
 
       ui.codexSessionValueEl.textContent = `${sessionPct}%`;
       ui.codexSessionBarEl.style.width = `${sessionPct}%`;
       ui.codexSessionBarEl.className = getProgressBarClass(sessionPct);
 
-      ui.codexWeeklyValueEl.textContent = `${weeklyPct}%`;
-      ui.codexWeeklyBarEl.style.width = `${weeklyPct}%`;
-      ui.codexWeeklyBarEl.className = getProgressBarClass(weeklyPct);
+      if (codex.weeklyPercent === null) {
+        // Account only reports a session window.
+        ui.codexWeeklyValueEl.textContent = 'n/a';
+        ui.codexWeeklyBarEl.style.width = '0%';
+        ui.codexWeeklyBarEl.className = 'progress-bar-fill';
+      } else {
+        const weeklyPct = Math.round(codex.weeklyPercent);
+        ui.codexWeeklyValueEl.textContent = `${weeklyPct}%`;
+        ui.codexWeeklyBarEl.style.width = `${weeklyPct}%`;
+        ui.codexWeeklyBarEl.className = getProgressBarClass(weeklyPct);
+      }
 
       ui.codexLastUpdatedEl.textContent = `Last updated: ${codex.lastUpdatedAt}`;
     } else {
@@ -257,6 +279,17 @@ function setResultError(statusBoxEl: HTMLElement, result: IpcResult<unknown>): v
   statusBoxEl.appendChild(errorDiv);
 }
 
+function appendSaveWarning(
+  statusBoxEl: HTMLElement,
+  result: IpcResult<SaveSettingsResult>,
+): void {
+  if (!('value' in result) || !result.value.warning) return;
+  const warningDiv = document.createElement('div');
+  warningDiv.className = 'error';
+  warningDiv.textContent = result.value.warning;
+  statusBoxEl.appendChild(warningDiv);
+}
+
 type Ui = {
   trackClaudeEl: HTMLInputElement;
   trackCodexEl: HTMLInputElement;
@@ -303,17 +336,43 @@ type Ui = {
   // Codex config (in modal)
   codexUsageSourceEl: HTMLSelectElement;
   codexHintEl: HTMLElement;
+  codexCookieSectionEl: HTMLElement;
+  codexCookieEl: HTMLInputElement;
+  rememberCodexCookieEl: HTMLInputElement;
 
   // Global settings
   refreshIntervalEl: HTMLSelectElement;
   notifyResetEl: HTMLInputElement;
   autostartEl: HTMLInputElement;
   updatesStartupEl: HTMLInputElement;
+  trayWarnPercentEl: HTMLInputElement;
+  trayCriticalPercentEl: HTMLInputElement;
 
   forgetKeyButton: HTMLButtonElement;
   statusBoxEl: HTMLElement;
 };
 
+const CODEX_SOURCE_LABELS: Record<CodexUsageSource, string> = {
+  oauth: 'OAuth',
+  web: 'Web (cookie)',
+  cli: 'CLI',
+  auto: 'Auto',
+};
+
+const CODEX_SOURCE_HINTS: Record<CodexUsageSource, string> = {
+  oauth: 'Uses ~/.codex/auth.json',
+  web: 'Uses chatgpt.com cookie',
+  cli: 'Uses local codex CLI',
+  auto: 'OAuth, then cookie, then CLI',
+};
+
+const CODEX_MODAL_HINTS: Record<CodexUsageSource, string> = {
+  oauth: 'Uses your local Codex login (reads ~/.codex/auth.json).',
+  web: 'Uses a chatgpt.com cookie pasted below.',
+  cli: 'Uses the local codex CLI (no network).',
+  auto: 'Tries your local Codex login, then the cookie below, then the codex CLI.',
+};
+
 function applyVisibility(
   ui: Ui,
   trackClaudeEnabled: boolean,
@@ -333,19 +392,19 @@ function applyVisibility(
     claudeSource === 'web' ? 'Uses claude.ai cookie' : 'Uses Claude Code login';
 
   // Update Codex source label
-  ui.codexSourceLabelEl.textContent = codexSource === 'cli' ? 'CLI' : 'OAuth';
-  ui.codexSourceHintEl.textContent =
-    codexSource === 'cli' ? 'Uses local codex CLI' : 'Uses ~/.codex/auth.json';
+  ui.codexSourceLabelEl.textContent = CODEX_SOURCE_LABELS[codexSource];
+  ui.codexSourceHintEl.textContent = CODEX_SOURCE_HINTS[codexSource];
 
   // Modal: web-only section visibility
   ui.webOnlySectionEl.toggleAttribute('hidden', claudeSource !== 'web');
   ui.forgetClaudeKeyButton.toggleAttribute('hidden', claudeSource !== 'web');
 
-  // Codex hint in modal
-  ui.codexHintEl.textContent =
-    codexSource === 'cli'
-      ? 'Uses the local codex CLI (no network).'
-      : 'Uses your local Codex login (reads ~/.codex/auth.json).';
+  // Codex hint and cookie field in modal
+  ui.codexHintEl.textContent = CODEX_MODAL_HINTS[codexSource];
+  ui.codexCookieSectionEl.toggleAttribute(
+    'hidden',
+    codexSource !== 'web' && codexSource !== 'auto',
+  );
 
   ui.forgetKeyButton.toggleAttribute('hidden', true);
 }
@@ -365,15 +424,20 @@ async function loadState(ui: Ui): Promise<SettingsState> {
   );
 
   ui.rememberKeyEl.checked = Boolean(state.rememberSessionKey);
+  ui.rememberCodexCookieEl.checked = Boolean(state.rememberCodexCookie);
   ui.refreshIntervalEl.value = String(state.refreshIntervalSeconds || 60);
   ui.notifyResetEl.checked = state.notifyOnUsageReset ?? false;
   ui.autostartEl.checked = state.autostartEnabled ?? false;
   ui.updatesStartupEl.checked = state.checkUpdatesOnStartup ?? true;
+  ui.trayWarnPercentEl.value = String(state.trayWarnPercent ?? 50);
+  ui.trayCriticalPercentEl.value = String(state.trayCriticalPercent ?? 70);
   renderOrgs(ui.orgSelectEl, state.organizations || [], state.selectedOrganizationId);
 
   ui.rememberKeyEl.disabled = !state.keyringAvailable;
+  ui.rememberCodexCookieEl.disabled = !state.keyringAvailable;
   if (!state.keyringAvailable) {
     ui.rememberKeyEl.checked = false;
+    ui.rememberCodexCookieEl.checked = false;
   }
   ui.claudeStorageHintEl.textContent = state.keyringAvailable
     ? ''
@@ -383,6 +447,7 @@ async function loadState(ui: Ui): Promise<SettingsState> {
   updateUsageStats(ui, state.latestSnapshot);
   setStatus(ui.statusBoxEl, renderSnapshot(state.latestSnapshot));
   ui.sessionKeyEl.value = '';
+  ui.codexCookieEl.value = '';
   return state;
 }
 
@@ -538,6 +603,20 @@ function renderApp(root: HTMLElement): void {
                 <span class="slider"></span>
               </label>
             </div>
+
+            <div class="setting">
+              <div class="setting-text">
+                <label class="setting-title" for="trayWarnPercent">Tray warn at (%)</label>
+              </div>
+              <input type="number" id="trayWarnPercent" class="setting-select" min="0" max="100" />
+            </div>
+
+            <div class="setting">
+              <div class="setting-text">
+                <label class="setting-title" for="trayCriticalPercent">Tray critical above (%)</label>
+              </div>
+              <input type="number" id="trayCriticalPercent" class="setting-select" min="0" max="100" />
+            </div>
           </div>
         </div>
       </div>
@@ -618,10 +697,32 @@ function renderApp(root: HTMLElement): void {
               <label for="codexUsageSource">Usage data source</label>
               <select id="codexUsageSource">
                 <option value="oauth">OAuth (from codex auth.json)</option>
+                <option value="web">Web (chatgpt.com cookie)</option>
                 <option value="cli">CLI (local codex)</option>
+                <option value="auto">Auto (OAuth, then cookie, then CLI)</option>
               </select>
               <div class="hint" id="codexHint"></div>
             </div>
+
+            <div id="codexCookieSection">
+              <div class="row">
+                <label for="codexCookie">Cookie (from chatgpt.com)</label>
+                <input id="codexCookie" type="password" placeholder="__Secure-next-auth.session-token=..." autocomplete="off" />
+                <div class="hint">Leave blank to keep the stored cookie. Never paste this anywhere else.</div>
+              </div>
+
+              <div class="row">
+                <div class="setting">
+                  <div class="setting-text">
+                    <label class="setting-title" for="rememberCodexCookie">Remember Codex cookie</label>
+                  </div>
+                  <label class="toggle-switch toggle-switch-small">
+                    <input type="checkbox" id="rememberCodexCookie" />
+                    <span class="slider"></span>
+                  </label>
+                </div>
+              </div>
+            </div>
           </div>
         </div>
         <div class="modal-footer">
@@ -678,12 +779,17 @@ function renderApp(root: HTMLElement): void {
     // Codex config (in modal)
     codexUsageSourceEl: el<HTMLSelectElement>(root, '#codexUsageSource'),
     codexHintEl: el<HTMLElement>(root, '#codexHint'),
+    codexCookieSectionEl: el<HTMLElement>(root, '#codexCookieSection'),
+    codexCookieEl: el<HTMLInputElement>(root, '#codexCookie'),
+    rememberCodexCookieEl: el<HTMLInputElement>(root, '#rememberCodexCookie'),
 
     // Global settings
     refreshIntervalEl: el<HTMLSelectElement>(root, '#refreshInterval'),
     notifyResetEl: el<HTMLInputElement>(root, '#notifyReset'),
     autostartEl: el<HTMLInputElement>(root, '#autostart'),
     updatesStartupEl: el<HTMLInputElement>(root, '#updatesStartup'),
+    trayWarnPercentEl: el<HTMLInputElement>(root, '#trayWarnPercent'),
+    trayCriticalPercentEl: el<HTMLInputElement>(root, '#trayCriticalPercent'),
 
     forgetKeyButton: el<HTMLButtonElement>(root, '#forgetKey'),
     statusBoxEl: el<HTMLElement>(root, '#statusBox'),
@@ -781,13 +887,18 @@ function renderApp(root: HTMLElement): void {
       notifyOnUsageReset: ui.notifyResetEl.checked,
       autostartEnabled: ui.autostartEl.checked,
       checkUpdatesOnStartup: ui.updatesStartupEl.checked,
+      trayWarnPercent: Number(ui.trayWarnPercentEl.value || 50),
+      trayCriticalPercent: Number(ui.trayCriticalPercentEl.value || 70),
       selectedOrganizationId: selectedOrganizationId ? selectedOrganizationId : null,
+      codexCookie: trackCodexEnabled ? ui.codexCookieEl.value.trim() : '',
+      rememberCodexCookie: ui.rememberCodexCookieEl.checked,
     };
 
     const result = await settingsSave(payload);
     setResultError(ui.statusBoxEl, result);
     if (result.ok) {
       await loadState(ui);
+      appendSaveWarning(ui.statusBoxEl, result);
       closeModal();
     }
   });
@@ -852,13 +963,18 @@ function renderApp(root: HTMLElement): void {
       notifyOnUsageReset: ui.notifyResetEl.checked,
       autostartEnabled: ui.autostartEl.checked,
       checkUpdatesOnStartup: ui.updatesStartupEl.checked,
+      trayWarnPercent: Number(ui.trayWarnPercentEl.value || 50),
+      trayCriticalPercent: Number(ui.trayCriticalPercentEl.value || 70),
       selectedOrganizationId: selectedOrganizationId ? selectedOrganizationId : null,
+      codexCookie: trackCodexEnabled ? ui.codexCookieEl.value.trim() : '',
+      rememberCodexCookie: ui.rememberCodexCookieEl.checked,
     };
 
     const result = await settingsSave(payload);
     setResultError(ui.statusBoxEl, result);
     if (result.ok) {
       await loadState(ui);
+      appendSaveWarning(ui.statusBoxEl, result);
     }
   });
 
@@ -883,6 +999,17 @@ function renderApp(root: HTMLElement): void {
     setStatus(ui.statusBoxEl, renderSnapshot(event.payload));
   });
 
+  // The window is reused between opens: show the cached snapshot right away instead of waiting
+  // for the next refresh tick.
+  window.addEventListener('focus', () => {
+    void settingsGetSnapshot()
+      .then(({ snapshot }) => {
+        updateUsageStats(ui, snapshot);
+        setStatus(ui.statusBoxEl, renderSnapshot(snapshot));
+      })
+      .catch(() => {});
+  });
+
   const versionEl = root.querySelector('.footer-version');
   if (versionEl) {
     void getVersion()